@@ -2,19 +2,38 @@
 //! a final immediate representation of the input program.
 
 use crate::{ stream, parsing };
-//use std::collections::HashMap;
+use std::collections::{ HashMap, HashSet };
 
-pub fn input<T: Iterator<Item=parsing::Statement>>(stmts: T) -> super::Result<Vec<super::Instruction>> {
-    Checker::new(stmts).execute()
+pub fn input(stmts: Vec<parsing::Statement>) -> Result<(Vec<super::Instruction>, Vec<super::Warning>), Vec<super::Failure>> {
+    Checker::new().execute(stmts)
 }
 
-/// Performs scoping and type checking on a stream of parsed statements. Yields
-/// a final lower-level immediate representation of the input program.
-pub struct Checker<T: Iterator<Item=parsing::Statement>> {
-    /// Iterator of statements to be checked.
-    stmts: T,
+/// Instructions, local variable count, optional return type and the span of the
+/// expression returned, and whether every path through the statement returns -
+/// yielded by the evaluation of a statement within a function.
+type InnerStmtInfo = (Vec<super::Instruction>, usize, Option<(super::Type, stream::Span)>, bool);
+
+/// Instructions, local variable count, optional return type and the span of the
+/// first expression returned, and whether every path through the block returns
+/// - yielded by the evaluation of a block.
+type BlockInfo = (Vec<super::Instruction>, usize, Option<(super::Type, stream::Span)>, bool);
+
+/// Performs scoping and type checking on the parsed statements of an entire
+/// program. Yields a final lower-level immediate representation of the input
+/// program.
+pub struct Checker {
     /// Contains all function definitions.
     functions: Vec<super::FunctionDef>,
+    /// Contains all record type definitions.
+    records: Vec<super::RecordDef>,
+    /// Contains all enum type definitions.
+    enums: Vec<super::EnumDef>,
+    /// The scope containing global variables, which is accessible from every
+    /// function regardless of where a given global variable is declared.
+    globals: super::Scope,
+    /// Instructions for initialising global variables in the order they were
+    /// declared. These are executed before the body of the main function.
+    global_initialisation: Vec<super::Instruction>,
     /// The scope stack. The scope at the end of this vector is the inner most
     /// scope at a given point.
     scopes: Vec<super::Scope>,
@@ -22,79 +41,237 @@ pub struct Checker<T: Iterator<Item=parsing::Statement>> {
     id_counter: super::Id,
     /// IDs of local variables that are no longer used (i.e. went out of scope).
     available_local_variable_ids: Vec<super::Id>,
+    /// IDs of variables that are definitely assigned a value along every path
+    /// to the statement currently being checked.
+    initialised_variables: HashSet<super::Id>,
+    /// IDs of variables in scope whose values have been read.
+    read_variables: HashSet<super::Id>,
+    /// IDs of variables in scope that have been assigned a value.
+    assigned_variables: HashSet<super::Id>,
+    /// Labels of the functions called by each function (identified by label).
+    calls: HashMap<String, HashSet<String>>,
+    /// Warnings encountered so far.
+    warnings: Vec<super::Warning>,
+    /// Failures encountered so far.
+    failures: Vec<super::Failure>,
+    /// Label of the function currently being checked, or `None` when checking
+    /// global variable initialisation (which is performed by main).
+    current_function: Option<String>,
+    /// Return type of the function currently being checked.
+    current_return_type: Option<super::Type>,
     /// Has the main function been defined?
     main_defined: bool
 }
 
-impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
-    fn new(stmts: T) -> Self {
+impl Checker {
+    fn new() -> Self {
         Checker {
-            stmts,
-            //global_variables: HashMap::new(),
             functions: Vec::new(),
+            records: Vec::new(),
+            enums: Vec::new(),
+            globals: super::Scope { variables: Vec::new() },
+            global_initialisation: Vec::new(),
             scopes: Vec::new(),
             id_counter: 0,
             available_local_variable_ids: Vec::new(),
+            initialised_variables: HashSet::new(),
+            read_variables: HashSet::new(),
+            assigned_variables: HashSet::new(),
+            calls: HashMap::new(),
+            warnings: Vec::new(),
+            failures: Vec::new(),
+            current_function: None,
+            current_return_type: None,
             main_defined: false
         }
     }
 
     /// Perform scoping and type checking before yielding the final immediate
-    /// representation of the input program. This will consume the `Checker`
-    /// instance.
-    fn execute(mut self) -> super::Result<Vec<super::Instruction>> {
+    /// representation of the input program along with any warnings, or every
+    /// failure encountered. This will consume the `Checker` instance.
+    fn execute(mut self, stmts: Vec<parsing::Statement>) -> Result<(Vec<super::Instruction>, Vec<super::Warning>), Vec<super::Failure>> {
         // Holds the primitive instructions that will make up the final immediate
         // representation of the input program.
         let mut final_ir = Vec::new();
 
-        // Evaluate top-level statements:
-        while let Some(stmt) = self.stmts.next() {
-            let new_instructions = self.eval_top_level_stmt(stmt)?;
+        // Type definitions are evaluated first so that every type may be used
+        // in any function signature:
+        let mut remaining_stmts = Vec::new();
+        for stmt in stmts {
+            match stmt {
+                parsing::Statement::RecordDefinition { .. } | parsing::Statement::EnumDefinition { .. } =>
+                    final_ir.extend(self.check_top_level_stmt(stmt)),
+                _ => remaining_stmts.push(stmt)
+            }
+        }
+
+        // The signature of every function is then collected before any function
+        // body is checked so that a function may be called from anywhere in
+        // the program, regardless of where it is defined. The body of a
+        // function whose signature is invalid is not checked:
+        let mut declared = Vec::new();
+        for stmt in remaining_stmts.iter() {
+            let result = match stmt {
+                parsing::Statement::FunctionDefinition { span, identifier, parameters, return_type, body: _ } =>
+                    self.declare_function(span, identifier, parameters, return_type.as_deref()),
+                _ => Ok(())
+            };
+
+            declared.push(result.is_ok());
+            if let Err(failure) = result { self.failures.push(failure); }
+        }
+
+        // Global variables are then declared (and their initial values checked)
+        // in order so that every function may use any global variable, while
+        // a global variable's initial value may only use those declared before
+        // it:
+        let mut function_defs = Vec::new();
+        for (stmt, is_declared) in remaining_stmts.into_iter().zip(declared) {
+            match stmt {
+                parsing::Statement::FunctionDefinition { .. } => if is_declared { function_defs.push(stmt) },
+                _ => final_ir.extend(self.check_top_level_stmt(stmt))
+            }
+        }
+
+        // Finally, evaluate the body of each function in order:
+        for stmt in function_defs {
+            let new_instructions = self.check_top_level_stmt(stmt);
             final_ir.extend(new_instructions);
         }
 
         assert!(self.scopes.is_empty());
 
-        if self.main_defined { Ok(final_ir) }
-        else { Err(super::Failure::MainUndefined) }
+        if !self.main_defined { self.failures.push(super::Failure::MainUndefined); }
+
+        if !self.failures.is_empty() { return Err(self.failures); }
+
+        self.warn_of_unused_globals();
+        self.warn_of_unused_functions();
+
+        // Insert global variable initialisation at the beginning of the main
+        // function:
+        let main_index = final_ir.iter().position(|instruction| {
+            matches!(instruction, super::Instruction::Function { label, .. } if label == "main")
+        }).unwrap();
+        final_ir.splice(main_index + 1..main_index + 1, self.global_initialisation);
+
+        Ok((final_ir, self.warnings))
+    }
+
+    /// Add a warning for every global variable that is never read (unless its
+    /// identifier begins with an underscore), as is done for local variables
+    /// by `end_scope`.
+    fn warn_of_unused_globals(&mut self) {
+        for def in self.globals.variables.iter() {
+            if !self.read_variables.contains(&def.id) && !def.identifier.starts_with('_') {
+                self.warnings.push(
+                    if self.assigned_variables.contains(&def.id) { super::Warning::VariableNeverRead(def.span.clone(), def.identifier.clone()) }
+                    else { super::Warning::UnusedVariable(def.span.clone(), def.identifier.clone()) }
+                );
+            }
+        }
+    }
+
+    /// Add a warning for every function that is not called by the main
+    /// function, either directly or by way of other functions.
+    fn warn_of_unused_functions(&mut self) {
+        let mut reachable = HashSet::new();
+        let mut to_visit = vec!["main"];
+
+        while let Some(label) = to_visit.pop() {
+            if reachable.insert(label) {
+                if let Some(called) = self.calls.get(label) {
+                    to_visit.extend(called.iter().map(String::as_str));
+                }
+            }
+        }
+
+        for def in self.functions.iter() {
+            if !reachable.contains(def.label.as_str()) && !def.identifier.starts_with('_') {
+                self.warnings.push(super::Warning::UnusedFunction(def.span.clone(), def.identifier.clone(), def.parameter_types.clone()));
+            }
+        }
+    }
+
+    /// Check the signature of a function definition, adding the definition of
+    /// that function so that it may be called. This is done for every function
+    /// before any function bodies are evaluated.
+    fn declare_function(&mut self, span: &stream::Span, identifier: &str, parameters: &[parsing::Parameter], return_type: Option<&str>) -> super::Result<()> {
+        // Create a label for this function ("main" if the main function,
+        // "func" followed by a new ID otherwise):
+        let label = {
+            if identifier == "main" && parameters.is_empty() {
+                self.main_defined = true;
+                identifier.to_string()
+            }
+            else { format!("func{}", self.new_id()) }
+        };
+
+        // Check the declared return type is actually a real type:
+        let checked_return_type = return_type.map(|x| self.resolve_type(x, span)).transpose()?;
+        let param_types = self.resolve_parameter_types(parameters)?;
+
+        // Check if the function already exists:
+        if let Ok(existing_def) = self.function_lookup(identifier, param_types.as_slice(), span) {
+            return Err(super::Failure::RedefinedExistingFunction(Box::new(super::FunctionRedefinition {
+                span: span.clone(), identifier: identifier.to_string(), params: param_types,
+                previous_span: existing_def.span.clone()
+            })))
+        }
+
+        self.add_function_def(span.clone(), identifier.to_string(), param_types, checked_return_type, label);
+
+        Ok(())
+    }
+
+    fn resolve_parameter_types(&self, parameters: &[parsing::Parameter]) -> super::Result<Vec<super::Type>> {
+        parameters.iter().map(|param| self.resolve_type(&param.param_type, &param.span)).collect()
+    }
+
+    /// Evaluate a top-level statement, recording any failure so that checking
+    /// may continue with the next top-level statement. Should a function fail
+    /// to be checked, the state of that function at the point of failure is
+    /// discarded along with any warnings regarding it.
+    fn check_top_level_stmt(&mut self, stmt: parsing::Statement) -> Vec<super::Instruction> {
+        let warnings_count = self.warnings.len();
+
+        self.eval_top_level_stmt(stmt).unwrap_or_else(|failure| {
+            // End every scope left open by the failure so that the IDs of its
+            // variables are released and no longer considered initialised,
+            // read or assigned, discarding any warnings given along the way:
+            while !self.scopes.is_empty() { self.end_scope(); }
+            self.current_function = None;
+            self.current_return_type = None;
+            self.warnings.truncate(warnings_count);
+            self.failures.push(failure);
+            vec![]
+        })
     }
 
     /// Ensure the validity and evaluate a top-level statement (function
-    /// definition expected).
+    /// definition or global variable declaration expected).
     fn eval_top_level_stmt(&mut self, stmt: parsing::Statement) -> super::Result<Vec<super::Instruction>> {
         match stmt {
-            parsing::Statement::FunctionDefinition { pos, identifier, parameters, return_type, body } => {
-                // Create a label for this function ("main" if the main function,
-                // "func" followed by a new ID otherwise):
-                let label = {
-                    if identifier == "main" && parameters.is_empty() {
-                        self.main_defined = true;
-                        identifier.clone()
-                    }
-                    else { format!("func{}", self.new_id()) }
+            parsing::Statement::FunctionDefinition { span, identifier, parameters, return_type: _, body } => {
+                // The signature of the function has already been checked and
+                // its definition added by `declare_function`:
+                let param_types = self.resolve_parameter_types(&parameters)?;
+                let (label, checked_return_type) = {
+                    let def = self.function_lookup(&identifier, &param_types, &span)?;
+                    (def.label.clone(), def.return_type.clone())
                 };
 
-                // Check the declared return type is actually a real type:
-                let checked_return_type = return_type.map(|x| super::Type::from_identifier(&x)).transpose()?;
+                let checked_parameters = parameters.into_iter().zip(param_types.clone()).collect();
 
-                let mut param_types = Vec::new();
-                for param in parameters.iter() {
-                    param_types.push(super::Type::from_identifier(&param.param_type)?);
-                }
-                let checked_parameters = parameters.into_iter().map(|x| x.identifier).zip(param_types.clone().into_iter()).collect();
-
-                // Check if the function already exists:
-                if self.function_lookup(&identifier, param_types.as_slice(), &pos).is_ok() {
-                    return Err(super::Failure::RedefinedExistingFunction(identifier, param_types.to_vec()))
-                }
-                else {
-                    // Create the function definition before evaluating the body
-                    // so as to allow recursion:
-                    self.add_function_def(identifier.clone(), param_types.clone(), checked_return_type.clone(), label.clone());
-                }
+                // Should a path through the function body not return, it will
+                // be after the final statement of that body:
+                let fall_through_span = body.last().map_or(&span, |stmt| stmt.span()).clone();
 
                 // Evaluate the function body:
-                let (body_instructions, local_variable_count, optional_body_return_type) = self.eval_block(body, checked_parameters)?;
+                self.current_function = Some(label.clone());
+                self.current_return_type = checked_return_type.clone();
+                let (body_instructions, local_variable_count, optional_body_return_type, body_always_returns) = self.eval_block(body, checked_parameters)?;
+                self.current_function = None;
 
                 let mut instructions = vec![super::Instruction::Function { label, local_variable_count }];
                 instructions.extend(body_instructions);
@@ -103,35 +280,41 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
                 if let Some(expected_return_type) = checked_return_type {
                     // Function body should return something if a return type
                     // has been specified in the signature:
-                    if let Some(body_return_type) = optional_body_return_type {
+                    if let Some((body_return_type, return_span)) = optional_body_return_type {
                         // Are those types the same?
-                        if body_return_type == expected_return_type { Ok(instructions) }
-                        else {
-                            Err(super::Failure::FunctionUnexpectedReturnType {
-                                pos, identifier, params: param_types.to_vec(),
+                        if body_return_type != expected_return_type {
+                            Err(super::Failure::FunctionUnexpectedReturnType(Box::new(super::UnexpectedReturnType {
+                                span: return_span, identifier, params: param_types.to_vec(),
                                 expected: expected_return_type,
                                 encountered: Some(body_return_type)
-                            })
+                            })))
+                        } // Every path through the body must return a value:
+                        else if !body_always_returns {
+                            Err(super::Failure::FunctionMayNotReturn(Box::new(super::MissingReturn {
+                                span, identifier, params: param_types.to_vec(),
+                                expected: expected_return_type, fall_through_span
+                            })))
                         }
+                        else { Ok(instructions) }
                     } // Function body doesn't return anything:
                     else {
-                        Err(super::Failure::FunctionUnexpectedReturnType {
-                            pos, identifier, params: param_types.to_vec(),
+                        Err(super::Failure::FunctionUnexpectedReturnType(Box::new(super::UnexpectedReturnType {
+                            span, identifier, params: param_types.to_vec(),
                             expected: expected_return_type, encountered: None
-                        })
+                        })))
                     }
                 } // No return type specified in signature:
                 else {
                     // Does function body return something?
-                    if let Some(body_return_type) = optional_body_return_type {
+                    if let Some((body_return_type, return_span)) = optional_body_return_type {
                         Err(super::Failure::VoidFunctionReturnsValue(
-                            pos, identifier, param_types.to_vec(),
-                            body_return_type
+                            return_span, identifier, param_types.to_vec(),
+                            Box::new(body_return_type)
                         ))
                     }
                     else {
                         // Ensure function has final return statement:
-                        if instructions.last() != Some(&super::Instruction::ReturnVoid) {
+                        if !body_always_returns {
                             instructions.push(super::Instruction::ReturnVoid);
                         }
 
@@ -140,36 +323,131 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
                 }
             }
 
-            _ => Err(super::Failure::InvalidTopLevelStatement)
+            parsing::Statement::VariableDeclaration { span, var_type, identifier, value } => {
+                let checked_type = self.resolve_type(&var_type, &span)?;
+                let (var_id, instructions) = self.global_declaration_instructions(identifier, &checked_type, span)?;
+
+                if let Some(initial_value) = value {
+                    let (value_instructions, _) = self.expect_expr_type(initial_value, checked_type)?;
+                    self.global_initialisation.extend(value_instructions);
+                    self.global_initialisation.push(super::Instruction::Store(var_id));
+                    self.assigned_variables.insert(var_id);
+                }
+
+                Ok(instructions)
+            }
+
+            parsing::Statement::InferredDeclaration { span, identifier, value } => {
+                let (value_instructions, checked_type, _) = self.eval_expr(value)?;
+                let (var_id, instructions) = self.global_declaration_instructions(identifier, &checked_type, span)?;
+
+                self.global_initialisation.extend(value_instructions);
+                self.global_initialisation.push(super::Instruction::Store(var_id));
+                self.assigned_variables.insert(var_id);
+
+                Ok(instructions)
+            }
+
+            parsing::Statement::ArrayDeclaration { span, element_type, length, identifier } => {
+                let checked_type = self.array_type(&element_type, length, &span)?;
+
+                let (id, newly_declared) = self.declare_global_variable(identifier, checked_type, span)?;
+
+                if newly_declared { Ok(vec![super::Instruction::GlobalArray { id, length }]) }
+                else {
+                    // Redeclaring an array sets its elements to zero once more:
+                    self.global_initialisation.push(super::Instruction::ClearArray { id, length });
+                    Ok(vec![])
+                }
+            }
+
+            parsing::Statement::RecordDefinition { span, identifier, fields } => {
+                log::trace!("Defining record type '{}'", identifier);
+
+                if self.type_identifier_in_use(&identifier) {
+                    return Err(super::Failure::RedefinedType(span, identifier));
+                }
+
+                let checked_fields = self.check_fields(&identifier, fields)?;
+                self.records.push(super::RecordDef { identifier, fields: checked_fields });
+
+                Ok(vec![])
+            }
+
+            parsing::Statement::EnumDefinition { span, identifier, variants } => {
+                log::trace!("Defining enum type '{}'", identifier);
+
+                if self.type_identifier_in_use(&identifier) {
+                    return Err(super::Failure::RedefinedType(span, identifier));
+                }
+
+                let mut checked_variants: Vec<super::RecordDef> = Vec::new();
+
+                for variant in variants {
+                    // Variants are constructed in the same way as records so
+                    // must not share an identifier with any type or variant:
+                    if variant.identifier == identifier || self.type_identifier_in_use(&variant.identifier) ||
+                       checked_variants.iter().any(|def| def.identifier == variant.identifier) {
+                        return Err(super::Failure::RedefinedType(variant.span, variant.identifier));
+                    }
+
+                    let checked_fields = self.check_fields(&variant.identifier, variant.fields)?;
+                    checked_variants.push(super::RecordDef { identifier: variant.identifier, fields: checked_fields });
+                }
+
+                self.enums.push(super::EnumDef { identifier, variants: checked_variants });
+
+                Ok(vec![])
+            }
+
+            other => Err(super::Failure::InvalidTopLevelStatement(other.span().clone()))
         }
     }
 
     /// Check the validity of a given statement within a function. Returns a
     /// `Result` containing a tuple. The first item in this tuple will be a
     /// vector containing final IR instructions. The second item will be the
-    /// number of local variables declared by the given statement. The final
-    /// item in the tuple will be a return type and stream position should
-    /// the statement be a return statement or an if or while statement with a
-    /// block containing a return statement.
-    fn eval_inner_stmt(&mut self, stmt: parsing::Statement) -> super::Result<(Vec<super::Instruction>, usize, Option<(super::Type, stream::Position)>)> {
+    /// number of local variables declared by the given statement. The third
+    /// item in the tuple will be a return type and the span of the returned
+    /// expression should the statement be a return statement or an if or while statement with a
+    /// block containing a return statement. The final item indicates whether
+    /// every path through the statement ends in a return statement.
+    fn eval_inner_stmt(&mut self, stmt: parsing::Statement) -> super::Result<InnerStmtInfo> {
         match stmt {
-            parsing::Statement::Return(Some(expr)) => {
-                let (mut instructions, ret_type, pos) = self.eval_expr(expr)?;
-                instructions.push(super::Instruction::ReturnValue);
-                Ok((instructions, 0, Some((ret_type, pos))))
+            parsing::Statement::Return(_, Some(expr)) => {
+                let expected = self.current_return_type.clone();
+                let (mut instructions, ret_type, span) = self.eval_expr_expecting(expr, expected.as_ref())?;
+                instructions.push(
+                    if ret_type.is_compound() { super::Instruction::ReturnRecord { size: self.type_size(&ret_type) } }
+                    else { super::Instruction::ReturnValue }
+                );
+                Ok((instructions, 0, Some((ret_type, span)), true))
             }
-            parsing::Statement::Return(None) =>
-                Ok((vec![super::Instruction::ReturnVoid], 0, None)),
-
-            parsing::Statement::Display(expr) => {
-                let (mut instructions, value_type, pos) = self.eval_expr(expr)?;
+            parsing::Statement::Return(_, None) =>
+                Ok((vec![super::Instruction::ReturnVoid], 0, None, true)),
+
+            parsing::Statement::Display(_, expr) => {
+                let (mut instructions, value_type, span) = self.eval_expr(expr)?;
+
+                let value_type = match value_type {
+                    super::Type::Char => super::DisplayType::Char,
+                    super::Type::Int => super::DisplayType::Int,
+                    super::Type::Num => super::DisplayType::Num,
+                    super::Type::Bool => super::DisplayType::Bool,
+                    super::Type::String => super::DisplayType::String,
+                    super::Type::Record(type_identifier) | super::Type::Enum(type_identifier) =>
+                        return Err(super::Failure::CompoundValueNotAllowed { span, type_identifier, usage: "displayed" }),
+                    // Only an element of an array may be displayed:
+                    super::Type::Array(element_type, length) =>
+                        return Err(super::Failure::UnexpectedType { span, expected: *element_type.clone(), encountered: super::Type::Array(element_type, length) })
+                };
                 instructions.push(super::Instruction::Display {
-                    value_type, line_number: pos.line_number
+                    value_type, line_number: span.start.line_number
                 });
-                Ok((instructions, 0, None))
+                Ok((instructions, 0, None, false))
             }
 
-            parsing::Statement::While { condition, block } => {
+            parsing::Statement::While { span: _, condition, block } => {
                 let block_end_id = self.new_id();
                 let start_id = self.new_id();
                 
@@ -178,72 +456,99 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
                     super::Instruction::Label(start_id)
                 ];
 
-                let (block_instructions, block_locals_count, block_ret_type) = self.eval_block(block, vec![])?;
+                let initialised_before = self.initialised_variables.clone();
+
+                let (block_instructions, block_locals_count, block_ret_type, _) = self.eval_block(block, vec![])?;
                 instructions.extend(block_instructions);
                 instructions.push(super::Instruction::Label(block_end_id));
 
-                let (condition_instructions, pos) = self.expect_expr_type(condition, super::Type::Bool)?;
+                // The condition is first evaluated before the loop body and the
+                // body may never be executed, so any assignments made within
+                // the body cannot be relied upon:
+                self.initialised_variables = initialised_before;
+
+                let (condition_instructions, _) = self.expect_expr_type(condition, super::Type::Bool)?;
                 instructions.extend(condition_instructions);
                 instructions.push(super::Instruction::JumpIfTrue(start_id));
 
-                Ok((
-                    instructions, block_locals_count,
-                    block_ret_type.map(|ret_type| (ret_type, pos))
-                ))
+                // The loop body may never be executed so a while statement
+                // cannot be relied upon to return:
+                Ok((instructions, block_locals_count, block_ret_type, false))
             }
 
-            parsing::Statement::If { condition, block } => {
+            parsing::Statement::If { span: _, condition, block, else_block } => {
                 let skip_block_id = self.new_id();
 
-                let (mut instructions, pos) = self.expect_expr_type(condition, super::Type::Bool)?;
+                let (mut instructions, _) = self.expect_expr_type(condition, super::Type::Bool)?;
                 instructions.push(super::Instruction::JumpIfFalse(skip_block_id));
 
-                let (block_instructions, block_locals_count, block_ret_type) = self.eval_block(block, vec![])?;
+                let initialised_before = self.initialised_variables.clone();
+
+                let (block_instructions, mut locals_count, mut ret_type, block_always_returns) = self.eval_block(block, vec![])?;
                 instructions.extend(block_instructions);
 
-                instructions.push(super::Instruction::Label(skip_block_id));
+                let initialised_after_block = std::mem::replace(&mut self.initialised_variables, initialised_before);
 
-                Ok(
-                    if let Some(ret_type) = block_ret_type { (instructions, block_locals_count, Some((ret_type, pos))) }
-                    else { (instructions, block_locals_count, None) }
-                )
-            }
+                let always_returns = match else_block {
+                    Some(else_block) => {
+                        let end_id = self.new_id();
 
-            parsing::Statement::VariableDeclaration { var_type, identifier, value } => {
-                let checked_type = super::Type::from_identifier(&var_type)?;
-                let mut local_variable_count = 0;
-                let mut instructions = Vec::new();
+                        // Skip over the else block once the if block is complete:
+                        instructions.push(super::Instruction::Jump(end_id));
+                        instructions.push(super::Instruction::Label(skip_block_id));
 
-                let var_id = {
-                    // If variable is already defined in this same scope then
-                    // ensure it is being redeclared to the same type:
-                    if let Some(existing_def) = self.get_inner_scope().find_variable_def(&identifier) {
-                        log::trace!("Redeclaring variable '{}' in same scope", identifier);
-
-                        if checked_type != existing_def.var_type {
-                            return Err(
-                                super::Failure::VariableRedeclaredToDifferentType {
-                                    identifier: identifier.to_string(),
-                                    expected: existing_def.var_type.clone(),
-                                    encountered: checked_type
-                                }
-                            );
+                        let (else_instructions, else_locals_count, else_ret_type, else_always_returns) = self.eval_block(else_block, vec![])?;
+                        instructions.extend(else_instructions);
+                        instructions.push(super::Instruction::Label(end_id));
+                        locals_count += else_locals_count;
+
+                        // A variable is only definitely assigned after the if
+                        // statement if it is assigned in both blocks (ignoring
+                        // any block that returns before reaching the end):
+                        if else_always_returns {
+                            self.initialised_variables = initialised_after_block;
+                        }
+                        else if !block_always_returns {
+                            self.initialised_variables.retain(|id| initialised_after_block.contains(id));
                         }
 
-                        existing_def.id
-                    }
-                    else {
-                        log::trace!("Introducing variable '{}' to current scope", identifier);
+                        // Both blocks should return values of the same type:
+                        match (&ret_type, else_ret_type) {
+                            (Some((expected, _)), Some((encountered, span))) if *expected != encountered =>
+                                return Err(super::Failure::UnexpectedType { span, expected: expected.clone(), encountered }),
+                            (None, else_ret_type) => ret_type = else_ret_type,
+                            _ => {}
+                        }
 
-                        let id = self.add_variable_def_to_inner_scope(identifier, checked_type.clone());
-                        
-                        instructions.push(super::Instruction::Local(id));
-                        local_variable_count = 1;
+                        block_always_returns && else_always_returns
+                    }
 
-                        id
+                    None => {
+                        instructions.push(super::Instruction::Label(skip_block_id));
+                        false
                     }
                 };
 
+                Ok((instructions, locals_count, ret_type, always_returns))
+            }
+
+            parsing::Statement::Match { span, expr, arms } => self.eval_match(span, expr, arms),
+
+            parsing::Statement::FunctionCall { span, identifier, args } => {
+                let (mut instructions, option_ret_type, _) = self.eval_function_call(&identifier, args, &span)?;
+
+                // The value returned by the function is unused:
+                if let Some(ret_type) = option_ret_type {
+                    for _ in 0..self.type_size(&ret_type) { instructions.push(super::Instruction::Discard); }
+                }
+
+                Ok((instructions, 0, None, false))
+            }
+
+            parsing::Statement::VariableDeclaration { span, var_type, identifier, value } => {
+                let checked_type = self.resolve_type(&var_type, &span)?;
+                let (var_id, mut instructions, local_variable_count) = self.local_declaration_instructions(identifier, &checked_type, span)?;
+
                 // Ensure initial value expression is of correct type:
                 if let Some(initial_value) = value {
                     let (value_instructions, _) = self.expect_expr_type(initial_value, checked_type)?;
@@ -251,84 +556,304 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
 
                     // Store the initial value in the variable:
                     instructions.push(super::Instruction::Store(var_id));
+                    self.initialised_variables.insert(var_id);
+                    self.assigned_variables.insert(var_id);
                 }
 
-                Ok((instructions, local_variable_count, None))
+                Ok((instructions, local_variable_count, None, false))
             }
 
-            parsing::Statement::VariableAssignment { identifier, assign_to } => {
-                let mut instructions = Vec::new();
+            parsing::Statement::InferredDeclaration { span, identifier, value } => {
+                // The initial value is evaluated before the variable is declared
+                // as its type is needed in order to do so (hence a variable
+                // being shadowed may be referenced in the initial value):
+                let (value_instructions, checked_type, _) = self.eval_expr(value)?;
+                let (var_id, mut instructions, local_variable_count) = self.local_declaration_instructions(identifier, &checked_type, span)?;
 
-                let var_id = {
-                    let (expr_instructions, assign_to_type, strm_pos) = self.eval_expr(assign_to)?;
-                    instructions.extend(expr_instructions);
-                    
-                    let var_def = self.variable_lookup(&identifier, &strm_pos)?;
+                instructions.extend(value_instructions);
+                instructions.push(super::Instruction::Store(var_id));
+                self.initialised_variables.insert(var_id);
+                self.assigned_variables.insert(var_id);
 
-                    if var_def.var_type != assign_to_type {
-                        return Err(super::Failure::UnexpectedType {
-                            pos: strm_pos,
-                            encountered: assign_to_type,
-                            expected: var_def.var_type.clone()
-                        });
-                    }
+                Ok((instructions, local_variable_count, None, false))
+            }
+
+            parsing::Statement::ArrayDeclaration { span, element_type, length, identifier } => {
+                let checked_type = self.array_type(&element_type, length, &span)?;
+
+                let (id, newly_declared) = self.declare_local_variable(identifier, checked_type, span)?;
+
+                // The elements of an array are set to zero when it is declared
+                // so an array may always be read from:
+                self.initialised_variables.insert(id);
 
-                    var_def.id
+                if newly_declared { Ok((vec![super::Instruction::LocalArray { id, length }], length, None, false)) }
+                else { Ok((vec![super::Instruction::ClearArray { id, length }], 0, None, false)) }
+            }
+
+            parsing::Statement::VariableAssignment { span, identifier, assign_to } => {
+                let mut instructions = Vec::new();
+
+                let (var_type, var_id) = {
+                    let var_def = self.variable_lookup(&identifier, &span)?;
+                    (var_def.var_type.clone(), var_def.id)
                 };
 
+                // Arrays may only be assigned to one element at a time:
+                if let super::Type::Array(..) = var_type {
+                    return Err(super::Failure::ArrayNotIndexed(span, identifier));
+                }
+
+                let (expr_instructions, _) = self.expect_expr_type(assign_to, var_type)?;
+                instructions.extend(expr_instructions);
+
                 instructions.push(super::Instruction::Store(var_id));
+                self.initialised_variables.insert(var_id);
+                self.assigned_variables.insert(var_id);
 
                 // A variable assignment modifies a previously declared local
                 // variable so does not increase the local variable count:
-                Ok((instructions, 0, None))
+                Ok((instructions, 0, None, false))
+            }
+
+            parsing::Statement::ElementAssignment { span, identifier, index, assign_to } => {
+                let (element_type, length, id) = self.array_lookup(&identifier, &span)?;
+
+                let (mut instructions, _) = self.expect_expr_type(index, super::Type::Int)?;
+
+                let (value_instructions, _) = self.expect_expr_type(assign_to, element_type)?;
+                instructions.extend(value_instructions);
+
+                instructions.push(super::Instruction::StoreElement { id, length, line_number: span.start.line_number });
+                self.assigned_variables.insert(id);
+
+                Ok((instructions, 0, None, false))
+            }
+
+            parsing::Statement::FieldAssignment { span, identifier, fields, assign_to } => {
+                let (mut record_type, id) = {
+                    let var_def = self.variable_lookup(&identifier, &span)?;
+                    (var_def.var_type.clone(), var_def.id)
+                };
+
+                // Find the type of the field being assigned to and its offset
+                // from the start of the record variable:
+                let mut offset = 0;
+                for field in fields {
+                    let (field_type, field_offset) = self.field_lookup(&record_type, &field, &span)?;
+                    record_type = field_type;
+                    offset += field_offset;
+                }
+
+                let size = self.type_size(&record_type);
+
+                let (mut instructions, _) = self.expect_expr_type(assign_to, record_type)?;
+                instructions.push(super::Instruction::StoreField { id, offset, size });
+                self.assigned_variables.insert(id);
+
+                Ok((instructions, 0, None, false))
             }
 
-            parsing::Statement::FunctionDefinition { pos, identifier, parameters: _, return_type: _, body: _ } =>
-                Err(super::Failure::NestedFunctions(pos, identifier))
+            parsing::Statement::FunctionDefinition { span, identifier, parameters: _, return_type: _, body: _ } =>
+                Err(super::Failure::NestedFunctions(span, identifier)),
+
+            parsing::Statement::RecordDefinition { span, identifier, .. } |
+            parsing::Statement::EnumDefinition { span, identifier, .. } =>
+                Err(super::Failure::NestedTypeDefinition(span, identifier))
         }
     }
 
     /// Iterate over the statements contained in a block, checking each. Should
-    /// a return statement be encountered, the type of the returned expression
-    /// is returned within `Ok((_, _, Some(...)))`. If there are multiple return
+    /// a return statement be encountered, the type and span of the returned
+    /// expression is returned within `Ok((_, _, Some(...)))`. If there are multiple return
     /// statements then it will be ensured that they are all returning the same
     /// type. Also returns the number of local variables created (excluding
-    /// parameters) within the block as the second part of the returned tuple.
-    fn eval_block(&mut self, block: parsing::Block, params: Vec<(String, super::Type)>) -> super::Result<(Vec<super::Instruction>, usize, Option<super::Type>)> {
+    /// parameters) within the block as the second part of the returned tuple,
+    /// and whether every path through the block returns as the final part.
+    fn eval_block(&mut self, block: parsing::Block, params: Vec<(parsing::Parameter, super::Type)>) -> super::Result<BlockInfo> {
         let mut instructions = Vec::new();
 
         self.begin_new_scope();
 
-        for (identifier, param_type) in params.into_iter().rev() {
-            let var_id = self.add_variable_def_to_inner_scope(identifier, param_type);
-            instructions.push(super::Instruction::Parameter(var_id));
+        for (param, param_type) in params.into_iter().rev() {
+            let size = self.type_size(&param_type);
+            let is_compound = param_type.is_compound();
+
+            let var_id = self.add_variable_def_to_inner_scope(param.identifier, param_type, param.span, true);
+            instructions.push(
+                if is_compound { super::Instruction::ParameterRecord { id: var_id, size } }
+                else { super::Instruction::Parameter(var_id) }
+            );
+            self.initialised_variables.insert(var_id);
         }
 
         let mut ret_type = None;
         let mut local_variable_count = 0;
+        let mut always_returns = false;
 
         for stmt in block {
-            let (inner_instructions, inner_locals_count, optional_ret_info) = self.eval_inner_stmt(stmt)?;
+            let (inner_instructions, inner_locals_count, optional_ret_info, stmt_always_returns) = self.eval_inner_stmt(stmt)?;
             instructions.extend(inner_instructions);
             local_variable_count += inner_locals_count;
+            always_returns |= stmt_always_returns;
 
-            if let Some((new, pos)) = optional_ret_info {
+            if let Some((new, span)) = optional_ret_info {
                 // Has a return type already been established for this block?
-                if let Some(current) = &ret_type {
+                if let Some((current, _)) = &ret_type {
                     if new != *current { // Can't have return statements with different types!
                         return Err(super::Failure::UnexpectedType {
-                            pos, expected: current.clone(),
+                            span, expected: current.clone(),
                             encountered: new
                         })
                     }
                 }
-                else { ret_type.replace(new); }
+                else { ret_type.replace((new, span)); }
             }
         }
 
         self.end_scope();
 
-        Ok((instructions, local_variable_count, ret_type))
+        Ok((instructions, local_variable_count, ret_type, always_returns))
+    }
+
+    /// Check a match statement, ensuring that each variant of the enum type of
+    /// the matched value is handled by exactly one arm. The matched value is
+    /// stored in a hidden local variable, with its tag being compared against
+    /// that of the variant of each arm in turn so as to jump to the arm that
+    /// handles it.
+    fn eval_match(&mut self, span: stream::Span, expr: parsing::Expression, arms: Vec<parsing::MatchArm>) -> super::Result<InnerStmtInfo> {
+        let (mut instructions, matched_type, expr_span) = self.eval_expr(expr)?;
+
+        let enum_identifier = match &matched_type {
+            super::Type::Enum(ident) => ident.clone(),
+            _ => return Err(super::Failure::NonEnumMatched(expr_span, matched_type))
+        };
+
+        let variants: Vec<(String, Vec<super::Type>)> = self.enum_lookup(&enum_identifier).unwrap().variants.iter()
+            .map(|variant| (variant.identifier.clone(), variant.fields.iter().map(|(_, field_type)| field_type.clone()).collect()))
+            .collect();
+
+        // Determine the tag of the variant handled by each arm:
+        let mut tags = Vec::new();
+        for arm in arms.iter() {
+            let tag = variants.iter().position(|(ident, _)| *ident == arm.variant).ok_or_else(|| {
+                super::Failure::UndefinedVariant { span: arm.span.clone(), enum_type: enum_identifier.clone(), variant: arm.variant.clone() }
+            })?;
+
+            if tags.contains(&tag) { return Err(super::Failure::DuplicateMatchArm(arm.span.clone(), arm.variant.clone())); }
+            tags.push(tag);
+        }
+
+        let missing: Vec<String> = variants.iter().enumerate()
+            .filter(|(tag, _)| !tags.contains(tag))
+            .map(|(_, (ident, _))| ident.clone())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(super::Failure::NonExhaustiveMatch { span, enum_type: enum_identifier, missing });
+        }
+
+        let size = self.type_size(&matched_type);
+        let matched_id = self.new_id();
+        let mut locals_count = size;
+
+        instructions.push(super::Instruction::LocalRecord { id: matched_id, size });
+        instructions.push(super::Instruction::Store(matched_id));
+
+        let arm_ids: Vec<super::Id> = arms.iter().map(|_| self.new_id()).collect();
+        let end_id = self.new_id();
+
+        // As every variant is handled, one of these jumps is always taken:
+        for (tag, arm_id) in tags.iter().zip(arm_ids.iter()) {
+            instructions.extend(vec![
+                super::Instruction::Push(super::Value::Variable(matched_id)),
+                super::Instruction::Field { record_size: size, offset: 0, size: 1 },
+                super::Instruction::Push(super::Value::Int(*tag as i64)),
+                super::Instruction::Equals,
+                super::Instruction::JumpIfTrue(*arm_id)
+            ]);
+        }
+
+        let initialised_before = self.initialised_variables.clone();
+        let mut initialised_after: Option<HashSet<super::Id>> = None;
+        let mut ret_type: Option<(super::Type, stream::Span)> = None;
+        let mut always_returns = true;
+
+        for ((arm, tag), arm_id) in arms.into_iter().zip(tags).zip(arm_ids) {
+            self.initialised_variables = initialised_before.clone();
+            instructions.push(super::Instruction::Label(arm_id));
+
+            let (variant, field_types) = &variants[tag];
+
+            if arm.bindings.len() != field_types.len() {
+                return Err(super::Failure::MatchArmBindingCount {
+                    span: arm.span, variant: variant.clone(), expected: field_types.len(), encountered: arm.bindings.len()
+                });
+            }
+
+            // Each field of the variant is copied into a variable in a scope
+            // enclosing the block of the arm:
+            self.begin_new_scope();
+
+            let mut offset = 1; // The first value is the tag.
+            for (binding, field_type) in arm.bindings.into_iter().zip(field_types.iter()) {
+                let binding_type = self.resolve_type(&binding.param_type, &binding.span)?;
+
+                if binding_type != *field_type {
+                    return Err(super::Failure::UnexpectedType { span: binding.span, expected: field_type.clone(), encountered: binding_type });
+                }
+
+                let field_size = self.type_size(field_type);
+                let is_compound = binding_type.is_compound();
+                let id = self.add_variable_def_to_inner_scope(binding.identifier, binding_type, binding.span, false);
+
+                instructions.extend(vec![
+                    if is_compound { super::Instruction::LocalRecord { id, size: field_size } }
+                    else { super::Instruction::Local(id) },
+                    super::Instruction::Push(super::Value::Variable(matched_id)),
+                    super::Instruction::Field { record_size: size, offset, size: field_size },
+                    super::Instruction::Store(id)
+                ]);
+                self.initialised_variables.insert(id);
+
+                offset += field_size;
+                locals_count += field_size;
+            }
+
+            let (block_instructions, block_locals_count, block_ret_type, block_always_returns) = self.eval_block(arm.block, vec![])?;
+            instructions.extend(block_instructions);
+            instructions.push(super::Instruction::Jump(end_id));
+            locals_count += block_locals_count;
+
+            self.end_scope();
+
+            // As with an if statement, a variable is only definitely assigned
+            // after the match if it is assigned by every arm that reaches the
+            // end of its block:
+            if !block_always_returns {
+                always_returns = false;
+                initialised_after = Some(match initialised_after {
+                    Some(mut initialised) => {
+                        initialised.retain(|id| self.initialised_variables.contains(id));
+                        initialised
+                    }
+                    None => self.initialised_variables.clone()
+                });
+            }
+
+            // Every arm should return values of the same type:
+            match (&ret_type, block_ret_type) {
+                (Some((expected, _)), Some((encountered, span))) if *expected != encountered =>
+                    return Err(super::Failure::UnexpectedType { span, expected: expected.clone(), encountered }),
+                (None, block_ret_type) => ret_type = block_ret_type,
+                _ => {}
+            }
+        }
+
+        instructions.push(super::Instruction::Label(end_id));
+        self.initialised_variables = initialised_after.unwrap_or(initialised_before);
+
+        Ok((instructions, locals_count, ret_type, always_returns))
     }
 
     /// Introduce a new, inner-most scope which is added to the end of the scope
@@ -338,10 +863,22 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
     }
 
     /// Remove the inner-most scope from the scopes stack and allow for the usage
-    /// of the IDs of all variables that belonged to said scope.
+    /// of the IDs of all variables that belonged to said scope. A warning is
+    /// added for each of those variables that was never read (unless its
+    /// identifier begins with an underscore).
     fn end_scope(&mut self) {
         if let Some(previous_scope) = self.scopes.pop() {
             for def in previous_scope.variables {
+                if !self.read_variables.remove(&def.id) && !def.identifier.starts_with('_') {
+                    self.warnings.push(
+                        if def.parameter { super::Warning::UnusedParameter(def.span, def.identifier) }
+                        else if self.assigned_variables.contains(&def.id) { super::Warning::VariableNeverRead(def.span, def.identifier) }
+                        else { super::Warning::UnusedVariable(def.span, def.identifier) }
+                    );
+                }
+
+                self.assigned_variables.remove(&def.id);
+                self.initialised_variables.remove(&def.id);
                 self.available_local_variable_ids.push(def.id);
             }
         }
@@ -355,200 +892,716 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
 
     /// Search for a definition for a function with a given identifier and set
     /// of parameter types.
-    fn function_lookup(&self, ident: &str, params: &[super::Type], strm_pos: &stream::Position) -> super::Result<&super::FunctionDef> {
+    fn function_lookup(&self, ident: &str, params: &[super::Type], span: &stream::Span) -> super::Result<&super::FunctionDef> {
         for def in self.functions.iter() {
             if def.identifier == ident && def.parameter_types == params {
                 return Ok(def);
             }
         }
-        Err(super::Failure::FunctionUndefined(strm_pos.clone(), ident.to_string(), params.to_vec()))
+        Err(super::Failure::FunctionUndefined(span.clone(), ident.to_string(), params.to_vec()))
     }
 
-    fn add_function_def(&mut self, identifier: String, parameter_types: Vec<super::Type>, return_type: Option<super::Type>, label: String) {
+    fn add_function_def(&mut self, span: stream::Span, identifier: String, parameter_types: Vec<super::Type>, return_type: Option<super::Type>, label: String) {
         self.functions.push(super::FunctionDef {
-            identifier, parameter_types, return_type, label
+            span, identifier, parameter_types, return_type, label
         });
     }
 
     /// Search the current accessible scopes for the variable definition with
-    /// the given identifier.
-    fn variable_lookup(&self, ident: &str, strm_pos: &stream::Position) -> super::Result<&super::VariableDef> {
+    /// the given identifier. Global variables are searched last.
+    fn variable_lookup(&self, ident: &str, span: &stream::Span) -> super::Result<&super::VariableDef> {
         // Reverse the iterator so that the inner most scope has priority (i.e.
         // automatically handle shadowing).
-        for scope in self.scopes.iter().rev() {
+        for scope in self.scopes.iter().rev().chain(std::iter::once(&self.globals)) {
             if let Some(var_def) = scope.find_variable_def(ident) {
                 return Ok(var_def)
             }
         }
-        Err(super::Failure::VariableNotInScope(strm_pos.clone(), ident.to_string()))
-    }
-
-    fn add_variable_def_to_inner_scope(&mut self, identifier: String, var_type: super::Type) -> super::Id {
-        let id = {
-            if let Some(available_id) = self.available_local_variable_ids.pop() { available_id }
-            else { self.new_id() }
-        };
-        
-        self.get_inner_scope().variables.push(super::VariableDef {
-            identifier, var_type, id
-        });
-        
-        id
+        Err(super::Failure::VariableNotInScope(span.clone(), ident.to_string()))
     }
 
-    /// Check the validity of a given expression as well as return the appropriate
-    /// instructions to be inserted into the final IR.
-    fn eval_expr(&self, expr: parsing::Expression) -> super::Result<(Vec<super::Instruction>, super::Type, stream::Position)> {
-        match expr {
-            parsing::Expression::Variable { pos, identifier } => {
-                log::trace!("Searching scope for the type of referenced variable with identifier '{}'", identifier);
-
-                let (var_type, id) = { // TODO: Check if variable is initialised before use!
-                    let def = self.variable_lookup(&identifier, &pos)?;
-                    (def.var_type.clone(), def.id)
-                };
-
-                Ok((
-                    vec![super::Instruction::Push(super::Value::Variable(id))],
-                    var_type, pos
-                ))
+    /// Declare a global variable of the given type, returning its ID and
+    /// whether it was newly introduced. As with local variables, a global
+    /// variable may only be redeclared to the same type.
+    fn declare_global_variable(&mut self, identifier: String, var_type: super::Type, span: stream::Span) -> super::Result<(super::Id, bool)> {
+        if let Some(existing_def) = self.globals.find_variable_def(&identifier) {
+            log::trace!("Redeclaring global variable '{}'", identifier);
+
+            if var_type != existing_def.var_type {
+                return Err(super::Failure::VariableRedeclaredToDifferentType(Box::new(super::VariableRedeclaration {
+                    span, identifier,
+                    expected: existing_def.var_type.clone(),
+                    encountered: var_type,
+                    previous_span: existing_def.span.clone()
+                })));
             }
 
-            parsing::Expression::FunctionCall {pos, identifier, args } => {
-                log::trace!("Searching scope for the return type of referenced function '{}' given arguments {:?}", identifier, args);
-
-                let mut instructions = Vec::new();
-
-                let mut arg_types = Vec::new();
-                for arg in args {
-                    let (arg_instructions, arg_type, _) = self.eval_expr(arg)?;
+            Ok((existing_def.id, false))
+        }
+        else {
+            log::trace!("Introducing global variable '{}'", identifier);
 
-                    instructions.extend(arg_instructions);
-                    arg_types.push(arg_type); 
-                }
+            let id = self.new_id();
+            self.globals.variables.push(super::VariableDef { identifier, var_type, id, span, parameter: false });
 
-                let (ident, option_ret_type, label) = {
-                    let def = self.function_lookup(&identifier, arg_types.as_slice(), &pos)?;
-                    (def.identifier.clone(), def.return_type.clone(), def.label.clone())
-                };
+            // Global variables are zero-initialised so may always be read from:
+            self.initialised_variables.insert(id);
 
-                instructions.push(
-                    if option_ret_type.is_some() { super::Instruction::CallExpectingValue(label) }
-                    else { super::Instruction::CallExpectingVoid(label) }
-                );
+            Ok((id, true))
+        }
+    }
 
-                match option_ret_type {
-                    Some(ret_type) => Ok((instructions, ret_type, pos)),
-                    None => Err(super::Failure::VoidFunctionInExpr(pos, ident, arg_types))
-                }
+    /// Declare a variable of the given type in the inner-most scope, returning
+    /// its ID and whether it was newly introduced. If the variable is already
+    /// defined in that same scope then it is ensured that it is being
+    /// redeclared to the same type.
+    fn declare_local_variable(&mut self, identifier: String, var_type: super::Type, span: stream::Span) -> super::Result<(super::Id, bool)> {
+        if let Some(existing_def) = self.get_inner_scope().find_variable_def(&identifier) {
+            log::trace!("Redeclaring variable '{}' in same scope", identifier);
+
+            if var_type != existing_def.var_type {
+                return Err(super::Failure::VariableRedeclaredToDifferentType(Box::new(super::VariableRedeclaration {
+                    span, identifier,
+                    expected: existing_def.var_type.clone(),
+                    encountered: var_type,
+                    previous_span: existing_def.span.clone()
+                })));
             }
 
-            parsing::Expression::Add(l, r) => {
-                let (instructions, pos) = self.eval_arithmetic_expr(*l, *r, super::Instruction::Add, "addition")?;
-                Ok((instructions, super::Type::Num, pos))
-            }
+            Ok((existing_def.id, false))
+        }
+        else {
+            log::trace!("Introducing variable '{}' to current scope", identifier);
 
-            parsing::Expression::Subtract(l, r) => {
-                let (instructions, pos) = self.eval_arithmetic_expr(*l, *r, super::Instruction::Subtract, "subtraction")?;
-                Ok((instructions, super::Type::Num, pos))
-            }
+            let id = self.add_variable_def_to_inner_scope(identifier, var_type, span, false);
+            self.initialised_variables.remove(&id);
 
-            parsing::Expression::Multiply(l, r) => {
-                let (instructions, pos) = self.eval_arithmetic_expr(*l, *r, super::Instruction::Multiply, "multiplication")?;
-                Ok((instructions, super::Type::Num, pos))
-            }
+            Ok((id, true))
+        }
+    }
 
-            parsing::Expression::Divide(l, r) => {
-                let (instructions, pos) = self.eval_arithmetic_expr(*l, *r, super::Instruction::Divide, "divide")?;
-                Ok((instructions, super::Type::Num, pos))
-            }
+    /// Declare a global variable of the given type, returning its ID and the
+    /// instructions required to create it should it be newly introduced.
+    fn global_declaration_instructions(&mut self, identifier: String, var_type: &super::Type, span: stream::Span) -> super::Result<(super::Id, Vec<super::Instruction>)> {
+        let (var_id, newly_declared) = self.declare_global_variable(identifier, var_type.clone(), span)?;
+        let mut instructions = Vec::new();
 
-            parsing::Expression::GreaterThan(l, r) => {
-                let (instructions, pos) = self.eval_arithmetic_expr(*l, *r, super::Instruction::GreaterThan, "greater than")?;
-                Ok((instructions, super::Type::Bool, pos))
-            }
+        if newly_declared {
+            instructions.push(
+                if var_type.is_compound() { super::Instruction::GlobalRecord { id: var_id, size: self.type_size(var_type) } }
+                else { super::Instruction::Global(var_id) }
+            );
+        }
 
-            parsing::Expression::LessThan(l, r) => {
-                let (instructions, pos) = self.eval_arithmetic_expr(*l, *r, super::Instruction::LessThan, "less than")?;
-                Ok((instructions, super::Type::Bool, pos))
-            }
+        Ok((var_id, instructions))
+    }
 
-            parsing::Expression::Equal(left, right) => {
-                log::trace!("Verifying types of equality expression - types on both sides of the operator should be the same");
+    /// Declare a local variable of the given type, returning its ID, the
+    /// instructions required to create it, and the number of values of stack
+    /// space it requires (both of which are empty should it not be newly
+    /// introduced).
+    fn local_declaration_instructions(&mut self, identifier: String, var_type: &super::Type, span: stream::Span) -> super::Result<(super::Id, Vec<super::Instruction>, usize)> {
+        let (var_id, newly_declared) = self.declare_local_variable(identifier, var_type.clone(), span)?;
+        let mut instructions = Vec::new();
+        let mut local_variable_count = 0;
 
-                let (mut instructions, left_type, strm_pos) = self.eval_expr(*left)?;
-                let (right_instructions, right_type, _) = self.eval_expr(*right)?;
+        if newly_declared {
+            local_variable_count = self.type_size(var_type);
 
-                if left_type == right_type {
-                    instructions.extend(right_instructions);
-                    instructions.push(super::Instruction::Equals);
+            if var_type.is_compound() {
+                instructions.push(super::Instruction::LocalRecord { id: var_id, size: local_variable_count });
 
-                    Ok((instructions, super::Type::Bool, strm_pos))
-                }
-                else {
-                    Err(super::Failure::UnexpectedType {
-                        pos: strm_pos,
-                        expected: left_type,
-                        encountered: right_type
-                    })
-                }
+                // As with arrays, the fields of a record are set to zero when it
+                // is declared (so an enum value is initially the first variant
+                // of that enum):
+                self.initialised_variables.insert(var_id);
             }
+            else { instructions.push(super::Instruction::Local(var_id)); }
+        }
 
-            parsing::Expression::BooleanNot(expr) => {
-                log::trace!("Verifying type of expression to which boolean NOT operator is being applied - expecting Bool expression to right of operator");
+        Ok((var_id, instructions, local_variable_count))
+    }
 
-                let (mut instructions, strm_pos) = self.expect_expr_type(*expr, super::Type::Bool)?;
-                instructions.push(super::Instruction::Not);
+    /// Get the type with the given identifier, being either a primitive type or
+    /// a previously defined record or enum type.
+    fn type_lookup(&self, ident: &str) -> Option<super::Type> {
+        super::Type::from_identifier(ident).or_else(|| {
+            if self.record_lookup(ident).is_some() { Some(super::Type::Record(ident.to_string())) }
+            else if self.enum_lookup(ident).is_some() { Some(super::Type::Enum(ident.to_string())) }
+            else { None }
+        })
+    }
 
-                Ok((instructions, super::Type::Bool, strm_pos))
-            }
+    /// As with `type_lookup`, but failing should no such type exist. The given
+    /// span is that of the source code in which the type identifier is used.
+    fn resolve_type(&self, ident: &str, span: &stream::Span) -> super::Result<super::Type> {
+        self.type_lookup(ident).ok_or_else(|| super::Failure::NonexistentPrimitiveType(span.clone(), ident.to_string()))
+    }
 
-            parsing::Expression::UnaryMinus(expr) => {
-                log::trace!("Verify type of expression to which unary minus is being applied - expecting Num");
+    /// Whether the given identifier is that of an existing type or enum variant.
+    fn type_identifier_in_use(&self, ident: &str) -> bool {
+        self.type_lookup(ident).is_some() || self.variant_lookup(ident).is_some()
+    }
 
-                let mut instructions = vec![super::Instruction::Push(super::Value::Num(0.0))];
-                
-                let (contained_instructions, strm_pos) = self.expect_expr_type(*expr, super::Type::Num)?;
-                instructions.extend(contained_instructions);
-                
-                instructions.push(super::Instruction::Subtract);
+    /// Check the fields of a record type or enum variant with the given
+    /// identifier, ensuring there are no duplicate field names.
+    fn check_fields(&self, identifier: &str, fields: Vec<parsing::Field>) -> super::Result<Vec<(String, super::Type)>> {
+        let mut checked_fields: Vec<(String, super::Type)> = Vec::new();
 
-                Ok((instructions, super::Type::Num, strm_pos))
+        for field in fields {
+            if checked_fields.iter().any(|(existing, _)| *existing == field.identifier) {
+                return Err(super::Failure::DuplicateField {
+                    span: field.span, type_identifier: identifier.to_string(), field: field.identifier
+                });
             }
 
-            parsing::Expression::NumberLiteral {pos, value } => 
-                Ok((vec![super::Instruction::Push(super::Value::Num(value))], super::Type::Num, pos)),
+            // Only previously defined types may be used as the type of a field
+            // (so a type can never contain itself):
+            let field_type = self.resolve_type(&field.field_type, &field.span)?;
+            checked_fields.push((field.identifier, field_type));
+        }
 
-            parsing::Expression::BooleanLiteral { pos, value } =>
-                Ok((vec![super::Instruction::Push(super::Value::Bool(value))], super::Type::Bool, pos)),
+        Ok(checked_fields)
+    }
 
-            parsing::Expression::CharLiteral { pos, value } =>
-                Ok((vec![super::Instruction::Push(super::Value::Char(value))], super::Type::Char, pos))
+    /// Get the type of an array with elements of the given type, which must be
+    /// a primitive type.
+    fn array_type(&self, element_type: &str, length: usize, span: &stream::Span) -> super::Result<super::Type> {
+        if length > super::MAX_ARRAY_LENGTH {
+            return Err(super::Failure::ArrayTooLong(span.clone(), length));
         }
-    }
 
-    /// Ensure the two sub-expressions of an arithmetic expression are both of
-    /// Num type. Insert the relevant final IR instruction also.
-    fn eval_arithmetic_expr(&self, left: parsing::Expression, right: parsing::Expression, operation_instruction: super::Instruction, expr_type: &str) -> super::Result<(Vec<super::Instruction>, stream::Position)> {
-        log::trace!("Verifying types of {} expression - Num type on both sides of operator expected", expr_type);
+        match self.resolve_type(element_type, span)? {
+            checked_type if checked_type.is_compound() =>
+                Err(super::Failure::NonPrimitiveArrayElement(span.clone(), element_type.to_string())),
+            checked_type => Ok(super::Type::Array(Box::new(checked_type), length))
+        }
+    }
 
-        let (mut instructions, strm_pos) = self.expect_expr_type(left, super::Type::Num)?;
-        let (right_instructions, _) = self.expect_expr_type(right, super::Type::Num)?;
+    fn record_lookup(&self, ident: &str) -> Option<&super::RecordDef> {
+        self.records.iter().find(|def| def.identifier == ident)
+    }
 
-        instructions.extend(right_instructions);
-        instructions.push(operation_instruction);
+    fn enum_lookup(&self, ident: &str) -> Option<&super::EnumDef> {
+        self.enums.iter().find(|def| def.identifier == ident)
+    }
 
-        Ok((instructions, strm_pos))
+    /// Search for the enum variant with the given identifier, returning the
+    /// definition of the enum it belongs to and its tag.
+    fn variant_lookup(&self, ident: &str) -> Option<(&super::EnumDef, usize)> {
+        self.enums.iter().find_map(|def| {
+            def.variants.iter().position(|variant| variant.identifier == ident).map(|tag| (def, tag))
+        })
     }
 
-    fn expect_expr_type(&self, expr: parsing::Expression, expected: super::Type) -> super::Result<(Vec<super::Instruction>, stream::Position)> {
-        let (instructions, expr_type, strm_pos) = self.eval_expr(expr)?;
-        
-        if expr_type == expected { Ok((instructions, strm_pos)) }
-        else {
-            Err(super::Failure::UnexpectedType {
-                pos: strm_pos, expected, encountered: expr_type
-            }) 
+    /// Get the type of the field with the given identifier of a value of the
+    /// given record type, and the number of values between the start of the
+    /// record and that field.
+    fn field_lookup(&self, record_type: &super::Type, field: &str, span: &stream::Span) -> super::Result<(super::Type, usize)> {
+        let undefined_field = || super::Failure::UndefinedField {
+            span: span.clone(), record_type: record_type.clone(), field: field.to_string()
+        };
+
+        let def = match record_type {
+            super::Type::Record(ident) => self.record_lookup(ident).unwrap(),
+            _ => return Err(undefined_field())
+        };
+
+        let mut offset = 0;
+        for (field_identifier, field_type) in &def.fields {
+            if field_identifier == field { return Ok((field_type.clone(), offset)) }
+            offset += self.type_size(field_type);
+        }
+
+        Err(undefined_field())
+    }
+
+    /// Get the number of values occupied by a value of the given type.
+    fn type_size(&self, of_type: &super::Type) -> usize {
+        match of_type {
+            super::Type::Array(_, length) => *length,
+            super::Type::Record(ident) => self.fields_size(&self.record_lookup(ident).unwrap().fields),
+            // The tag is followed by enough values for the fields of the
+            // largest variant:
+            super::Type::Enum(ident) => {
+                1 + self.enum_lookup(ident).unwrap().variants.iter().map(|variant| self.fields_size(&variant.fields)).max().unwrap_or(0)
+            }
+            _ => 1
+        }
+    }
+
+    fn fields_size(&self, fields: &[(String, super::Type)]) -> usize {
+        fields.iter().map(|(_, field_type)| self.type_size(field_type)).sum()
+    }
+
+    /// Search for the array with the given identifier, returning the type of
+    /// its elements, its length, and its ID.
+    fn array_lookup(&self, ident: &str, span: &stream::Span) -> super::Result<(super::Type, usize, super::Id)> {
+        let def = self.variable_lookup(ident, span)?;
+
+        match &def.var_type {
+            super::Type::Array(element_type, length) => Ok(((**element_type).clone(), *length, def.id)),
+            other => Err(super::Failure::NonArrayIndexed(span.clone(), ident.to_string(), other.clone()))
+        }
+    }
+
+    fn add_variable_def_to_inner_scope(&mut self, identifier: String, var_type: super::Type, span: stream::Span, parameter: bool) -> super::Id {
+        let id = {
+            if let Some(available_id) = self.available_local_variable_ids.pop() { available_id }
+            else { self.new_id() }
+        };
+        
+        self.get_inner_scope().variables.push(super::VariableDef {
+            identifier, var_type, id, span, parameter
+        });
+        
+        id
+    }
+
+    /// Check the validity of a given expression as well as return the appropriate
+    /// instructions to be inserted into the final IR.
+    fn eval_expr(&mut self, expr: parsing::Expression) -> super::Result<(Vec<super::Instruction>, super::Type, stream::Span)> {
+        self.eval_expr_expecting(expr, None)
+    }
+
+    /// Check the validity of a given expression where a value of the given type
+    /// is expected (if any). An expression made up of only integer literals and
+    /// arithmetic operators is of type Int unless a Num is expected, in which
+    /// case each integer literal is evaluated as a Num (so `7 / 2` is either 3
+    /// or 3.5). The type of the expression is not otherwise required to be the
+    /// expected type.
+    fn eval_expr_expecting(&mut self, expr: parsing::Expression, expected: Option<&super::Type>) -> super::Result<(Vec<super::Instruction>, super::Type, stream::Span)> {
+        match expr {
+            parsing::Expression::Variable { span, identifier } => {
+                log::trace!("Searching scope for the type of referenced variable with identifier '{}'", identifier);
+
+                let (var_type, id) = {
+                    let def = self.variable_lookup(&identifier, &span)?;
+                    (def.var_type.clone(), def.id)
+                };
+
+                if let super::Type::Array(..) = var_type {
+                    return Err(super::Failure::ArrayNotIndexed(span, identifier));
+                }
+
+                // Ensure the variable is assigned a value along every path to
+                // this point:
+                if !self.initialised_variables.contains(&id) {
+                    return Err(super::Failure::UseOfUninitialisedVariable(span, identifier));
+                }
+                self.read_variables.insert(id);
+
+                Ok((
+                    vec![super::Instruction::Push(super::Value::Variable(id))],
+                    var_type, span
+                ))
+            }
+
+            parsing::Expression::FunctionCall {span, identifier, args } => {
+                let (instructions, option_ret_type, arg_types) = self.eval_function_call(&identifier, args, &span)?;
+
+                match option_ret_type {
+                    Some(ret_type) => Ok((instructions, ret_type, span)),
+                    None => Err(super::Failure::VoidFunctionInExpr(span, identifier, arg_types))
+                }
+            }
+
+            parsing::Expression::Index { span, identifier, index } => {
+                let (element_type, length, id) = self.array_lookup(&identifier, &span)?;
+
+                let (mut instructions, _) = self.expect_expr_type(*index, super::Type::Int)?;
+                instructions.push(super::Instruction::PushElement { id, length, line_number: span.start.line_number });
+                self.read_variables.insert(id);
+
+                Ok((instructions, element_type, span))
+            }
+
+            parsing::Expression::Construction { span, type_identifier, args } => {
+                // Construction of an enum value from values for each of the
+                // fields of one of its variants:
+                if let Some((def, tag)) = self.variant_lookup(&type_identifier) {
+                    let enum_type = super::Type::Enum(def.identifier.clone());
+                    let field_types: Vec<super::Type> = def.variants[tag].fields.iter()
+                        .map(|(_, field_type)| field_type.clone()).collect();
+
+                    if args.len() != field_types.len() {
+                        return Err(super::Failure::ConstructionArgumentCount {
+                            span, type_identifier, expected: field_types.len(), encountered: args.len()
+                        });
+                    }
+
+                    log::trace!("Verifying types of the fields of enum variant '{}' being constructed", type_identifier);
+
+                    let mut instructions = vec![super::Instruction::Push(super::Value::Int(tag as i64))];
+                    let mut size = 1;
+
+                    for (arg, field_type) in args.into_iter().zip(field_types) {
+                        size += self.type_size(&field_type);
+                        instructions.extend(self.expect_expr_type(arg, field_type)?.0);
+                    }
+
+                    // Variants with fewer fields than the largest variant are
+                    // padded with zeroes:
+                    for _ in size..self.type_size(&enum_type) {
+                        instructions.push(super::Instruction::Push(super::Value::Int(0)));
+                    }
+
+                    return Ok((instructions, enum_type, span));
+                }
+
+                let to = self.resolve_type(&type_identifier, &span)?;
+
+                // Construction of a record from values for each of its fields:
+                if let super::Type::Record(_) = to {
+                    let field_types: Vec<super::Type> = self.record_lookup(&type_identifier).unwrap().fields.iter()
+                        .map(|(_, field_type)| field_type.clone()).collect();
+
+                    if args.len() != field_types.len() {
+                        return Err(super::Failure::ConstructionArgumentCount {
+                            span, type_identifier, expected: field_types.len(), encountered: args.len()
+                        });
+                    }
+
+                    log::trace!("Verifying types of the fields of record '{}' being constructed", type_identifier);
+
+                    let mut instructions = Vec::new();
+                    for (arg, field_type) in args.into_iter().zip(field_types) {
+                        instructions.extend(self.expect_expr_type(arg, field_type)?.0);
+                    }
+
+                    return Ok((instructions, to, span));
+                }
+
+                // Otherwise, conversion of a single value to a primitive type:
+                if args.len() != 1 {
+                    return Err(super::Failure::ConstructionArgumentCount {
+                        span, type_identifier, expected: 1, encountered: args.len()
+                    });
+                }
+                let expr = args.into_iter().next().unwrap();
+
+                log::trace!("Verifying type of expression being converted to {:?}", to);
+
+                let (mut instructions, from, _) = self.eval_expr(expr)?;
+
+                match conversion_instructions(&from, &to, span.start.line_number) {
+                    Some(conversion) => {
+                        instructions.extend(conversion);
+                        Ok((instructions, to, span))
+                    }
+                    None => Err(super::Failure::InvalidConversion { span, from, to })
+                }
+            }
+
+            parsing::Expression::FieldAccess { span, record, field } => {
+                let (mut instructions, record_type, _) = self.eval_expr(*record)?;
+
+                let (field_type, offset) = self.field_lookup(&record_type, &field, &span)?;
+
+                instructions.push(super::Instruction::Field {
+                    record_size: self.type_size(&record_type),
+                    offset,
+                    size: self.type_size(&field_type)
+                });
+
+                Ok((instructions, field_type, span))
+            }
+
+            parsing::Expression::Add(span, l, r) => {
+                let (instructions, operand_type) = self.eval_arithmetic_expr(*l, *r, expected, super::Instruction::Add, super::Instruction::IntAdd, "addition")?;
+                Ok((instructions, operand_type, span))
+            }
+
+            parsing::Expression::Subtract(span, l, r) => {
+                let (instructions, operand_type) = self.eval_arithmetic_expr(*l, *r, expected, super::Instruction::Subtract, super::Instruction::IntSubtract, "subtraction")?;
+                Ok((instructions, operand_type, span))
+            }
+
+            parsing::Expression::Multiply(span, l, r) => {
+                let (instructions, operand_type) = self.eval_arithmetic_expr(*l, *r, expected, super::Instruction::Multiply, super::Instruction::IntMultiply, "multiplication")?;
+                Ok((instructions, operand_type, span))
+            }
+
+            parsing::Expression::Divide(span, l, r) => {
+                let (instructions, operand_type) = self.eval_arithmetic_expr(*l, *r, expected, super::Instruction::Divide, super::Instruction::IntDivide { line_number: span.start.line_number }, "divide")?;
+                Ok((instructions, operand_type, span))
+            }
+
+            parsing::Expression::Modulo(span, l, r) => {
+                let (instructions, operand_type) = self.eval_arithmetic_expr(*l, *r, expected, super::Instruction::Modulo, super::Instruction::IntModulo { line_number: span.start.line_number }, "modulo")?;
+                Ok((instructions, operand_type, span))
+            }
+
+            parsing::Expression::Power(span, l, r) => {
+                let (instructions, operand_type) = self.eval_arithmetic_expr(*l, *r, expected, super::Instruction::Power, super::Instruction::IntPower, "power")?;
+                Ok((instructions, operand_type, span))
+            }
+
+            parsing::Expression::GreaterThan(span, l, r) => {
+                let (instructions, _) = self.eval_arithmetic_expr(*l, *r, None, super::Instruction::GreaterThan, super::Instruction::IntGreaterThan, "greater than")?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::LessThan(span, l, r) => {
+                let (instructions, _) = self.eval_arithmetic_expr(*l, *r, None, super::Instruction::LessThan, super::Instruction::IntLessThan, "less than")?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::GreaterThanOrEqual(span, l, r) => {
+                let (instructions, _) = self.eval_arithmetic_expr(*l, *r, None, super::Instruction::GreaterThanOrEqual, super::Instruction::IntGreaterThanOrEqual, "greater than or equal")?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::LessThanOrEqual(span, l, r) => {
+                let (instructions, _) = self.eval_arithmetic_expr(*l, *r, None, super::Instruction::LessThanOrEqual, super::Instruction::IntLessThanOrEqual, "less than or equal")?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::Equal(span, left, right) => {
+                let instructions = self.eval_equality_expr(&span, *left, *right, false)?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::NotEqual(span, left, right) => {
+                let instructions = self.eval_equality_expr(&span, *left, *right, true)?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::And(span, left, right) => {
+                log::trace!("Verifying types of logical and expression - expecting Bool expressions on both sides of the operator");
+
+                // Right side is only evaluated should the left side be true:
+                let instructions = self.eval_short_circuit_expr(*left, *right, false)?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::Or(span, left, right) => {
+                log::trace!("Verifying types of logical or expression - expecting Bool expressions on both sides of the operator");
+
+                // Right side is only evaluated should the left side be false:
+                let instructions = self.eval_short_circuit_expr(*left, *right, true)?;
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::BooleanNot(span, expr) => {
+                log::trace!("Verifying type of expression to which boolean NOT operator is being applied - expecting Bool expression to right of operator");
+
+                let (mut instructions, _) = self.expect_expr_type(*expr, super::Type::Bool)?;
+                instructions.push(super::Instruction::Not);
+
+                Ok((instructions, super::Type::Bool, span))
+            }
+
+            parsing::Expression::UnaryMinus(span, expr) => {
+                log::trace!("Verify type of expression to which unary minus is being applied - expecting Num or Int");
+
+                let (contained_instructions, expr_type, expr_span) = self.eval_expr_expecting(*expr, expected)?;
+
+                // Subtract the value of the expression from zero:
+                let (zero, subtract_instruction) = match expr_type {
+                    super::Type::Num => (super::Value::Num(0.0), super::Instruction::Subtract),
+                    super::Type::Int => (super::Value::Int(0), super::Instruction::IntSubtract),
+                    _ => return Err(super::Failure::UnexpectedType {
+                        span: expr_span, expected: super::Type::Num, encountered: expr_type
+                    })
+                };
+
+                let mut instructions = vec![super::Instruction::Push(zero)];
+                instructions.extend(contained_instructions);
+                instructions.push(subtract_instruction);
+
+                Ok((instructions, expr_type, span))
+            }
+
+            parsing::Expression::IntegerLiteral { span, value } => {
+                if expected == Some(&super::Type::Num) {
+                    Ok((vec![super::Instruction::Push(super::Value::Num(value as f64))], super::Type::Num, span))
+                }
+                else { Ok((vec![super::Instruction::Push(super::Value::Int(value))], super::Type::Int, span)) }
+            }
+
+            parsing::Expression::NumberLiteral {span, value } => 
+                Ok((vec![super::Instruction::Push(super::Value::Num(value))], super::Type::Num, span)),
+
+            parsing::Expression::BooleanLiteral { span, value } =>
+                Ok((vec![super::Instruction::Push(super::Value::Bool(value))], super::Type::Bool, span)),
+
+            parsing::Expression::CharLiteral { span, value } =>
+                Ok((vec![super::Instruction::Push(super::Value::Char(value))], super::Type::Char, span)),
+
+            parsing::Expression::StringLiteral { span, value } =>
+                Ok((vec![super::Instruction::Push(super::Value::String(value))], super::Type::String, span))
+        }
+    }
+
+    /// Check a call to a function with the given arguments, returning the
+    /// instructions to evaluate those arguments and make the call, the return
+    /// type of the function called (if any), and the types of the arguments.
+    fn eval_function_call(&mut self, identifier: &str, args: Vec<parsing::Expression>, span: &stream::Span) -> super::Result<(Vec<super::Instruction>, Option<super::Type>, Vec<super::Type>)> {
+        log::trace!("Searching scope for the return type of referenced function '{}' given arguments {:?}", identifier, args);
+
+        let mut args_instructions = Vec::new();
+        let mut arg_types = Vec::new();
+        let mut arg_literal_exprs = Vec::new();
+
+        for arg in args {
+            arg_literal_exprs.push(if is_integer_literal_expr(&arg) { Some(arg.clone()) } else { None });
+
+            let (arg_instructions, arg_type, _) = self.eval_expr(arg)?;
+            args_instructions.push(arg_instructions);
+            arg_types.push(arg_type);
+        }
+
+        // Should no function have parameter types exactly matching the argument
+        // types, integer literal arguments may be passed as Num parameters:
+        if self.function_lookup(identifier, arg_types.as_slice(), span).is_err() {
+            let matching_param_types = self.functions.iter().find(|def| {
+                def.identifier == identifier && def.parameter_types.len() == arg_types.len() &&
+                def.parameter_types.iter().zip(&arg_types).zip(&arg_literal_exprs).all(|((param_type, arg_type), literal_expr)| {
+                    param_type == arg_type || (*param_type == super::Type::Num && literal_expr.is_some())
+                })
+            }).map(|def| def.parameter_types.clone());
+
+            if let Some(param_types) = matching_param_types {
+                for (i, literal_expr) in arg_literal_exprs.into_iter().enumerate() {
+                    if let (Some(expr), super::Type::Num) = (literal_expr, &param_types[i]) {
+                        args_instructions[i] = self.expect_expr_type(expr, super::Type::Num)?.0;
+                        arg_types[i] = super::Type::Num;
+                    }
+                }
+            }
+        }
+
+        let mut instructions: Vec<super::Instruction> = args_instructions.into_iter().flatten().collect();
+
+        let (option_ret_type, label) = {
+            let def = self.function_lookup(identifier, arg_types.as_slice(), span)?;
+            (def.return_type.clone(), def.label.clone())
+        };
+
+        let caller = self.current_function.clone().unwrap_or_else(|| "main".to_string());
+        self.calls.entry(caller).or_default().insert(label.clone());
+
+        instructions.push(match &option_ret_type {
+            Some(ret_type) if ret_type.is_compound() => super::Instruction::CallExpectingRecord { label, size: self.type_size(ret_type) },
+            Some(_) => super::Instruction::CallExpectingValue(label),
+            None => super::Instruction::CallExpectingVoid(label)
+        });
+
+        Ok((instructions, option_ret_type, arg_types))
+    }
+
+    /// Ensure both sub-expressions of an arithmetic or comparison expression
+    /// are either of type Num or of type Int, inserting the instruction for the
+    /// operation on the relevant type. The type of the sub-expressions is
+    /// returned. The expected type (if any) is that of the sub-expressions.
+    fn eval_arithmetic_expr(&mut self, left: parsing::Expression, right: parsing::Expression, expected: Option<&super::Type>, num_instruction: super::Instruction, int_instruction: super::Instruction, expr_type: &str) -> super::Result<(Vec<super::Instruction>, super::Type)> {
+        log::trace!("Verifying types of {} expression - Num or Int type on both sides of operator expected", expr_type);
+
+        let (mut instructions, operand_type) = self.eval_operands(left, right, expected, true)?;
+
+        instructions.push(if operand_type == super::Type::Int { int_instruction } else { num_instruction });
+
+        Ok((instructions, operand_type))
+    }
+
+    /// Evaluate both sub-expressions of a binary expression, ensuring they are of
+    /// the same type. A sub-expression made up of only integer literals is
+    /// evaluated expecting the type of the other sub-expression, so is treated
+    /// as being of type Num alongside a Num. When `numeric` is set, the
+    /// sub-expressions must be of either type Num or type Int.
+    fn eval_operands(&mut self, left: parsing::Expression, right: parsing::Expression, expected: Option<&super::Type>, numeric: bool) -> super::Result<(Vec<super::Instruction>, super::Type)> {
+        let expect_numeric = |operand_type: &super::Type, span: &stream::Span| {
+            if !numeric || *operand_type == super::Type::Num || *operand_type == super::Type::Int { Ok(()) }
+            else {
+                Err(super::Failure::UnexpectedType {
+                    span: span.clone(), expected: super::Type::Num, encountered: operand_type.clone()
+                })
+            }
+        };
+
+        // Take the type of the right sub-expression should only the left be
+        // made up of integer literals:
+        if is_integer_literal_expr(&left) && !is_integer_literal_expr(&right) {
+            let (right_instructions, operand_type, right_span) = self.eval_expr_expecting(right, expected)?;
+            expect_numeric(&operand_type, &right_span)?;
+
+            let (mut instructions, _) = self.expect_expr_type(left, operand_type.clone())?;
+            instructions.extend(right_instructions);
+
+            Ok((instructions, operand_type))
+        }
+        else {
+            let (mut instructions, operand_type, left_span) = self.eval_expr_expecting(left, expected)?;
+            expect_numeric(&operand_type, &left_span)?;
+
+            let (right_instructions, _) = self.expect_expr_type(right, operand_type.clone())?;
+            instructions.extend(right_instructions);
+
+            Ok((instructions, operand_type))
+        }
+    }
+
+    /// Ensure the two sub-expressions of an equality (or inequality) expression
+    /// are of the same type. Insert the relevant final IR instruction(s) also.
+    /// The given span is that of the entire equality expression.
+    fn eval_equality_expr(&mut self, span: &stream::Span, left: parsing::Expression, right: parsing::Expression, negate: bool) -> super::Result<Vec<super::Instruction>> {
+        log::trace!("Verifying types of equality expression - types on both sides of the operator should be the same");
+
+        let (mut instructions, left_type) = self.eval_operands(left, right, None, false)?;
+
+        if let super::Type::Record(type_identifier) | super::Type::Enum(type_identifier) = left_type {
+            return Err(super::Failure::CompoundValueNotAllowed { span: span.clone(), type_identifier, usage: "compared" });
+        }
+
+        // Strings are compared by contents rather than by address:
+        if left_type == super::Type::String {
+            instructions.push(super::Instruction::StringEquals);
+            if negate { instructions.push(super::Instruction::Not); }
+        }
+        else {
+            instructions.push(if negate { super::Instruction::NotEquals } else { super::Instruction::Equals });
+        }
+
+        Ok(instructions)
+    }
+
+    /// Ensure both sub-expressions of a logical and/or expression are of Bool
+    /// type. The right sub-expression is only evaluated should the left not
+    /// evaluate to the given short-circuit value (false for and, true for or),
+    /// in which case the short-circuit value is the result of the expression.
+    fn eval_short_circuit_expr(&mut self, left: parsing::Expression, right: parsing::Expression, short_circuit_value: bool) -> super::Result<Vec<super::Instruction>> {
+        let short_circuit_id = self.new_id();
+        let end_id = self.new_id();
+
+        let (mut instructions, _) = self.expect_expr_type(left, super::Type::Bool)?;
+        instructions.push(
+            if short_circuit_value { super::Instruction::JumpIfTrue(short_circuit_id) }
+            else { super::Instruction::JumpIfFalse(short_circuit_id) }
+        );
+
+        let (right_instructions, _) = self.expect_expr_type(right, super::Type::Bool)?;
+        instructions.extend(right_instructions);
+
+        instructions.extend(vec![
+            super::Instruction::Jump(end_id),
+            super::Instruction::Label(short_circuit_id),
+            super::Instruction::Push(super::Value::Bool(short_circuit_value)),
+            super::Instruction::Label(end_id)
+        ]);
+
+        Ok(instructions)
+    }
+
+    /// Evaluate an expression, ensuring it is of the expected type.
+    fn expect_expr_type(&mut self, expr: parsing::Expression, expected: super::Type) -> super::Result<(Vec<super::Instruction>, stream::Span)> {
+        let (instructions, expr_type, span) = self.eval_expr_expecting(expr, Some(&expected))?;
+        
+        if expr_type == expected { Ok((instructions, span)) }
+        else {
+            Err(super::Failure::UnexpectedType {
+                span, expected, encountered: expr_type
+            }) 
         }
     }
 
@@ -560,43 +1613,89 @@ impl<T: Iterator<Item=parsing::Statement>> Checker<T> {
 }
 
 
+/// Is the given expression made up of only integer literals and arithmetic
+/// operators (e.g. `~2 * (3 + 4)`)?
+fn is_integer_literal_expr(expr: &parsing::Expression) -> bool {
+    use parsing::Expression;
+
+    match expr {
+        Expression::IntegerLiteral { .. } => true,
+        Expression::UnaryMinus(_, inner) => is_integer_literal_expr(inner),
+        Expression::Add(_, left, right) | Expression::Subtract(_, left, right) |
+        Expression::Multiply(_, left, right) | Expression::Divide(_, left, right) |
+        Expression::Modulo(_, left, right) | Expression::Power(_, left, right) =>
+            is_integer_literal_expr(left) && is_integer_literal_expr(right),
+        _ => false
+    }
+}
+
+/// Get the instructions that convert a value of one type to another, or `None`
+/// should such a conversion not be allowed. Characters are stored as their code
+/// point and Boolean values as either 0 or 1 so several conversions require no
+/// instructions at all. The line number is that of the conversion.
+fn conversion_instructions(from: &super::Type, to: &super::Type, line_number: u64) -> Option<Vec<super::Instruction>> {
+    use super::{ Type, Instruction };
+
+    match (from, to) {
+        (Type::String, _) | (_, Type::String) => None,
+        _ if from == to => Some(vec![]),
+
+        (Type::Char, Type::Int) | (Type::Bool, Type::Int) => Some(vec![]),
+        (Type::Int, Type::Char) => Some(vec![Instruction::IntToChar { line_number }]),
+        (Type::Char, Type::Num) | (Type::Bool, Type::Num) | (Type::Int, Type::Num) => Some(vec![Instruction::IntToNum]),
+        (Type::Num, Type::Int) => Some(vec![Instruction::NumToInt]),
+        (Type::Int, Type::Bool) => Some(vec![Instruction::IntToBool]),
+        (Type::Num, Type::Bool) => Some(vec![Instruction::NumToBool]),
+
+        _ => None
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use std::iter;
-    use crate::{ assert_pattern, parsing, checking, stream::Position };
+    use crate::{ parsing, checking, stream::{ Span, Position } };
 
-    fn new_empty_checker() -> super::Checker<iter::Empty<parsing::Statement>> {
-        let mut chkr = super::Checker::new(iter::empty());
+    fn new_empty_checker() -> super::Checker {
+        let mut chkr = super::Checker::new();
         chkr.begin_new_scope();
         chkr
     }
 
+    /// Check both the signature and body of a function definition statement.
+    fn define_function(chkr: &mut super::Checker, stmt: parsing::Statement) -> checking::Result<Vec<checking::Instruction>> {
+        if let parsing::Statement::FunctionDefinition { span, identifier, parameters, return_type, body: _ } = &stmt {
+            chkr.declare_function(span, identifier, parameters, return_type.as_deref())?;
+        }
+        chkr.eval_top_level_stmt(stmt)
+    }
+
     #[test]
     fn scoping() {
         let mut chkr = new_empty_checker();
 
-        let pos = Position::new();
+        let span = Span::new();
 
-        chkr.add_variable_def_to_inner_scope("outer".to_string(), checking::Type::Num);
-        assert_eq!(chkr.variable_lookup("outer", &pos), Ok(&checking::VariableDef {
+        chkr.add_variable_def_to_inner_scope("outer".to_string(), checking::Type::Num, Span::new(), false);
+        assert_eq!(chkr.variable_lookup("outer", &span), Ok(&checking::VariableDef {
             identifier: "outer".to_string(),
             var_type: checking::Type::Num,
-            id: 0
+            id: 0,
+            span: Span::new(),
+            parameter: false
         }));
 
         chkr.begin_new_scope();
 
-        chkr.add_variable_def_to_inner_scope("inner".to_string(), checking::Type::Bool);
+        chkr.add_variable_def_to_inner_scope("inner".to_string(), checking::Type::Bool, Span::new(), false);
 
-        assert!(chkr.variable_lookup("inner", &pos).is_ok());
-        assert!(chkr.variable_lookup("outer", &pos).is_ok());
+        assert!(chkr.variable_lookup("inner", &span).is_ok());
+        assert!(chkr.variable_lookup("outer", &span).is_ok());
 
         chkr.end_scope();
 
-        assert!(chkr.variable_lookup("inner", &pos).is_err());
-        assert!(chkr.variable_lookup("outer", &pos).is_ok());
-        assert!(chkr.variable_lookup("undefined", &pos).is_err());
+        assert!(chkr.variable_lookup("inner", &span).is_err());
+        assert!(chkr.variable_lookup("outer", &span).is_ok());
+        assert!(chkr.variable_lookup("undefined", &span).is_err());
     }
 
     #[test]
@@ -604,33 +1703,34 @@ mod tests {
         let mut chkr = new_empty_checker();
 
         assert_eq!(
-            chkr.eval_expr(parsing::Expression::NumberLiteral { pos: Position::new(), value: 10.5 }),
+            chkr.eval_expr(parsing::Expression::NumberLiteral { span: Span::new(), value: 10.5 }),
             Ok((
                 vec![checking::Instruction::Push(checking::Value::Num(10.5))],
-                checking::Type::Num, Position::new()
+                checking::Type::Num, Span::new()
             ))
         );
 
         assert_eq!(
-            chkr.eval_expr(parsing::Expression::BooleanLiteral { pos: Position::new(), value: true }),
+            chkr.eval_expr(parsing::Expression::BooleanLiteral { span: Span::new(), value: true }),
             Ok((
                 vec![checking::Instruction::Push(checking::Value::Bool(true))],
-                checking::Type::Bool, Position::new()
+                checking::Type::Bool, Span::new()
             ))
         );
 
         assert_eq!(
-            chkr.eval_expr(parsing::Expression::CharLiteral { pos: Position::new(), value: '話' }),
+            chkr.eval_expr(parsing::Expression::CharLiteral { span: Span::new(), value: '話' }),
             Ok((
                 vec![checking::Instruction::Push(checking::Value::Char('話'))],
-                checking::Type::Char, Position::new()
+                checking::Type::Char, Span::new()
             ))
         );
 
         assert_eq!(
             chkr.eval_expr(parsing::Expression::Equal(
-                Box::new(parsing::Expression::CharLiteral { pos: Position::new(), value: 'x' }),
-                Box::new(parsing::Expression::CharLiteral { pos: Position::new(), value: 'y' })
+                Span::new(),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'x' }),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'y' })
             )),
             Ok((
                 vec![
@@ -638,25 +1738,75 @@ mod tests {
                     checking::Instruction::Push(checking::Value::Char('y')),
                     checking::Instruction::Equals
                 ],
-                checking::Type::Bool, Position::new()
+                checking::Type::Bool, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Equal(
+                Span::new(),
+                Box::new(parsing::Expression::StringLiteral { span: Span::new(), value: "abc".to_string() }),
+                Box::new(parsing::Expression::StringLiteral { span: Span::new(), value: "abd".to_string() })
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::String("abc".to_string())),
+                    checking::Instruction::Push(checking::Value::String("abd".to_string())),
+                    checking::Instruction::StringEquals
+                ],
+                checking::Type::Bool, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::And(
+                Span::new(),
+                Box::new(parsing::Expression::BooleanLiteral { span: Span::new(), value: true }),
+                Box::new(parsing::Expression::BooleanLiteral { span: Span::new(), value: false })
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Bool(true)),
+                    checking::Instruction::JumpIfFalse(0),
+                    checking::Instruction::Push(checking::Value::Bool(false)),
+                    checking::Instruction::Jump(1),
+                    checking::Instruction::Label(0),
+                    checking::Instruction::Push(checking::Value::Bool(false)),
+                    checking::Instruction::Label(1)
+                ],
+                checking::Type::Bool, Span::new()
             ))
         );
 
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Or(
+                Span::new(),
+                Box::new(parsing::Expression::BooleanLiteral { span: Span::new(), value: true }),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 })
+            )),
+            Err(checking::Failure::UnexpectedType {
+                encountered: checking::Type::Num,
+                expected: checking::Type::Bool, span: _
+            })
+        );
+
         assert_pattern!(
             chkr.eval_expr(parsing::Expression::Equal(
-                Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 1.5 }),
-                Box::new(parsing::Expression::BooleanLiteral { pos: Position::new(), value: false })
+                Span::new(),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.5 }),
+                Box::new(parsing::Expression::BooleanLiteral { span: Span::new(), value: false })
             )),
             Err(checking::Failure::UnexpectedType {
                 encountered: checking::Type::Bool,
-                expected: checking::Type::Num, pos: _
+                expected: checking::Type::Num, span: _
             })
         );
 
         assert_eq!(
             chkr.eval_expr(parsing::Expression::GreaterThan(
-                Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 1.34 }),
-                Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 0.95 })
+                Span::new(),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.34 }),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 0.95 })
             )),
             Ok((
                 vec![
@@ -664,25 +1814,59 @@ mod tests {
                     checking::Instruction::Push(checking::Value::Num(0.95)),
                     checking::Instruction::GreaterThan
                 ],
-                checking::Type::Bool, Position::new()
+                checking::Type::Bool, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::LessThanOrEqual(
+                Span::new(),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 2.0 })
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(1.0)),
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::LessThanOrEqual
+                ],
+                checking::Type::Bool, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::NotEqual(
+                Span::new(),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'x' }),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'y' })
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Char('x')),
+                    checking::Instruction::Push(checking::Value::Char('y')),
+                    checking::Instruction::NotEquals
+                ],
+                checking::Type::Bool, Span::new()
             ))
         );
 
         assert_pattern!(
             chkr.eval_expr(parsing::Expression::LessThan(
-                Box::new(parsing::Expression::CharLiteral { pos: Position::new(), value: 'b' }),
-                Box::new(parsing::Expression::CharLiteral { pos: Position::new(), value: 'a' })
+                Span::new(),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'b' }),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'a' })
             )),
             Err(checking::Failure::UnexpectedType {
                 encountered: checking::Type::Char,
-                expected: checking::Type::Num, pos: _
+                expected: checking::Type::Num, span: _
             })
         );
 
         assert_eq!(
             chkr.eval_expr(parsing::Expression::Add(
-                Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 10.0 }),
-                Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 11.2 })
+                Span::new(),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 10.0 }),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 11.2 })
             )),
             Ok((
                 vec![
@@ -690,66 +1874,158 @@ mod tests {
                     checking::Instruction::Push(checking::Value::Num(11.2)),
                     checking::Instruction::Add
                 ],
-                checking::Type::Num, Position::new()
+                checking::Type::Num, Span::new()
             ))
         );
 
-        assert_pattern!(
-            chkr.eval_expr(parsing::Expression::Divide(
-                Box::new(parsing::Expression::CharLiteral { pos: Position::new(), value: 'x' }),
-                Box::new(parsing::Expression::BooleanLiteral { pos: Position::new(), value: false })
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Multiply(
+                Span::new(),
+                Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value: 3 }),
+                Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value: 4 })
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(3)),
+                    checking::Instruction::Push(checking::Value::Int(4)),
+                    checking::Instruction::IntMultiply
+                ],
+                checking::Type::Int, Span::new()
+            ))
+        );
+
+        // Integer literals are treated as Num literals alongside a Num:
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::LessThan(
+                Span::new(),
+                Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value: 1 }),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.5 })
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(1.0)),
+                    checking::Instruction::Push(checking::Value::Num(1.5)),
+                    checking::Instruction::LessThan
+                ],
+                checking::Type::Bool, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.expect_expr_type(
+                parsing::Expression::UnaryMinus(Span::new(), Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value: 2 })),
+                checking::Type::Num
+            ),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(0.0)),
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::Subtract
+                ],
+                Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Add(
+                Span::new(),
+                Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value: 1 }),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'a' })
+            )),
+            Err(checking::Failure::UnexpectedType {
+                span: Span::new(), expected: checking::Type::Num, encountered: checking::Type::Char
+            })
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Power(
+                Span::new(),
+                Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 2.0 }),
+                Box::new(parsing::Expression::Modulo(
+                    Span::new(),
+                    Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 7.0 }),
+                    Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 4.0 })
+                ))
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::Push(checking::Value::Num(7.0)),
+                    checking::Instruction::Push(checking::Value::Num(4.0)),
+                    checking::Instruction::Modulo,
+                    checking::Instruction::Power
+                ],
+                checking::Type::Num, Span::new()
+            ))
+        );
+
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Divide(
+                Span::new(),
+                Box::new(parsing::Expression::CharLiteral { span: Span::new(), value: 'x' }),
+                Box::new(parsing::Expression::BooleanLiteral { span: Span::new(), value: false })
             )),
             Err(checking::Failure::UnexpectedType {
                 encountered: checking::Type::Char,
-                expected: checking::Type::Num, pos: _
+                expected: checking::Type::Num, span: _
             })
         );
 
         assert_pattern!(
             chkr.eval_expr(parsing::Expression::Variable {
-                pos: Position::new(),
+                span: Span::new(),
                 identifier: "undefined".to_string()
             }),
             Err(checking::Failure::VariableNotInScope(_, _))
         );
 
-        let var_id = chkr.add_variable_def_to_inner_scope("var".to_string(), checking::Type::Num);
+        let var_id = chkr.add_variable_def_to_inner_scope("var".to_string(), checking::Type::Num, Span::new(), false);
+
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Variable {
+                span: Span::new(),
+                identifier: "var".to_string()
+            }),
+            Err(checking::Failure::UseOfUninitialisedVariable(_, _))
+        );
+
+        chkr.initialised_variables.insert(var_id);
 
         chkr.begin_new_scope();
         assert_eq!(
             chkr.eval_expr(parsing::Expression::Variable {
-                pos: Position::new(),
+                span: Span::new(),
                 identifier: "var".to_string()
             }),
             Ok((
                 vec![checking::Instruction::Push(checking::Value::Variable(var_id))],
-                checking::Type::Num, Position::new()
+                checking::Type::Num, Span::new()
             ))
         );
         chkr.end_scope();
 
-        chkr.add_function_def("func".to_string(), vec![checking::Type::Char], Some(checking::Type::Num), "func0".to_string());
+        chkr.add_function_def(Span::new(), "func".to_string(), vec![checking::Type::Char], Some(checking::Type::Num), "func0".to_string());
 
         assert_eq!(
             chkr.eval_expr(parsing::Expression::FunctionCall {
-                pos: Position::new(),
+                span: Span::new(),
                 identifier: "func".to_string(),
-                args: vec![parsing::Expression::CharLiteral { pos: Position::new(), value: 'a' }]
+                args: vec![parsing::Expression::CharLiteral { span: Span::new(), value: 'a' }]
             }),
             Ok((
                 vec![
                     checking::Instruction::Push(checking::Value::Char('a')),
                     checking::Instruction::CallExpectingValue("func0".to_string())
                 ],
-                checking::Type::Num, Position::new()
+                checking::Type::Num, Span::new()
             ))
         );
 
         match chkr.eval_expr(parsing::Expression::FunctionCall {
-            pos: Position::new(),
+            span: Span::new(),
             identifier: "func".to_string(),
             args: vec![
-                parsing::Expression::NumberLiteral { pos: Position::new(), value: 1.5 }
+                parsing::Expression::NumberLiteral { span: Span::new(), value: 1.5 }
             ]
         }) {
             Err(checking::Failure::FunctionUndefined(_, ident, args)) => {
@@ -759,34 +2035,244 @@ mod tests {
             _ => panic!()
         }
 
-        chkr.add_function_def("abc".to_string(), vec![checking::Type::Char], None, "func1".to_string());
+        chkr.add_function_def(Span::new(), "abc".to_string(), vec![checking::Type::Char], None, "func1".to_string());
 
         assert_pattern!(
             chkr.eval_expr(parsing::Expression::FunctionCall {
-                pos: Position::new(),
+                span: Span::new(),
                 identifier: "abc".to_string(),
                 args: vec![
-                    parsing::Expression::CharLiteral { pos: Position::new(), value: 'x' }
+                    parsing::Expression::CharLiteral { span: Span::new(), value: 'x' }
                 ]
             }),
             Err(checking::Failure::VoidFunctionInExpr(_, _, _))
         );
     }
 
+    #[test]
+    fn integers() -> checking::Result<()> {
+        let mut chkr = new_empty_checker();
+
+        let int = |value| Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value });
+        let variable = |identifier: &str| Box::new(parsing::Expression::Variable { span: Span::new(), identifier: identifier.to_string() });
+        let two_times_three = || parsing::Expression::Multiply(Span::new(), int(2), int(3));
+
+        // Integer arithmetic:
+        assert_eq!(
+            chkr.eval_expr(two_times_three()),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(2)),
+                    checking::Instruction::Push(checking::Value::Int(3)),
+                    checking::Instruction::IntMultiply
+                ],
+                checking::Type::Int, Span::new()
+            ))
+        );
+
+        // Integer division and modulo are checked for a zero divisor at run time
+        // using the line number of the expression:
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Modulo(Span::new(), int(7), int(0))),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(7)),
+                    checking::Instruction::Push(checking::Value::Int(0)),
+                    checking::Instruction::IntModulo { line_number: Span::new().start.line_number }
+                ],
+                checking::Type::Int, Span::new()
+            ))
+        );
+
+        // An expression made up of only integer literals is evaluated as a Num
+        // where a Num is expected:
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(), identifier: "x".to_string(), var_type: "Num".to_string(), value: Some(two_times_three())
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Local(0),
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::Push(checking::Value::Num(3.0)),
+                    checking::Instruction::Multiply,
+                    checking::Instruction::Store(0)
+                ],
+                1, None, false
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Add(
+                Span::new(),
+                Box::new(parsing::Expression::Subtract(Span::new(), int(1), int(i64::MIN))),
+                variable("x")
+            )),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(1.0)),
+                    checking::Instruction::Push(checking::Value::Num(-9223372036854775808.0)),
+                    checking::Instruction::Subtract,
+                    checking::Instruction::Push(checking::Value::Variable(0)),
+                    checking::Instruction::Add
+                ],
+                checking::Type::Num, Span::new()
+            ))
+        );
+
+        // Division of integer literals therefore depends on whether a Num is
+        // expected (7 / 2 is either 3 or 3.5):
+        let seven_over_two = || parsing::Expression::Divide(Span::new(), int(7), int(2));
+
+        assert_eq!(
+            chkr.eval_expr(seven_over_two()),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(7)),
+                    checking::Instruction::Push(checking::Value::Int(2)),
+                    checking::Instruction::IntDivide { line_number: Span::new().start.line_number }
+                ],
+                checking::Type::Int, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.expect_expr_type(seven_over_two(), checking::Type::Num),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(7.0)),
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::Divide
+                ],
+                Span::new()
+            ))
+        );
+
+        chkr.current_return_type = Some(checking::Type::Num);
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::Return(Span::new(), Some(seven_over_two()))),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(7.0)),
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::Divide,
+                    checking::Instruction::ReturnValue
+                ],
+                0, Some((checking::Type::Num, Span::new())), true
+            ))
+        );
+        chkr.current_return_type = None;
+
+        // Int and Num values cannot otherwise be mixed:
+        chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+            span: Span::new(), identifier: "i".to_string(), var_type: "Int".to_string(), value: Some(*int(1))
+        })?;
+
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Add(Span::new(), variable("i"), variable("x"))),
+            Err(checking::Failure::UnexpectedType { span: Span::new(), expected: checking::Type::Int, encountered: checking::Type::Num })
+        );
+
+        // The same applies to global variables:
+        let main_def = parsing::Statement::FunctionDefinition {
+            span: Span::new(), identifier: "main".to_string(), parameters: vec![], return_type: None, body: vec![]
+        };
+
+        assert_eq!(
+            super::input(vec![
+                parsing::Statement::VariableDeclaration {
+                    span: Span::new(), identifier: "x".to_string(), var_type: "Num".to_string(), value: Some(two_times_three())
+                },
+                main_def
+            ]),
+            Ok((vec![
+                checking::Instruction::Global(0),
+                checking::Instruction::Function { label: "main".to_string(), local_variable_count: 0 },
+                checking::Instruction::Push(checking::Value::Num(2.0)),
+                checking::Instruction::Push(checking::Value::Num(3.0)),
+                checking::Instruction::Multiply,
+                checking::Instruction::Store(0),
+                checking::Instruction::ReturnVoid
+            ], vec![checking::Warning::VariableNeverRead(Span::new(), "x".to_string())]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn conversions() {
+        let mut chkr = new_empty_checker();
+
+        let convert = |to_type: &str, expr| parsing::Expression::Construction {
+            span: Span::new(), type_identifier: to_type.to_string(), args: vec![expr]
+        };
+
+        assert_eq!(
+            chkr.eval_expr(convert("Int", parsing::Expression::CharLiteral { span: Span::new(), value: 'a' })),
+            Ok((vec![checking::Instruction::Push(checking::Value::Char('a'))], checking::Type::Int, Span::new()))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(convert("Num", parsing::Expression::IntegerLiteral { span: Span::new(), value: 7 })),
+            Ok((
+                vec![checking::Instruction::Push(checking::Value::Int(7)), checking::Instruction::IntToNum],
+                checking::Type::Num, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(convert("Char", convert("Int", parsing::Expression::NumberLiteral { span: Span::new(), value: 98.5 }))),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(98.5)),
+                    checking::Instruction::NumToInt,
+                    checking::Instruction::IntToChar { line_number: Span::new().start.line_number }
+                ],
+                checking::Type::Char, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(convert("Bool", parsing::Expression::NumberLiteral { span: Span::new(), value: 0.0 })),
+            Ok((
+                vec![checking::Instruction::Push(checking::Value::Num(0.0)), checking::Instruction::NumToBool],
+                checking::Type::Bool, Span::new()
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(convert("Char", parsing::Expression::BooleanLiteral { span: Span::new(), value: true })),
+            Err(checking::Failure::InvalidConversion {
+                span: Span::new(), from: checking::Type::Bool, to: checking::Type::Char
+            })
+        );
+
+        assert_pattern!(
+            chkr.eval_expr(convert("String", parsing::Expression::CharLiteral { span: Span::new(), value: 'a' })),
+            Err(checking::Failure::InvalidConversion { .. })
+        );
+
+        assert_pattern!(
+            chkr.eval_expr(convert("Float", parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 })),
+            Err(checking::Failure::NonexistentPrimitiveType(_, _))
+        );
+    }
+
     #[test]
     fn eval_inner_stmts() {
         let mut chkr = new_empty_checker();
 
         assert_eq!(
-            chkr.eval_inner_stmt(parsing::Statement::Return(None)),
-            Ok((vec![checking::Instruction::ReturnVoid], 0, None))
+            chkr.eval_inner_stmt(parsing::Statement::Return(Span::new(), None)),
+            Ok((vec![checking::Instruction::ReturnVoid], 0, None, true))
         );
 
         assert_eq!(
-            chkr.eval_inner_stmt(parsing::Statement::Return(Some(
+            chkr.eval_inner_stmt(parsing::Statement::Return(Span::new(), Some(
                 parsing::Expression::Add(
-                    Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 1.2 }),
-                    Box::new(parsing::Expression::NumberLiteral { pos: Position::new(), value: 2.8 })
+                    Span::new(),
+                    Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.2 }),
+                    Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 2.8 })
                 )
             ))),
             Ok((
@@ -796,16 +2282,18 @@ mod tests {
                     checking::Instruction::Add,
                     checking::Instruction::ReturnValue
                 ],
-                0, Some((checking::Type::Num, Position::new()))
+                0, Some((checking::Type::Num, Span::new())), true
             ))
         );
 
         assert_eq!(
             chkr.eval_inner_stmt(parsing::Statement::If {
-                condition: parsing::Expression::BooleanLiteral { pos: Position::new(), value: true },
+                span: Span::new(),
+                condition: parsing::Expression::BooleanLiteral { span: Span::new(), value: true },
                 block: vec![
-                    parsing::Statement::Return(Some(parsing::Expression::CharLiteral { pos: Position::new(), value: 'x' }))
-                ]
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::CharLiteral { span: Span::new(), value: 'x' }))
+                ],
+                else_block: None
             }),
             Ok((
                 vec![
@@ -815,52 +2303,56 @@ mod tests {
                     checking::Instruction::ReturnValue,
                     checking::Instruction::Label(0)
                 ],
-                0, Some((checking::Type::Char, Position::new()))
+                0, Some((checking::Type::Char, Span::new())), false
             ))
         );
 
         assert_eq!(
             chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(),
                 identifier: "pi".to_string(),
                 var_type: "Num".to_string(),
-                value: Some(parsing::Expression::NumberLiteral { pos: Position::new(), value: 3.14 })
+                value: Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 2.5 })
             }),
             Ok((
                 vec![
                     checking::Instruction::Local(1),
-                    checking::Instruction::Push(checking::Value::Num(3.14)),
+                    checking::Instruction::Push(checking::Value::Num(2.5)),
                     checking::Instruction::Store(1)
                 ],
-                1, None
+                1, None, false
             ))
         );
-        assert!(chkr.variable_lookup("pi", &Position::new()).is_ok());
+        assert!(chkr.variable_lookup("pi", &Span::new()).is_ok());
 
         assert_eq!(
             chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(),
                 identifier: "xyz".to_string(),
                 var_type: "Oops".to_string(),
                 value: None
             }),
-            Err(checking::Failure::NonexistentPrimitiveType("Oops".to_string()))
+            Err(checking::Failure::NonexistentPrimitiveType(Span::new(), "Oops".to_string()))
         );
 
         assert_pattern!(
             chkr.eval_inner_stmt(parsing::Statement::VariableAssignment {
+                span: Span::new(),
                 identifier: "pi".to_string(),
-                assign_to: parsing::Expression::NumberLiteral { pos: Position::new(), value: 3.1 }
+                assign_to: parsing::Expression::NumberLiteral { span: Span::new(), value: 3.1 }
             }),
-            Ok((_, _, None))
+            Ok((_, _, None, false))
         );
 
         assert_pattern!(
             chkr.eval_inner_stmt(parsing::Statement::VariableAssignment {
+                span: Span::new(),
                 identifier: "pi".to_string(),
-                assign_to: parsing::Expression::BooleanLiteral { pos: Position::new(), value: true }
+                assign_to: parsing::Expression::BooleanLiteral { span: Span::new(), value: true }
             }),
             Err(checking::Failure::UnexpectedType {
                 expected: checking::Type::Num,
-                encountered: checking::Type::Bool, pos: _
+                encountered: checking::Type::Bool, span: _
             })
         );
 
@@ -870,10 +2362,96 @@ mod tests {
                 parameters: vec![],
                 return_type: None,
                 body: vec![],
-                pos: Position::new()
+                span: Span::new()
             }),
             Err(checking::Failure::NestedFunctions(_, _))
         );
+
+        chkr.add_function_def(Span::new(), "do_thing".to_string(), vec![checking::Type::Num], None, "func_a".to_string());
+        chkr.add_function_def(Span::new(), "get_thing".to_string(), vec![], Some(checking::Type::Char), "func_b".to_string());
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::FunctionCall {
+                identifier: "do_thing".to_string(),
+                args: vec![parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }],
+                span: Span::new()
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(1.0)),
+                    checking::Instruction::CallExpectingVoid("func_a".to_string())
+                ],
+                0, None, false
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::FunctionCall {
+                identifier: "get_thing".to_string(),
+                args: vec![],
+                span: Span::new()
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::CallExpectingValue("func_b".to_string()),
+                    checking::Instruction::Discard
+                ],
+                0, None, false
+            ))
+        );
+
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::FunctionCall {
+                identifier: "do_thing".to_string(),
+                args: vec![],
+                span: Span::new()
+            }),
+            Err(checking::Failure::FunctionUndefined(_, _, _))
+        );
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::If {
+                span: Span::new(),
+                condition: parsing::Expression::BooleanLiteral { span: Span::new(), value: false },
+                block: vec![
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }))
+                ],
+                else_block: Some(vec![
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 2.0 }))
+                ])
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Bool(false)),
+                    checking::Instruction::JumpIfFalse(2),
+                    checking::Instruction::Push(checking::Value::Num(1.0)),
+                    checking::Instruction::ReturnValue,
+                    checking::Instruction::Jump(3),
+                    checking::Instruction::Label(2),
+                    checking::Instruction::Push(checking::Value::Num(2.0)),
+                    checking::Instruction::ReturnValue,
+                    checking::Instruction::Label(3)
+                ],
+                0, Some((checking::Type::Num, Span::new())), true
+            ))
+        );
+
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::If {
+                span: Span::new(),
+                condition: parsing::Expression::BooleanLiteral { span: Span::new(), value: false },
+                block: vec![
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }))
+                ],
+                else_block: Some(vec![
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::CharLiteral { span: Span::new(), value: 'x' }))
+                ])
+            }),
+            Err(checking::Failure::UnexpectedType {
+                expected: checking::Type::Num,
+                encountered: checking::Type::Char, span: _
+            })
+        );
     }
 
     #[test]
@@ -882,17 +2460,18 @@ mod tests {
 
         pretty_env_logger::init();
         assert_eq!(
-            chkr.eval_top_level_stmt(parsing::Statement::FunctionDefinition {
+            define_function(&mut chkr, parsing::Statement::FunctionDefinition {
                 identifier: "func".to_string(),
                 parameters: vec![],
                 return_type: None,
                 body: vec![
                     parsing::Statement::VariableDeclaration {
+                        span: Span::new(),
                         identifier: "var".to_string(), var_type: "Num".to_string(),
                         value: None
                     }
                 ],
-                pos: Position::new()
+                span: Span::new()
             }),
             Ok(vec![
                 checking::Instruction::Function { label: "func0".to_string(), local_variable_count: 1 },
@@ -900,79 +2479,79 @@ mod tests {
                 checking::Instruction::ReturnVoid
             ])
         );
-        assert!(chkr.function_lookup("func", &[], &Position::new())?.return_type.is_none());
+        assert!(chkr.function_lookup("func", &[], &Span::new())?.return_type.is_none());
 
         assert_eq!(
-            chkr.eval_top_level_stmt(parsing::Statement::FunctionDefinition {
+            define_function(&mut chkr, parsing::Statement::FunctionDefinition {
                 identifier: "func".to_string(),
                 parameters: vec![],
                 return_type: Some("Num".to_string()),
                 body: vec![
-                    parsing::Statement::Return(Some(parsing::Expression::NumberLiteral {
-                        pos: Position::new(), value: 1.5
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::NumberLiteral {
+                        span: Span::new(), value: 1.5
                     }))
                 ],
-                pos: Position::new()
+                span: Span::new()
             }),
-            Err(checking::Failure::RedefinedExistingFunction(
-                "func".to_string(), vec![]
-            ))
+            Err(checking::Failure::RedefinedExistingFunction(Box::new(checking::FunctionRedefinition {
+                span: Span::new(), identifier: "func".to_string(), params: vec![], previous_span: Span::new()
+            })))
         );
 
-        assert_pattern!(
-            chkr.eval_top_level_stmt(parsing::Statement::FunctionDefinition {
+        assert_eq!(
+            define_function(&mut chkr, parsing::Statement::FunctionDefinition {
                 identifier: "func".to_string(),
                 parameters: vec![
                     parsing::Parameter {
-                        pos: Position::new(), identifier: "x".to_string(),
+                        span: Span::new(), identifier: "x".to_string(),
                         param_type: "Char".to_string()
                     }
                 ],
                 return_type: Some("Num".to_string()),
                 body: vec![],
-                pos: Position::new()
+                span: Span::new()
             }),
-            Err(checking::Failure::FunctionUnexpectedReturnType {
-                pos: _, identifier: _, params: _,
+            Err(checking::Failure::FunctionUnexpectedReturnType(Box::new(checking::UnexpectedReturnType {
+                span: Span::new(), identifier: "func".to_string(), params: vec![checking::Type::Char],
                 expected: checking::Type::Num, encountered: None
-            })
+            })))
         );
 
-        assert_pattern!(
-            chkr.eval_top_level_stmt(parsing::Statement::FunctionDefinition {
+        assert_eq!(
+            define_function(&mut chkr, parsing::Statement::FunctionDefinition {
                 identifier: "xyz".to_string(),
                 parameters: vec![],
                 return_type: None,
                 body: vec![
-                    parsing::Statement::Return(Some(parsing::Expression::BooleanLiteral {
-                        pos: Position::new(), value: true
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::BooleanLiteral {
+                        span: Span::new(), value: true
                     }))
                 ],
-                pos: Position::new()
+                span: Span::new()
             }),
             Err(checking::Failure::VoidFunctionReturnsValue(
-                _, _, _, checking::Type::Bool
+                Span::new(), "xyz".to_string(), vec![], Box::new(checking::Type::Bool)
             ))
         );
 
         chkr.id_counter = 0;
 
         assert_eq!(
-            chkr.eval_top_level_stmt(parsing::Statement::FunctionDefinition {
+            define_function(&mut chkr, parsing::Statement::FunctionDefinition {
                 identifier: "useless_function".to_string(),
                 parameters: vec![
                     parsing::Parameter {
-                        pos: Position::new(), identifier: "x".to_string(),
+                        span: Span::new(), identifier: "x".to_string(),
                         param_type: "Num".to_string()
                     }
                 ],
                 return_type: Some("Num".to_string()),
                 body: vec![
-                    parsing::Statement::Return(Some(parsing::Expression::Variable {
-                        pos: Position::new(), identifier: "x".to_string()
+                    parsing::Statement::Return(Span::new(), Some(parsing::Expression::Variable {
+                        span: Span::new(), identifier: "x".to_string()
                     }))
                 ],
-                pos: Position::new()
+                span: Span::new()
             }),
             Ok(vec![
                 checking::Instruction::Function { label: "func0".to_string(), local_variable_count: 0 },
@@ -982,12 +2561,34 @@ mod tests {
             ])
         );
 
-        let main_func = chkr.eval_top_level_stmt(parsing::Statement::FunctionDefinition {
+        assert_pattern!(
+            define_function(&mut chkr, parsing::Statement::FunctionDefinition {
+                identifier: "sometimes_returns".to_string(),
+                parameters: vec![],
+                return_type: Some("Num".to_string()),
+                body: vec![
+                    parsing::Statement::If {
+                        span: Span::new(),
+                        condition: parsing::Expression::BooleanLiteral { span: Span::new(), value: true },
+                        block: vec![
+                            parsing::Statement::Return(Span::new(), Some(parsing::Expression::NumberLiteral {
+                                span: Span::new(), value: 1.0
+                            }))
+                        ],
+                        else_block: None
+                    }
+                ],
+                span: Span::new()
+            }),
+            Err(checking::Failure::FunctionMayNotReturn(_))
+        );
+
+        let main_func = define_function(&mut chkr, parsing::Statement::FunctionDefinition {
             identifier: "main".to_string(),
             parameters: vec![],
             return_type: None,
             body: vec![],
-            pos: Position::new()
+            span: Span::new()
         })?;
         assert_eq!(main_func[0], checking::Instruction::Function { label: "main".to_string(), local_variable_count: 0 });
 
@@ -995,45 +2596,841 @@ mod tests {
     }
 
     #[test]
-    fn variable_shadowing() -> checking::Result<()> {
+    fn definite_assignment() -> checking::Result<()> {
         let mut chkr = new_empty_checker();
 
-        let pos = Position::new();
+        let declare = |identifier: &str| parsing::Statement::VariableDeclaration {
+            span: Span::new(),
+            identifier: identifier.to_string(), var_type: "Num".to_string(), value: None
+        };
+        let assign = |identifier: &str| parsing::Statement::VariableAssignment {
+            span: Span::new(),
+            identifier: identifier.to_string(),
+            assign_to: parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }
+        };
+        let read = |identifier: &str| parsing::Statement::Display(Span::new(), parsing::Expression::Variable {
+            span: Span::new(), identifier: identifier.to_string()
+        });
+        let condition = || parsing::Expression::BooleanLiteral { span: Span::new(), value: true };
+
+        // Assigned in only one branch:
+        chkr.eval_inner_stmt(declare("x"))?;
+        chkr.eval_inner_stmt(parsing::Statement::If { span: Span::new(), condition: condition(), block: vec![assign("x")], else_block: None })?;
+        assert_pattern!(chkr.eval_inner_stmt(read("x")), Err(checking::Failure::UseOfUninitialisedVariable(_, _)));
 
-        chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
-            identifier: "x".to_string(),
-            var_type: "Num".to_string(),
-            value: None
+        // Assigned in both branches:
+        chkr.eval_inner_stmt(parsing::Statement::If {
+            span: Span::new(),
+            condition: condition(), block: vec![assign("x")], else_block: Some(vec![assign("x")])
         })?;
+        assert!(chkr.eval_inner_stmt(read("x")).is_ok());
+
+        // Loop body may never be executed:
+        chkr.eval_inner_stmt(declare("y"))?;
+        chkr.eval_inner_stmt(parsing::Statement::While { span: Span::new(), condition: condition(), block: vec![assign("y")] })?;
+        assert_pattern!(chkr.eval_inner_stmt(read("y")), Err(checking::Failure::UseOfUninitialisedVariable(_, _)));
+
+        // Branch that always returns does not reach the read:
+        chkr.eval_inner_stmt(declare("z"))?;
+        chkr.eval_inner_stmt(parsing::Statement::If {
+            span: Span::new(),
+            condition: condition(), block: vec![parsing::Statement::Return(Span::new(), None)], else_block: Some(vec![assign("z")])
+        })?;
+        assert!(chkr.eval_inner_stmt(read("z")).is_ok());
 
-        chkr.begin_new_scope();
+        Ok(())
+    }
 
-        // Shadow variable 'x' by declaring a variable in the inner scope of the
-        // same name but a different type:
-        chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
-            identifier: "x".to_string(),
-            var_type: "Bool".to_string(),
-            value: None
-        })?;
+    #[test]
+    fn global_variables() {
+        let global_decl = |var_type: &str, value| parsing::Statement::VariableDeclaration {
+            span: Span::new(),
+            identifier: "counter".to_string(), var_type: var_type.to_string(), value
+        };
 
-        assert_eq!(chkr.variable_lookup("x", &pos)?.var_type, checking::Type::Bool);
+        let main_def = parsing::Statement::FunctionDefinition {
+            identifier: "main".to_string(),
+            parameters: vec![],
+            return_type: None,
+            body: vec![
+                parsing::Statement::Display(Span::new(), parsing::Expression::Variable {
+                    span: Span::new(), identifier: "counter".to_string()
+                })
+            ],
+            span: Span::new()
+        };
 
-        chkr.end_scope();
+        // Global variables may be used by functions defined before them:
+        assert_eq!(
+            super::input(vec![
+                main_def,
+                global_decl("Num", Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }))
+            ]),
+            Ok((vec![
+                checking::Instruction::Global(0),
+                checking::Instruction::Function { label: "main".to_string(), local_variable_count: 0 },
+                // Global initialisation occurs at the start of main:
+                checking::Instruction::Push(checking::Value::Num(1.0)),
+                checking::Instruction::Store(0),
+                checking::Instruction::Push(checking::Value::Variable(0)),
+                checking::Instruction::Display { value_type: checking::DisplayType::Num, line_number: Span::new().start.line_number },
+                checking::Instruction::ReturnVoid
+            ], vec![]))
+        );
 
-        assert_eq!(chkr.variable_lookup("x", &pos)?.var_type, checking::Type::Num);
+        assert_eq!(
+            super::input(vec![global_decl("Num", None), global_decl("Bool", None)]),
+            Err(vec![
+                checking::Failure::VariableRedeclaredToDifferentType(Box::new(checking::VariableRedeclaration {
+                    span: Span::new(),
+                    identifier: "counter".to_string(),
+                    expected: checking::Type::Num,
+                    encountered: checking::Type::Bool,
+                    previous_span: Span::new()
+                })),
+                checking::Failure::MainUndefined
+            ])
+        );
 
         assert_eq!(
-            chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
-                identifier: "x".to_string(),
+            super::input(vec![parsing::Statement::Return(Span::new(), None)]),
+            Err(vec![checking::Failure::InvalidTopLevelStatement(Span::new()), checking::Failure::MainUndefined])
+        );
+    }
+
+    #[test]
+    fn functions_called_before_definition() {
+        let call = |identifier: &str| parsing::Statement::FunctionCall { span: Span::new(), identifier: identifier.to_string(), args: vec![] };
+        let func_def = |identifier: &str, body| parsing::Statement::FunctionDefinition {
+            span: Span::new(), identifier: identifier.to_string(), parameters: vec![], return_type: None, body
+        };
+
+        // Mutually recursive functions both defined after main:
+        assert_eq!(
+            super::input(vec![
+                func_def("main", vec![call("first")]),
+                func_def("first", vec![call("second")]),
+                func_def("second", vec![call("first")])
+            ]),
+            Ok((vec![
+                checking::Instruction::Function { label: "main".to_string(), local_variable_count: 0 },
+                checking::Instruction::CallExpectingVoid("func0".to_string()),
+                checking::Instruction::ReturnVoid,
+                checking::Instruction::Function { label: "func0".to_string(), local_variable_count: 0 },
+                checking::Instruction::CallExpectingVoid("func1".to_string()),
+                checking::Instruction::ReturnVoid,
+                checking::Instruction::Function { label: "func1".to_string(), local_variable_count: 0 },
+                checking::Instruction::CallExpectingVoid("func0".to_string()),
+                checking::Instruction::ReturnVoid
+            ], vec![]))
+        );
+
+        assert_eq!(
+            super::input(vec![func_def("main", vec![call("helper")]), func_def("helper", vec![]), func_def("helper", vec![])]),
+            Err(vec![checking::Failure::RedefinedExistingFunction(Box::new(checking::FunctionRedefinition {
+                span: Span::new(), identifier: "helper".to_string(), params: vec![], previous_span: Span::new()
+            }))])
+        );
+    }
+
+    #[test]
+    fn multiple_failures() {
+        let var = |identifier: &str| parsing::Expression::Variable { span: Span::new(), identifier: identifier.to_string() };
+        let func_def = |identifier: &str, parameters, body| parsing::Statement::FunctionDefinition {
+            span: Span::new(), identifier: identifier.to_string(), parameters, return_type: None, body
+        };
+        let undefined_param = || parsing::Parameter { param_type: "Undefined".to_string(), identifier: "x".to_string(), span: Span::new() };
+
+        // Checking continues with the next function after one fails, with only
+        // the first failure within a given function being reported. The body
+        // of a function with an invalid signature is not checked:
+        assert_eq!(
+            super::input(vec![
+                func_def("main", vec![], vec![
+                    parsing::Statement::Display(Span::new(), var("first")),
+                    parsing::Statement::Display(Span::new(), var("second"))
+                ]),
+                func_def("invalid", vec![undefined_param()], vec![parsing::Statement::Display(Span::new(), var("x"))]),
+                func_def("other", vec![], vec![parsing::Statement::Display(Span::new(), var("third"))])
+            ]),
+            Err(vec![
+                checking::Failure::NonexistentPrimitiveType(Span::new(), "Undefined".to_string()),
+                checking::Failure::VariableNotInScope(Span::new(), "first".to_string()),
+                checking::Failure::VariableNotInScope(Span::new(), "third".to_string())
+            ])
+        );
+
+        // The variables of a function that fails are discarded so that the
+        // next function is checked as though that function were never checked,
+        // even when it declares variables with the same identifiers:
+        let declare = |identifier: &str| parsing::Statement::VariableDeclaration {
+            span: Span::new(), var_type: "Num".to_string(), identifier: identifier.to_string(),
+            value: Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 })
+        };
+        let display = |identifier| parsing::Statement::Display(Span::new(), var(identifier));
+
+        let mut chkr = super::Checker::new();
+        chkr.declare_function(&Span::new(), "failing", &[], None).unwrap();
+        chkr.declare_function(&Span::new(), "valid", &[], None).unwrap();
+
+        let failing_ir = chkr.check_top_level_stmt(func_def("failing", vec![], vec![
+            declare("x"),
+            parsing::Statement::While {
+                span: Span::new(),
+                condition: parsing::Expression::BooleanLiteral { span: Span::new(), value: true },
+                block: vec![declare("y"), display("undefined")]
+            }
+        ]));
+        assert_eq!(failing_ir, vec![]);
+        assert_eq!(chkr.failures, vec![checking::Failure::VariableNotInScope(Span::new(), "undefined".to_string())]);
+        assert!(chkr.scopes.is_empty());
+        assert!(chkr.initialised_variables.is_empty() && chkr.read_variables.is_empty() && chkr.assigned_variables.is_empty());
+
+        let valid_ir = chkr.check_top_level_stmt(func_def("valid", vec![], vec![declare("x"), declare("y"), display("x"), display("y")]));
+        assert_eq!(valid_ir, vec![
+            checking::Instruction::Function { label: "func1".to_string(), local_variable_count: 2 },
+            // The IDs released by the failed function are reused:
+            checking::Instruction::Local(2),
+            checking::Instruction::Push(checking::Value::Num(1.0)),
+            checking::Instruction::Store(2),
+            checking::Instruction::Local(5),
+            checking::Instruction::Push(checking::Value::Num(1.0)),
+            checking::Instruction::Store(5),
+            checking::Instruction::Push(checking::Value::Variable(2)),
+            checking::Instruction::Display { value_type: checking::DisplayType::Num, line_number: 1 },
+            checking::Instruction::Push(checking::Value::Variable(5)),
+            checking::Instruction::Display { value_type: checking::DisplayType::Num, line_number: 1 },
+            checking::Instruction::ReturnVoid
+        ]);
+        assert_eq!(chkr.failures.len(), 1);
+        assert!(chkr.warnings.is_empty());
+    }
+
+    #[test]
+    fn warnings() {
+        let at = |line_number| {
+            let pos = Position { position: 0, line_number, line_position: 0 };
+            Span { start: pos.clone(), end: pos }
+        };
+        let var = |identifier: &str| parsing::Expression::Variable { span: Span::new(), identifier: identifier.to_string() };
+        let declare = |line_number, identifier: &str, value| parsing::Statement::VariableDeclaration {
+            span: at(line_number), var_type: "Num".to_string(), identifier: identifier.to_string(), value
+        };
+        let param = |line_number, param_type: &str, identifier: &str| parsing::Parameter {
+            param_type: param_type.to_string(), identifier: identifier.to_string(), span: at(line_number)
+        };
+        let call = |identifier: &str, args| parsing::Statement::FunctionCall { span: Span::new(), identifier: identifier.to_string(), args };
+        let func_def = |line_number, identifier: &str, parameters, body| parsing::Statement::FunctionDefinition {
+            span: at(line_number), identifier: identifier.to_string(), parameters, return_type: None, body
+        };
+        let one = || Some(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 });
+
+        let (_, warnings) = super::input(vec![
+            func_def(1, "main", vec![], vec![
+                declare(2, "unused", None),
+                declare(3, "written", one()),
+                declare(4, "_ignored", None),
+                declare(5, "read", one()),
+                call("used", vec![var("read"), parsing::Expression::BooleanLiteral { span: Span::new(), value: true }])
+            ]),
+            func_def(7, "used", vec![param(7, "Num", "value"), param(7, "Bool", "flag")], vec![
+                parsing::Statement::Display(Span::new(), var("value")),
+                parsing::Statement::Display(Span::new(), var("global_read"))
+            ]),
+            // Functions only called by each other are never called from main:
+            func_def(10, "unused", vec![], vec![call("also_unused", vec![])]),
+            func_def(13, "also_unused", vec![], vec![call("unused", vec![])]),
+            func_def(16, "_ignored", vec![], vec![]),
+            // Global variables are warned about in the same way as locals:
+            declare(18, "global_unused", None),
+            declare(19, "global_written", one()),
+            declare(20, "global_read", one()),
+            declare(21, "_global_ignored", None)
+        ]).unwrap();
+
+        assert_eq!(warnings, vec![
+            checking::Warning::UnusedVariable(at(2), "unused".to_string()),
+            checking::Warning::VariableNeverRead(at(3), "written".to_string()),
+            checking::Warning::UnusedParameter(at(7), "flag".to_string()),
+            checking::Warning::UnusedVariable(at(18), "global_unused".to_string()),
+            checking::Warning::VariableNeverRead(at(19), "global_written".to_string()),
+            checking::Warning::UnusedFunction(at(10), "unused".to_string(), vec![]),
+            checking::Warning::UnusedFunction(at(13), "also_unused".to_string(), vec![])
+        ]);
+        assert_eq!(warnings[1].code(), "W0002");
+    }
+
+    #[test]
+    fn arrays() {
+        let mut chkr = new_empty_checker();
+
+        let index_expr = |identifier: &str, value| parsing::Expression::Index {
+            span: Span::new(), identifier: identifier.to_string(),
+            index: Box::new(parsing::Expression::IntegerLiteral { span: Span::new(), value })
+        };
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Num".to_string(), length: 3, identifier: "values".to_string()
+            }),
+            Ok((vec![checking::Instruction::LocalArray { id: 0, length: 3 }], 3, None, false))
+        );
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::ElementAssignment {
+                span: Span::new(), identifier: "values".to_string(),
+                index: parsing::Expression::IntegerLiteral { span: Span::new(), value: 1 },
+                assign_to: parsing::Expression::IntegerLiteral { span: Span::new(), value: 5 }
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(1)),
+                    checking::Instruction::Push(checking::Value::Num(5.0)),
+                    checking::Instruction::StoreElement { id: 0, length: 3, line_number: Span::new().start.line_number }
+                ],
+                0, None, false
+            ))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(index_expr("values", 2)),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(2)),
+                    checking::Instruction::PushElement { id: 0, length: 3, line_number: Span::new().start.line_number }
+                ],
+                checking::Type::Num, Span::new()
+            ))
+        );
+
+        // Index must be an integer:
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Index {
+                span: Span::new(), identifier: "values".to_string(),
+                index: Box::new(parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 })
+            }),
+            Err(checking::Failure::UnexpectedType { expected: checking::Type::Int, encountered: checking::Type::Num, .. })
+        );
+
+        // Elements must be of the array's element type:
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::ElementAssignment {
+                span: Span::new(), identifier: "values".to_string(),
+                index: parsing::Expression::IntegerLiteral { span: Span::new(), value: 0 },
+                assign_to: parsing::Expression::BooleanLiteral { span: Span::new(), value: true }
+            }),
+            Err(checking::Failure::UnexpectedType { expected: checking::Type::Num, encountered: checking::Type::Bool, .. })
+        );
+
+        // Arrays cannot be used without being indexed:
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Variable { span: Span::new(), identifier: "values".to_string() }),
+            Err(checking::Failure::ArrayNotIndexed(_, _))
+        );
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::VariableAssignment {
+                span: Span::new(),
+                identifier: "values".to_string(),
+                assign_to: parsing::Expression::NumberLiteral { span: Span::new(), value: 1.0 }
+            }),
+            Err(checking::Failure::ArrayNotIndexed(_, _))
+        );
+
+        // Only arrays may be indexed:
+        chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+            span: Span::new(),
+            var_type: "Int".to_string(), identifier: "x".to_string(), value: None
+        }).unwrap();
+        assert_pattern!(chkr.eval_expr(index_expr("x", 0)), Err(checking::Failure::NonArrayIndexed(_, _, checking::Type::Int)));
+
+        // Redeclaring an array with a different length:
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Num".to_string(), length: 4, identifier: "values".to_string()
+            }),
+            Err(checking::Failure::VariableRedeclaredToDifferentType(Box::new(checking::VariableRedeclaration {
+                span: Span::new(),
+                identifier: "values".to_string(),
+                expected: checking::Type::Array(Box::new(checking::Type::Num), 3),
+                encountered: checking::Type::Array(Box::new(checking::Type::Num), 4),
+                previous_span: Span::new()
+            })))
+        );
+
+        // Redeclaring an array with the same length sets its elements to zero
+        // once more:
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Num".to_string(), length: 3, identifier: "values".to_string()
+            }),
+            Ok((vec![checking::Instruction::ClearArray { id: 0, length: 3 }], 0, None, false))
+        );
+
+        // Arrays may only have so many elements:
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Int".to_string(), length: 9999999999999, identifier: "big".to_string()
+            }),
+            Err(checking::Failure::ArrayTooLong(Span::new(), 9999999999999))
+        );
+
+        // Global arrays:
+        assert_eq!(
+            chkr.eval_top_level_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Char".to_string(), length: 8, identifier: "letters".to_string()
+            }),
+            Ok(vec![checking::Instruction::GlobalArray { id: 2, length: 8 }])
+        );
+
+        assert_eq!(
+            chkr.eval_top_level_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Char".to_string(), length: 8, identifier: "letters".to_string()
+            }),
+            Ok(vec![])
+        );
+        assert_eq!(chkr.global_initialisation, vec![checking::Instruction::ClearArray { id: 2, length: 8 }]);
+    }
+
+    #[test]
+    fn records() {
+        let mut chkr = new_empty_checker();
+
+        let field = |field_type: &str, identifier: &str| parsing::Field {
+            field_type: field_type.to_string(), identifier: identifier.to_string(), span: Span::new()
+        };
+        let num = |value| parsing::Expression::NumberLiteral { span: Span::new(), value };
+        let construct = |type_identifier: &str, args| parsing::Expression::Construction {
+            span: Span::new(), type_identifier: type_identifier.to_string(), args
+        };
+        let access = |record, field: &str| parsing::Expression::FieldAccess {
+            span: Span::new(), record: Box::new(record), field: field.to_string()
+        };
+        let line_var = || parsing::Expression::Variable { span: Span::new(), identifier: "l".to_string() };
+
+        assert_eq!(
+            chkr.eval_top_level_stmt(parsing::Statement::RecordDefinition {
+                span: Span::new(), identifier: "Point".to_string(), fields: vec![field("Num", "x"), field("Num", "y")]
+            }),
+            Ok(vec![])
+        );
+        assert_eq!(
+            chkr.eval_top_level_stmt(parsing::Statement::RecordDefinition {
+                span: Span::new(), identifier: "Line".to_string(),
+                fields: vec![field("Point", "start"), field("Point", "end"), field("Char", "tag")]
+            }),
+            Ok(vec![])
+        );
+
+        assert_pattern!(
+            chkr.eval_top_level_stmt(parsing::Statement::RecordDefinition {
+                span: Span::new(), identifier: "Point".to_string(), fields: vec![]
+            }),
+            Err(checking::Failure::RedefinedType(_, _))
+        );
+        assert_pattern!(
+            chkr.eval_top_level_stmt(parsing::Statement::RecordDefinition {
+                span: Span::new(), identifier: "Pair".to_string(), fields: vec![field("Int", "a"), field("Num", "a")]
+            }),
+            Err(checking::Failure::DuplicateField { .. })
+        );
+        // A record type cannot contain itself:
+        assert_pattern!(
+            chkr.eval_top_level_stmt(parsing::Statement::RecordDefinition {
+                span: Span::new(), identifier: "Node".to_string(), fields: vec![field("Node", "next")]
+            }),
+            Err(checking::Failure::NonexistentPrimitiveType(_, _))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(construct("Point", vec![num(1.0), parsing::Expression::IntegerLiteral { span: Span::new(), value: 2 }])),
+            Ok((
+                vec![checking::Instruction::Push(checking::Value::Num(1.0)), checking::Instruction::Push(checking::Value::Num(2.0))],
+                checking::Type::Record("Point".to_string()), Span::new()
+            ))
+        );
+        assert_pattern!(
+            chkr.eval_expr(construct("Point", vec![num(1.0)])),
+            Err(checking::Failure::ConstructionArgumentCount { expected: 2, encountered: 1, .. })
+        );
+        assert_pattern!(
+            chkr.eval_expr(construct("Point", vec![num(1.0), parsing::Expression::CharLiteral { span: Span::new(), value: 'a' }])),
+            Err(checking::Failure::UnexpectedType { expected: checking::Type::Num, encountered: checking::Type::Char, .. })
+        );
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(),
+                var_type: "Line".to_string(), identifier: "l".to_string(), value: None
+            }),
+            Ok((vec![checking::Instruction::LocalRecord { id: 0, size: 5 }], 5, None, false))
+        );
+
+        assert_eq!(
+            chkr.eval_expr(access(access(line_var(), "end"), "y")),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Variable(0)),
+                    checking::Instruction::Field { record_size: 5, offset: 2, size: 2 },
+                    checking::Instruction::Field { record_size: 2, offset: 1, size: 1 }
+                ],
+                checking::Type::Num, Span::new()
+            ))
+        );
+        assert_pattern!(chkr.eval_expr(access(line_var(), "middle")), Err(checking::Failure::UndefinedField { .. }));
+        assert_pattern!(chkr.eval_expr(access(access(line_var(), "tag"), "x")), Err(checking::Failure::UndefinedField { .. }));
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::FieldAssignment {
+                span: Span::new(), identifier: "l".to_string(), fields: vec!["end".to_string(), "x".to_string()],
+                assign_to: num(3.0)
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Num(3.0)),
+                    checking::Instruction::StoreField { id: 0, offset: 2, size: 1 }
+                ],
+                0, None, false
+            ))
+        );
+
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Display(Span::new(), line_var())),
+            Err(checking::Failure::CompoundValueNotAllowed { usage: "displayed", .. })
+        );
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Equal(Span::new(), Box::new(line_var()), Box::new(line_var()))),
+            Err(checking::Failure::CompoundValueNotAllowed { usage: "compared", .. })
+        );
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::ArrayDeclaration {
+                span: Span::new(), element_type: "Point".to_string(), length: 2, identifier: "points".to_string()
+            }),
+            Err(checking::Failure::NonPrimitiveArrayElement(_, _))
+        );
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::RecordDefinition {
+                span: Span::new(), identifier: "Inner".to_string(), fields: vec![]
+            }),
+            Err(checking::Failure::NestedTypeDefinition(_, _))
+        );
+
+        // Global records:
+        assert_eq!(
+            chkr.eval_top_level_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(),
+                var_type: "Point".to_string(), identifier: "origin".to_string(), value: None
+            }),
+            Ok(vec![checking::Instruction::GlobalRecord { id: 1, size: 2 }])
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let mut chkr = new_empty_checker();
+
+        let field = |field_type: &str, identifier: &str| parsing::Field {
+            field_type: field_type.to_string(), identifier: identifier.to_string(), span: Span::new()
+        };
+        let variant = |identifier: &str, fields| parsing::Variant { identifier: identifier.to_string(), fields, span: Span::new() };
+        let binding = |param_type: &str, identifier: &str| parsing::Parameter {
+            param_type: param_type.to_string(), identifier: identifier.to_string(), span: Span::new()
+        };
+        let arm = |variant: &str, bindings| parsing::MatchArm {
+            variant: variant.to_string(), bindings, span: Span::new(),
+            block: vec![parsing::Statement::Display(Span::new(), parsing::Expression::IntegerLiteral { span: Span::new(), value: 0 })]
+        };
+        let shape_var = || parsing::Expression::Variable { span: Span::new(), identifier: "s".to_string() };
+
+        assert_eq!(
+            chkr.eval_top_level_stmt(parsing::Statement::EnumDefinition {
+                span: Span::new(), identifier: "Shape".to_string(),
+                variants: vec![
+                    variant("Circle", vec![field("Num", "radius")]),
+                    variant("Rectangle", vec![field("Num", "width"), field("Num", "height")]),
+                    variant("Empty", vec![])
+                ]
+            }),
+            Ok(vec![])
+        );
+
+        // Variant identifiers must not be in use by any other type or variant:
+        assert_pattern!(
+            chkr.eval_top_level_stmt(parsing::Statement::EnumDefinition {
+                span: Span::new(), identifier: "Other".to_string(), variants: vec![variant("Empty", vec![])]
+            }),
+            Err(checking::Failure::RedefinedType(_, _))
+        );
+        assert_pattern!(
+            chkr.eval_top_level_stmt(parsing::Statement::EnumDefinition {
+                span: Span::new(), identifier: "Other".to_string(), variants: vec![variant("Int", vec![])]
+            }),
+            Err(checking::Failure::RedefinedType(_, _))
+        );
+
+        // Values are constructed with the tag of the variant first and padded to
+        // the size of the largest variant:
+        assert_eq!(
+            chkr.eval_expr(parsing::Expression::Construction {
+                span: Span::new(), type_identifier: "Circle".to_string(),
+                args: vec![parsing::Expression::NumberLiteral { span: Span::new(), value: 2.5 }]
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Int(0)),
+                    checking::Instruction::Push(checking::Value::Num(2.5)),
+                    checking::Instruction::Push(checking::Value::Int(0))
+                ],
+                checking::Type::Enum("Shape".to_string()), Span::new()
+            ))
+        );
+        assert_pattern!(
+            chkr.eval_expr(parsing::Expression::Construction { span: Span::new(), type_identifier: "Circle".to_string(), args: vec![] }),
+            Err(checking::Failure::ConstructionArgumentCount { expected: 1, encountered: 0, .. })
+        );
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(),
+                var_type: "Shape".to_string(), identifier: "s".to_string(), value: None
+            }),
+            Ok((vec![checking::Instruction::LocalRecord { id: 0, size: 3 }], 3, None, false))
+        );
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::Match {
+                span: Span::new(),
+                expr: shape_var(),
+                arms: vec![arm("Empty", vec![]), arm("Circle", vec![binding("Num", "r")]), arm("Rectangle", vec![binding("Num", "w"), binding("Num", "h")])]
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Push(checking::Value::Variable(0)),
+                    checking::Instruction::LocalRecord { id: 1, size: 3 },
+                    checking::Instruction::Store(1),
+                    // Jump to the arm for the variant with the matching tag:
+                    checking::Instruction::Push(checking::Value::Variable(1)),
+                    checking::Instruction::Field { record_size: 3, offset: 0, size: 1 },
+                    checking::Instruction::Push(checking::Value::Int(2)),
+                    checking::Instruction::Equals,
+                    checking::Instruction::JumpIfTrue(2),
+                    checking::Instruction::Push(checking::Value::Variable(1)),
+                    checking::Instruction::Field { record_size: 3, offset: 0, size: 1 },
+                    checking::Instruction::Push(checking::Value::Int(0)),
+                    checking::Instruction::Equals,
+                    checking::Instruction::JumpIfTrue(3),
+                    checking::Instruction::Push(checking::Value::Variable(1)),
+                    checking::Instruction::Field { record_size: 3, offset: 0, size: 1 },
+                    checking::Instruction::Push(checking::Value::Int(1)),
+                    checking::Instruction::Equals,
+                    checking::Instruction::JumpIfTrue(4),
+                    // Empty:
+                    checking::Instruction::Label(2),
+                    checking::Instruction::Push(checking::Value::Int(0)),
+                    checking::Instruction::Display { value_type: checking::DisplayType::Int, line_number: Span::new().start.line_number },
+                    checking::Instruction::Jump(5),
+                    // Circle:
+                    checking::Instruction::Label(3),
+                    checking::Instruction::Local(6),
+                    checking::Instruction::Push(checking::Value::Variable(1)),
+                    checking::Instruction::Field { record_size: 3, offset: 1, size: 1 },
+                    checking::Instruction::Store(6),
+                    checking::Instruction::Push(checking::Value::Int(0)),
+                    checking::Instruction::Display { value_type: checking::DisplayType::Int, line_number: Span::new().start.line_number },
+                    checking::Instruction::Jump(5),
+                    // Rectangle (reusing the ID of the variable from the previous
+                    // arm):
+                    checking::Instruction::Label(4),
+                    checking::Instruction::Local(6),
+                    checking::Instruction::Push(checking::Value::Variable(1)),
+                    checking::Instruction::Field { record_size: 3, offset: 1, size: 1 },
+                    checking::Instruction::Store(6),
+                    checking::Instruction::Local(7),
+                    checking::Instruction::Push(checking::Value::Variable(1)),
+                    checking::Instruction::Field { record_size: 3, offset: 2, size: 1 },
+                    checking::Instruction::Store(7),
+                    checking::Instruction::Push(checking::Value::Int(0)),
+                    checking::Instruction::Display { value_type: checking::DisplayType::Int, line_number: Span::new().start.line_number },
+                    checking::Instruction::Jump(5),
+                    checking::Instruction::Label(5)
+                ],
+                6, None, false
+            ))
+        );
+
+        match chkr.eval_inner_stmt(parsing::Statement::Match { span: Span::new(), expr: shape_var(), arms: vec![arm("Circle", vec![binding("Num", "r")])] }) {
+            Err(checking::Failure::NonExhaustiveMatch { enum_type, missing, .. }) => {
+                assert_eq!(enum_type, "Shape".to_string());
+                assert_eq!(missing, vec!["Rectangle".to_string(), "Empty".to_string()]);
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Match {
+                span: Span::new(),
+                expr: shape_var(), arms: vec![arm("Empty", vec![]), arm("Empty", vec![])]
+            }),
+            Err(checking::Failure::DuplicateMatchArm(_, _))
+        );
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Match { span: Span::new(), expr: shape_var(), arms: vec![arm("Square", vec![])] }),
+            Err(checking::Failure::UndefinedVariant { .. })
+        );
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Match {
+                span: Span::new(),
+                expr: parsing::Expression::IntegerLiteral { span: Span::new(), value: 1 }, arms: vec![arm("Empty", vec![])]
+            }),
+            Err(checking::Failure::NonEnumMatched(_, checking::Type::Int))
+        );
+
+        // Arm payload types must match the fields of the variant:
+        let all_arms = |circle_bindings| vec![arm("Circle", circle_bindings), arm("Rectangle", vec![binding("Num", "w"), binding("Num", "h")]), arm("Empty", vec![])];
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Match { span: Span::new(), expr: shape_var(), arms: all_arms(vec![binding("Int", "r")]) }),
+            Err(checking::Failure::UnexpectedType { expected: checking::Type::Num, encountered: checking::Type::Int, .. })
+        );
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Match { span: Span::new(), expr: shape_var(), arms: all_arms(vec![]) }),
+            Err(checking::Failure::MatchArmBindingCount { expected: 1, encountered: 0, .. })
+        );
+
+        assert_pattern!(
+            chkr.eval_inner_stmt(parsing::Statement::Display(Span::new(), shape_var())),
+            Err(checking::Failure::CompoundValueNotAllowed { usage: "displayed", .. })
+        );
+    }
+
+    #[test]
+    fn variable_shadowing() -> checking::Result<()> {
+        let mut chkr = new_empty_checker();
+
+        let span = Span::new();
+
+        chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+            span: Span::new(),
+            identifier: "x".to_string(),
+            var_type: "Num".to_string(),
+            value: None
+        })?;
+
+        chkr.begin_new_scope();
+
+        // Shadow variable 'x' by declaring a variable in the inner scope of the
+        // same name but a different type:
+        chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+            span: Span::new(),
+            identifier: "x".to_string(),
+            var_type: "Bool".to_string(),
+            value: None
+        })?;
+
+        assert_eq!(chkr.variable_lookup("x", &span)?.var_type, checking::Type::Bool);
+
+        chkr.end_scope();
+
+        assert_eq!(chkr.variable_lookup("x", &span)?.var_type, checking::Type::Num);
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::VariableDeclaration {
+                span: Span::new(),
+                identifier: "x".to_string(),
                 var_type: "Char".to_string(),
                 value: None
             }),
-            Err(checking::Failure::VariableRedeclaredToDifferentType {
+            Err(checking::Failure::VariableRedeclaredToDifferentType(Box::new(checking::VariableRedeclaration {
+                span: Span::new(),
                 identifier: "x".to_string(),
                 expected: checking::Type::Num,
-                encountered: checking::Type::Char
-            })
+                encountered: checking::Type::Char,
+                previous_span: Span::new()
+            })))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn inferred_declarations() -> checking::Result<()> {
+        let mut chkr = new_empty_checker();
+
+        let span = Span::new();
+        let x_var = || Box::new(parsing::Expression::Variable { span: Span::new(), identifier: "x".to_string() });
+        let int = |value| parsing::Expression::IntegerLiteral { span: Span::new(), value };
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::InferredDeclaration { span: Span::new(), identifier: "x".to_string(), value: int(5) }),
+            Ok((
+                vec![
+                    checking::Instruction::Local(0),
+                    checking::Instruction::Push(checking::Value::Int(5)),
+                    checking::Instruction::Store(0)
+                ],
+                1, None, false
+            ))
+        );
+        assert_eq!(chkr.variable_lookup("x", &span)?.var_type, checking::Type::Int);
+
+        // Redeclaring in the same scope is only allowed for a value of the same
+        // type:
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::InferredDeclaration { span: Span::new(), identifier: "x".to_string(), value: int(7) }),
+            Ok((
+                vec![checking::Instruction::Push(checking::Value::Int(7)), checking::Instruction::Store(0)],
+                0, None, false
+            ))
+        );
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::InferredDeclaration {
+                span: Span::new(),
+                identifier: "x".to_string(),
+                value: parsing::Expression::BooleanLiteral { span: Span::new(), value: true }
+            }),
+            Err(checking::Failure::VariableRedeclaredToDifferentType(Box::new(checking::VariableRedeclaration {
+                span: Span::new(),
+                identifier: "x".to_string(),
+                expected: checking::Type::Int,
+                encountered: checking::Type::Bool,
+                previous_span: Span::new()
+            })))
+        );
+
+        // A shadowed variable may be used in the initial value of the variable
+        // shadowing it:
+        chkr.begin_new_scope();
+
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::InferredDeclaration {
+                span: Span::new(),
+                identifier: "x".to_string(),
+                value: parsing::Expression::GreaterThan(Span::new(), x_var(), Box::new(int(2)))
+            }),
+            Ok((
+                vec![
+                    checking::Instruction::Local(1),
+                    checking::Instruction::Push(checking::Value::Variable(0)),
+                    checking::Instruction::Push(checking::Value::Int(2)),
+                    checking::Instruction::IntGreaterThan,
+                    checking::Instruction::Store(1)
+                ],
+                1, None, false
+            ))
+        );
+        assert_eq!(chkr.variable_lookup("x", &span)?.var_type, checking::Type::Bool);
+
+        chkr.end_scope();
+
+        // Functions with no return value cannot be used to infer a type:
+        chkr.add_function_def(Span::new(), "nothing".to_string(), vec![], None, "func0".to_string());
+        assert_eq!(
+            chkr.eval_inner_stmt(parsing::Statement::InferredDeclaration {
+                span: Span::new(),
+                identifier: "y".to_string(),
+                value: parsing::Expression::FunctionCall { span: Span::new(), identifier: "nothing".to_string(), args: vec![] }
+            }),
+            Err(checking::Failure::VoidFunctionInExpr(Span::new(), "nothing".to_string(), vec![]))
         );
+        assert_pattern!(chkr.variable_lookup("y", &span), Err(checking::Failure::VariableNotInScope(..)));
 
         Ok(())
     }
@@ -9,75 +9,182 @@ use std::fmt;
 
 #[derive(Debug, PartialEq)]
 pub enum Failure {
-    NonexistentPrimitiveType(String),
-    VariableNotInScope(stream::Position, String),
-    FunctionUndefined(stream::Position, String, Vec<Type>),
-    VoidFunctionInExpr(stream::Position, String, Vec<Type>),
-    RedefinedExistingFunction(String, Vec<Type>),
-    VoidFunctionReturnsValue(stream::Position, String, Vec<Type>, Type),
-    FunctionUnexpectedReturnType {
-        pos: stream::Position,
-        identifier: String, params: Vec<Type>,
-        expected: Type, encountered: Option<Type>,
-    },
-    VariableRedeclaredToDifferentType {
-        identifier: String,
-        expected: Type, encountered: Type
-    },
-    UnexpectedType { pos: stream::Position, expected: Type, encountered: Type },
-    InvalidTopLevelStatement,
-    NestedFunctions(stream::Position, String),
+    NonexistentPrimitiveType(stream::Span, String),
+    VariableNotInScope(stream::Span, String),
+    UseOfUninitialisedVariable(stream::Span, String),
+    FunctionUndefined(stream::Span, String, Vec<Type>),
+    VoidFunctionInExpr(stream::Span, String, Vec<Type>),
+    RedefinedExistingFunction(Box<FunctionRedefinition>),
+    VoidFunctionReturnsValue(stream::Span, String, Vec<Type>, Box<Type>),
+    FunctionUnexpectedReturnType(Box<UnexpectedReturnType>),
+    FunctionMayNotReturn(Box<MissingReturn>),
+    VariableRedeclaredToDifferentType(Box<VariableRedeclaration>),
+    UnexpectedType { span: stream::Span, expected: Type, encountered: Type },
+    InvalidConversion { span: stream::Span, from: Type, to: Type },
+    ConstructionArgumentCount { span: stream::Span, type_identifier: String, expected: usize, encountered: usize },
+    RedefinedType(stream::Span, String),
+    DuplicateField { span: stream::Span, type_identifier: String, field: String },
+    UndefinedField { span: stream::Span, record_type: Type, field: String },
+    CompoundValueNotAllowed { span: stream::Span, type_identifier: String, usage: &'static str },
+    NonPrimitiveArrayElement(stream::Span, String),
+    ArrayNotIndexed(stream::Span, String),
+    NonArrayIndexed(stream::Span, String, Type),
+    ArrayTooLong(stream::Span, usize),
+    NonEnumMatched(stream::Span, Type),
+    UndefinedVariant { span: stream::Span, enum_type: String, variant: String },
+    DuplicateMatchArm(stream::Span, String),
+    MatchArmBindingCount { span: stream::Span, variant: String, expected: usize, encountered: usize },
+    NonExhaustiveMatch { span: stream::Span, enum_type: String, missing: Vec<String> },
+    InvalidTopLevelStatement(stream::Span),
+    NestedFunctions(stream::Span, String),
+    NestedTypeDefinition(stream::Span, String),
     MainUndefined
 }
 
+// The details of the failures below are boxed so as to keep `Failure` (and so
+// every `Result` containing one) small.
+
+#[derive(Debug, PartialEq)]
+pub struct FunctionRedefinition {
+    pub span: stream::Span,
+    pub identifier: String,
+    pub params: Vec<Type>,
+    pub previous_span: stream::Span
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnexpectedReturnType {
+    pub span: stream::Span,
+    pub identifier: String,
+    pub params: Vec<Type>,
+    pub expected: Type,
+    pub encountered: Option<Type>
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MissingReturn {
+    pub span: stream::Span,
+    pub identifier: String,
+    pub params: Vec<Type>,
+    pub expected: Type,
+    pub fall_through_span: stream::Span
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VariableRedeclaration {
+    pub span: stream::Span,
+    pub identifier: String,
+    pub expected: Type,
+    pub encountered: Type,
+    pub previous_span: stream::Span
+}
+
 impl fmt::Display for Failure {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Failure::NonexistentPrimitiveType(ident) =>
-                write!(f, "The primitive type '{}' does not exist - please use either Num, Char or Bool", ident),
+            Failure::NonexistentPrimitiveType(span, ident) =>
+                write!(f, "The type '{}' used at {} does not exist - please use either Int, Num, Char, Bool, String or a defined record type", ident, span),
 
-            Failure::VariableNotInScope(pos, ident) =>
+            Failure::VariableNotInScope(span, ident) =>
                 write!(f, "Reference made at {} to variable '{}' which is either undefined or inaccessible from the current scope",
-                       pos, ident),
+                       span, ident),
+
+            Failure::UseOfUninitialisedVariable(span, ident) =>
+                write!(f, "Variable '{}' referenced at {} may not have been assigned a value along every path to that point",
+                       ident, span),
 
-            Failure::FunctionUndefined(pos, ident, params) =>
+            Failure::FunctionUndefined(span, ident, params) =>
                 write!(f, "Call made at {} to function '{}' with parameter types {:?} which is not yet defined",
-                       pos, ident, params),
+                       span, ident, params),
 
-            Failure::VoidFunctionInExpr(pos, ident, params) =>
+            Failure::VoidFunctionInExpr(span, ident, params) =>
                 write!(f, "Function '{}' with parameter types {:?} has no return value and so cannot be used in an expression at {}",
-                       ident, params, pos),
+                       ident, params, span),
 
-            Failure::RedefinedExistingFunction(ident, params) =>
-                write!(f, "Function '{}' with parameter types {:?} has already been defined",
-                       ident, params),
+            Failure::RedefinedExistingFunction(failure) =>
+                write!(f, "Function '{}' with parameter types {:?} defined at {} has already been defined",
+                       failure.identifier, failure.params, failure.span),
 
-            Failure::VoidFunctionReturnsValue(pos, ident, params, ret_type) =>
+            Failure::VoidFunctionReturnsValue(span, ident, params, ret_type) =>
                 write!(f, "Function '{}' with parameter types {:?} at {} defined without return type yet has a block that returns a value of type {:?}",
-                       ident, params, pos, ret_type),
+                       ident, params, span, ret_type),
 
-            Failure::FunctionUnexpectedReturnType { pos, identifier, params, expected, encountered } => {
+            Failure::FunctionUnexpectedReturnType(failure) => {
                 let encountered_as_string = {
-                    if let Some(encountered_type) = encountered { format!("{:?}", encountered_type) }
+                    if let Some(encountered_type) = &failure.encountered { format!("{:?}", encountered_type) }
                     else { "nothing".to_string() }
                 };
                 write!(f, "Function '{}' with parameter types {:?} at {} expected to return a value of type {:?} yet found to return {}",
-                       identifier, params, pos, expected, encountered_as_string)
+                       failure.identifier, failure.params, failure.span, failure.expected, encountered_as_string)
             }
 
-            Failure::VariableRedeclaredToDifferentType { identifier, expected, encountered } =>
-                write!(f, "Attempt made to redeclare variable '{}' of type {:?} to different type {:?} in the same scope",
-                       identifier, expected, encountered),
+            Failure::FunctionMayNotReturn(failure) =>
+                write!(f, "Function '{}' with parameter types {:?} at {} expected to return a value of type {:?} yet may reach the end of its body without returning after the statement at {}",
+                       failure.identifier, failure.params, failure.span, failure.expected, failure.fall_through_span),
 
-            Failure::UnexpectedType { pos, expected, encountered } =>
+            Failure::VariableRedeclaredToDifferentType(failure) =>
+                write!(f, "Attempt made at {} to redeclare variable '{}' of type {:?} to different type {:?} in the same scope",
+                       failure.span, failure.identifier, failure.expected, failure.encountered),
+
+            Failure::UnexpectedType { span, expected, encountered } =>
                 write!(f, "Expected type {:?} yet enountered {:?} at {}",
-                       expected, encountered, pos),
-            
-            Failure::InvalidTopLevelStatement =>
-                write!(f, "Only global variable and function definition statements are allowed at the top-level"),
+                       expected, encountered, span),
+
+            Failure::InvalidConversion { span, from, to } =>
+                write!(f, "Conversion at {} from type {:?} to type {:?} is not allowed", span, from, to),
+
+            Failure::ConstructionArgumentCount { span, type_identifier, expected, encountered } =>
+                write!(f, "Creation of a value of type '{}' at {} requires {} argument(s) yet {} were given",
+                       type_identifier, span, expected, encountered),
+
+            Failure::RedefinedType(span, ident) =>
+                write!(f, "Type '{}' defined at {} has already been defined", ident, span),
+
+            Failure::DuplicateField { span, type_identifier, field } =>
+                write!(f, "Type '{}' has more than one field named '{}' (see {})", type_identifier, field, span),
+
+            Failure::UndefinedField { span, record_type, field } =>
+                write!(f, "Value of type {:?} has no field named '{}' as accessed at {}", record_type, field, span),
+
+            Failure::CompoundValueNotAllowed { span, type_identifier, usage } =>
+                write!(f, "Value of record or enum type '{}' at {} cannot be {}", type_identifier, span, usage),
 
-            Failure::NestedFunctions(pos, ident) =>
-                write!(f, "Function '{}' at {} cannot be defined as it is contained within the body of another function", ident, pos),
+            Failure::NonPrimitiveArrayElement(span, ident) =>
+                write!(f, "Array declared at {} has elements of type '{}' yet arrays may only contain values of a primitive type",
+                       span, ident),
+
+            Failure::ArrayNotIndexed(span, ident) =>
+                write!(f, "Array '{}' referenced at {} must be indexed in order to access one of its elements", ident, span),
+
+            Failure::NonArrayIndexed(span, ident, var_type) =>
+                write!(f, "Variable '{}' of type {:?} indexed at {} is not an array", ident, var_type, span),
+
+            Failure::ArrayTooLong(span, length) =>
+                write!(f, "Array declared at {} has {} elements yet arrays may have at most {} elements", span, length, MAX_ARRAY_LENGTH),
+
+            Failure::NonEnumMatched(span, matched_type) =>
+                write!(f, "Value matched at {} is of type {:?} yet only values of an enum type may be matched", span, matched_type),
+
+            Failure::UndefinedVariant { span, enum_type, variant } =>
+                write!(f, "Enum type '{}' has no variant named '{}' as matched at {}", enum_type, variant, span),
+
+            Failure::DuplicateMatchArm(span, variant) =>
+                write!(f, "Variant '{}' is matched more than once (see {})", variant, span),
+
+            Failure::MatchArmBindingCount { span, variant, expected, encountered } =>
+                write!(f, "Variant '{}' matched at {} has {} field(s) yet {} were bound", variant, span, expected, encountered),
+
+            Failure::NonExhaustiveMatch { span, enum_type, missing } =>
+                write!(f, "Match on value of enum type '{}' at {} does not handle the variant(s): {}", enum_type, span, missing.join(", ")),
+
+            Failure::InvalidTopLevelStatement(span) =>
+                write!(f, "Statement at {} is not allowed at the top-level - only global variable and function definition statements are", span),
+
+            Failure::NestedFunctions(span, ident) =>
+                write!(f, "Function '{}' at {} cannot be defined as it is contained within the body of another function", ident, span),
+
+            Failure::NestedTypeDefinition(span, ident) =>
+                write!(f, "Type '{}' at {} cannot be defined within the body of a function", ident, span),
 
             Failure::MainUndefined =>
                 write!(f, "All till programs are required to have a main function yet such a function could not be found")
@@ -85,23 +192,169 @@ impl fmt::Display for Failure {
     }
 }
 
+impl Failure {
+    /// Get the code identifying this kind of failure. These codes do not change
+    /// between versions so may be relied upon by tools consuming them. Codes
+    /// are grouped by what they concern: variables (`T00xx`), functions
+    /// (`T01xx`), types and values (`T02xx`), arrays (`T03xx`), enums and
+    /// match statements (`T04xx`), and program structure (`T05xx`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            Failure::VariableNotInScope(..) => "T0001",
+            Failure::UseOfUninitialisedVariable(..) => "T0002",
+            Failure::VariableRedeclaredToDifferentType(..) => "T0003",
+
+            Failure::FunctionUndefined(..) => "T0101",
+            Failure::RedefinedExistingFunction(..) => "T0102",
+            Failure::VoidFunctionInExpr(..) => "T0103",
+            Failure::VoidFunctionReturnsValue(..) => "T0104",
+            Failure::FunctionUnexpectedReturnType(..) => "T0105",
+            Failure::FunctionMayNotReturn(..) => "T0106",
+            Failure::NestedFunctions(..) => "T0107",
+            Failure::MainUndefined => "T0108",
+
+            Failure::NonexistentPrimitiveType(..) => "T0201",
+            Failure::UnexpectedType { .. } => "T0202",
+            Failure::InvalidConversion { .. } => "T0203",
+            Failure::ConstructionArgumentCount { .. } => "T0204",
+            Failure::RedefinedType(..) => "T0205",
+            Failure::DuplicateField { .. } => "T0206",
+            Failure::UndefinedField { .. } => "T0207",
+            Failure::CompoundValueNotAllowed { .. } => "T0208",
+            Failure::NestedTypeDefinition(..) => "T0209",
+
+            Failure::NonPrimitiveArrayElement(..) => "T0301",
+            Failure::ArrayNotIndexed(..) => "T0302",
+            Failure::NonArrayIndexed(..) => "T0303",
+            Failure::ArrayTooLong(..) => "T0304",
+
+            Failure::NonEnumMatched(..) => "T0401",
+            Failure::UndefinedVariant { .. } => "T0402",
+            Failure::DuplicateMatchArm(..) => "T0403",
+            Failure::MatchArmBindingCount { .. } => "T0404",
+            Failure::NonExhaustiveMatch { .. } => "T0405",
+
+            Failure::InvalidTopLevelStatement(_) => "T0501"
+        }
+    }
+
+    /// Get the span of the input stream at which this failure occurred, if
+    /// there is one.
+    pub fn span(&self) -> Option<&stream::Span> {
+        match self {
+            Failure::NonexistentPrimitiveType(span, ..) | Failure::InvalidTopLevelStatement(span) |
+            Failure::VariableNotInScope(span, ..) | Failure::UseOfUninitialisedVariable(span, ..) |
+            Failure::FunctionUndefined(span, ..) | Failure::VoidFunctionInExpr(span, ..) |
+            Failure::VoidFunctionReturnsValue(span, ..) | Failure::RedefinedType(span, ..) |
+            Failure::NonPrimitiveArrayElement(span, ..) | Failure::ArrayNotIndexed(span, ..) |
+            Failure::NonArrayIndexed(span, ..) | Failure::NonEnumMatched(span, ..) |
+            Failure::ArrayTooLong(span, _) |
+            Failure::DuplicateMatchArm(span, ..) | Failure::NestedFunctions(span, ..) |
+            Failure::NestedTypeDefinition(span, ..) |
+            Failure::UnexpectedType { span, .. } | Failure::InvalidConversion { span, .. } |
+            Failure::ConstructionArgumentCount { span, .. } | Failure::DuplicateField { span, .. } |
+            Failure::UndefinedField { span, .. } | Failure::CompoundValueNotAllowed { span, .. } |
+            Failure::UndefinedVariant { span, .. } | Failure::MatchArmBindingCount { span, .. } |
+            Failure::NonExhaustiveMatch { span, .. } => Some(span),
+
+            Failure::RedefinedExistingFunction(failure) => Some(&failure.span),
+            Failure::FunctionUnexpectedReturnType(failure) => Some(&failure.span),
+            Failure::FunctionMayNotReturn(failure) => Some(&failure.span),
+            Failure::VariableRedeclaredToDifferentType(failure) => Some(&failure.span),
+
+            Failure::MainUndefined => None
+        }
+    }
+}
+
 type Result<T> = std::result::Result<T, Failure>;
 
-/// Represents the types available in till: `Char`, `Num`, and `Bool`.
+/// Represents problems found during checking that, unlike a `Failure`, do not
+/// prevent a till program from being compiled.
+#[derive(Debug, PartialEq)]
+pub enum Warning {
+    UnusedVariable(stream::Span, String),
+    VariableNeverRead(stream::Span, String),
+    UnusedParameter(stream::Span, String),
+    UnusedFunction(stream::Span, String, Vec<Type>)
+}
+
+impl Warning {
+    /// Get the code identifying this kind of warning. These codes do not change
+    /// between versions so may be relied upon by tools consuming them.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Warning::UnusedVariable(..) => "W0001",
+            Warning::VariableNeverRead(..) => "W0002",
+            Warning::UnusedParameter(..) => "W0003",
+            Warning::UnusedFunction(..) => "W0004"
+        }
+    }
+
+    /// Get the span of the declaration this warning concerns.
+    pub fn span(&self) -> &stream::Span {
+        match self {
+            Warning::UnusedVariable(span, _) | Warning::VariableNeverRead(span, _) |
+            Warning::UnusedParameter(span, _) | Warning::UnusedFunction(span, ..) => span
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::UnusedVariable(span, ident) =>
+                write!(f, "Variable '{}' declared at {} is never used", ident, span),
+
+            Warning::VariableNeverRead(span, ident) =>
+                write!(f, "Variable '{}' declared at {} is assigned a value yet is never read", ident, span),
+
+            Warning::UnusedParameter(span, ident) =>
+                write!(f, "Parameter '{}' at {} is never used", ident, span),
+
+            Warning::UnusedFunction(span, ident, params) =>
+                write!(f, "Function '{}' with parameter types {:?} defined at {} is never called from the main function",
+                       ident, params, span)
+        }
+    }
+}
+
+/// Represents the types available in till: `Char`, `Int`, `Num`, `Bool`,
+/// `String`, fixed-size arrays of elements of one of those types, and
+/// user-defined record and enum types (identified by name).
 #[derive(Clone, Debug, PartialEq)]
-pub enum Type { Char, Num, Bool }
+pub enum Type { Char, Int, Num, Bool, String, Array(Box<Type>, usize), Record(String), Enum(String) }
 
 impl Type {
-    fn from_identifier(ident: &str) -> Result<Type> {
+    fn from_identifier(ident: &str) -> Option<Type> {
         match ident {
-            "Char" => Ok(Type::Char),
-            "Num" => Ok(Type::Num),
-            "Bool" => Ok(Type::Bool),
-            _ => Err(Failure::NonexistentPrimitiveType(ident.to_string()))
+            "Char" => Some(Type::Char),
+            "Int" => Some(Type::Int),
+            "Num" => Some(Type::Num),
+            "Bool" => Some(Type::Bool),
+            "String" => Some(Type::String),
+            _ => None
         }
     }
+
+    /// Whether values of this type are made up of multiple fields. A value of
+    /// an enum type is represented in the same way as a record with a first
+    /// field identifying the variant (its tag) followed by the fields of that
+    /// variant.
+    fn is_compound(&self) -> bool {
+        matches!(self, Type::Record(_) | Type::Enum(_))
+    }
 }
 
+/// The greatest number of elements an array may have. Local arrays are stored on
+/// the stack so this is kept well within its typical size limit.
+pub const MAX_ARRAY_LENGTH: usize = 1 << 16;
+
+/// The types of value that may be displayed, which excludes arrays as well as
+/// record and enum values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayType { Char, Int, Num, Bool, String }
+
 /// Represents a scope within a till program. A new scope is created in the body
 /// of a function definition, if statement, or while statement. Any variables
 /// declared in a given scope will only be accessible from within that scope or
@@ -120,31 +373,54 @@ impl Scope {
 
 pub type Id = usize;
 
-/// Definition of a variable with a given identifier and type.
+/// Definition of a variable with a given identifier and type, along with the
+/// span of its declaration and whether it is a function parameter.
 #[derive(Debug, PartialEq)]
 struct VariableDef {
     identifier: String,
     var_type: Type,
-    id: Id
+    id: Id,
+    span: stream::Span,
+    parameter: bool
 }
 
 /// Definition of a function with an identifier, set of parameters, and a return
 /// type.
 #[derive(Debug, PartialEq)]
 struct FunctionDef {
+    span: stream::Span,
     identifier: String,
     parameter_types: Vec<Type>,
     return_type: Option<Type>,
     label: String
 }
 
+/// Definition of a record type with an identifier and a set of named fields.
+/// Also used for the variants of an enum type, the fields of a variant being
+/// the values it holds.
+#[derive(Debug, PartialEq)]
+struct RecordDef {
+    identifier: String,
+    fields: Vec<(String, Type)>
+}
+
+/// Definition of an enum type with an identifier and a set of variants, the
+/// tag of each variant being its index.
+#[derive(Debug, PartialEq)]
+struct EnumDef {
+    identifier: String,
+    variants: Vec<RecordDef>
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Value {
     /// Value is determined by that of the variable with the specified ID.
     Variable(Id),
+    Int(i64),
     Num(f64),
     Char(char),
-    Bool(bool)
+    Bool(bool),
+    String(String)
 }
 
 /// Represents the simple, assembly-like instructions that make up the final
@@ -152,13 +428,48 @@ pub enum Value {
 #[derive(Debug, PartialEq)]
 pub enum Instruction {
     /// Create a global variable with a given ID.
-    //Global(Id),
+    Global(Id),
+    /// Create a global array with a given ID and number of elements.
+    GlobalArray { id: Id, length: usize },
+    /// Create a global compound variable (i.e. one of a record or enum type)
+    /// with a given ID occupying the given number of values. All `*Record`
+    /// instructions apply to values of enum types as well as record types.
+    GlobalRecord { id: Id, size: usize },
     /// Create a function parameter with a given ID.
     Parameter(Id),
+    /// Create a function parameter with a given ID for a compound value
+    /// occupying the given number of values.
+    ParameterRecord { id: Id, size: usize },
     /// Reserve stack space for a local variable with a given ID.
     Local(Id),
+    /// Reserve stack space for a local array with a given ID and number of
+    /// elements, setting each of those elements to zero.
+    LocalArray { id: Id, length: usize },
+    /// Set each element of the existing array with a given ID and number of
+    /// elements to zero.
+    ClearArray { id: Id, length: usize },
+    /// Reserve stack space for a local compound variable with a given ID that
+    /// occupies the given number of values, setting each of those values to
+    /// zero.
+    LocalRecord { id: Id, size: usize },
     /// Pop a value off the stack and store it in the specified variable.
     Store(Id),
+    /// Pop an index off the stack and push the element of the specified array
+    /// at that index. Should the index be out of bounds, the program is
+    /// terminated with the given line number being reported.
+    PushElement { id: Id, length: usize, line_number: u64 },
+    /// Pop a value and then an index off the stack, storing the value as the
+    /// element of the specified array at that index. Bounds are checked as with
+    /// `PushElement`.
+    StoreElement { id: Id, length: usize, line_number: u64 },
+    /// Pop a record occupying `record_size` values off the stack and push the
+    /// field of that record occupying `size` values beginning `offset` values
+    /// from the start of the record.
+    Field { record_size: usize, offset: usize, size: usize },
+    /// Pop a value occupying `size` values off the stack and store it in the
+    /// field beginning `offset` values from the start of the specified record
+    /// variable.
+    StoreField { id: Id, offset: usize, size: usize },
     /// Push the specified value onto the stack.
     Push(Value),
     /// Identify a point in the series of instructions that can be jumped to (e.g.
@@ -170,15 +481,23 @@ pub enum Instruction {
     /// instruction encountered. The function called should not return a value.
     CallExpectingVoid(String),
     CallExpectingValue(String),
+    /// Call a function that returns a compound value occupying the given
+    /// number of values.
+    CallExpectingRecord { label: String, size: usize },
     /// Return from call, returning value on top of stack. Will also result in
     /// the deallocation of all variables allocated since the last begin scope
     /// instruction.
     ReturnValue,
+    /// Return from call, returning the compound value occupying the given
+    /// number of values on top of the stack.
+    ReturnRecord { size: usize },
     /// Return from call without including a value. Also deallocates all variables
     /// since the last begin scope instruction.
     ReturnVoid,
+    /// Pop a value off the stack without making use of it.
+    Discard,
     /// Pop value off stack and display via stdout.
-    Display { value_type: Type, line_number: u64 },
+    Display { value_type: DisplayType, line_number: u64 },
     /// Jump to a given label.
     Jump(Id),
     /// Pop a value off the stack, if that value is true then jump to the particular
@@ -187,12 +506,52 @@ pub enum Instruction {
     JumpIfFalse(Id),
     /// Pop 2 items off the stack, push true if they are equal, false otherwise.
     Equals,
+    /// Pop 2 items off the stack, push true if they are not equal, false
+    /// otherwise.
+    NotEquals,
+    /// Pop 2 strings off the stack, push true if their contents are equal, false
+    /// otherwise.
+    StringEquals,
     GreaterThan,
     LessThan,
+    GreaterThanOrEqual,
+    LessThanOrEqual,
     Add,
     Subtract,
     Multiply,
     Divide,
+    /// Pop 2 items off the stack, push the remainder of dividing the second
+    /// item by the top item. The result has the same sign as the dividend.
+    Modulo,
+    /// Pop 2 items off the stack, push the second item raised to the power of
+    /// the top item.
+    Power,
+    /// Integer equivalents of the above arithmetic and comparison instructions.
+    /// Division and modulo truncate towards zero. Dividing by zero terminates
+    /// the program with an error message including the given line number.
+    IntAdd,
+    IntSubtract,
+    IntMultiply,
+    IntDivide { line_number: u64 },
+    IntModulo { line_number: u64 },
+    IntPower,
+    IntGreaterThan,
+    IntLessThan,
+    IntGreaterThanOrEqual,
+    IntLessThanOrEqual,
+    /// Pop an integer off the stack, push it converted to a number.
+    IntToNum,
+    /// Pop a number off the stack, push it converted to an integer by truncating
+    /// towards zero.
+    NumToInt,
+    /// Check the integer on top of the stack is a Unicode code point so that it
+    /// may be used as a character, terminating the program with an error
+    /// message including the given line number otherwise.
+    IntToChar { line_number: u64 },
+    /// Pop an integer or number off the stack, push true if it is nonzero,
+    /// false otherwise.
+    IntToBool,
+    NumToBool,
     /// Pop top of stack, perform boolean not, push result.
     Not
 }
\ No newline at end of file
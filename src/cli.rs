@@ -0,0 +1,404 @@
+//! Contains code for the parsing of command-line arguments into the command the
+//! compiler should carry out.
+
+use std::fmt;
+
+pub const USAGE: &str = "\
+Usage: till <COMMAND> [OPTIONS] <INPUT>...
+
+Commands:
+  compile    Compile till programs (to assembly code unless --emit is given)
+  check      Check till programs for errors and warnings without compiling
+  run        Compile a till program to an executable and then run it
+  fmt        Format till code, rewriting each input file in place
+  explain    Display a detailed explanation of an error or warning code
+  help       Display this message
+
+Each input is the path of a till file, or '-' to read from stdin. Several
+inputs may be given to compile, check or format each in turn.
+
+Options:
+  -o, --output <PATH>        Write output to the given path ('-' for stdout)
+      --emit <KIND>          Output to produce: tokens, ast, ir, asm, obj or exe
+      --target <TARGET>      Target to compile for (only x86_64-linux is supported)
+  -O<LEVEL>                  Assembler optimisation level from 0 to 3 (see below)
+  -q, --quiet                Only display errors, warnings and requested output
+      --deny-warnings        Treat warnings as errors
+      --max-diagnostics <N>  Display at most N errors and warnings (default 20)
+      --json-diagnostics     Write errors and warnings to stderr as JSON lines
+  -h, --help                 Display this message
+  -V, --version              Display the version of the compiler
+
+The optimisation level is passed on to NASM, where it only affects how jumps are
+encoded (levels 2 and 3 are the same). till itself does not optimise the code
+it produces. '-O' alone is level 2.
+
+Examples:
+  till compile fib.til                 Write assembly code to fib.asm
+  till compile fib.til --emit=exe -O2  Write an executable to fib
+  till run fib.til                     Compile and run fib.til
+  till check a.til b.til --deny-warnings
+  till fmt -                           Format code from stdin to stdout
+  till explain T0103";
+
+/// Number of errors and warnings displayed when not otherwise specified.
+pub const DEFAULT_MAX_DIAGNOSTICS: usize = 20;
+
+/// Optimisation level used when `-O` is given without a level.
+const DEFAULT_OPTIMISATION_LEVEL: u8 = 2;
+
+/// Error in the command-line arguments given, the message of which describes
+/// how they should be corrected.
+#[derive(Debug, PartialEq)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+type Result<T> = std::result::Result<T, UsageError>;
+
+fn usage_error<T>(message: String) -> Result<T> {
+    Err(UsageError(message))
+}
+
+/// Command to be carried out by the compiler.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Help,
+    Version,
+    Explain(String),
+    Process(Subcommand, Options)
+}
+
+/// Subcommand that processes input till code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Subcommand { Compile, Check, Run, Fmt }
+
+impl Subcommand {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "compile" => Some(Subcommand::Compile),
+            "check" => Some(Subcommand::Check),
+            "run" => Some(Subcommand::Run),
+            "fmt" => Some(Subcommand::Fmt),
+            _ => None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Subcommand::Compile => "compile",
+            Subcommand::Check => "check",
+            Subcommand::Run => "run",
+            Subcommand::Fmt => "fmt"
+        }
+    }
+
+    /// Whether the option with the given name may be given to this subcommand.
+    fn accepts(&self, option: &str) -> bool {
+        match option {
+            "--output" => matches!(self, Subcommand::Compile | Subcommand::Fmt),
+            "--emit" => *self == Subcommand::Compile,
+            "--target" | "-O" => matches!(self, Subcommand::Compile | Subcommand::Run),
+            "--deny-warnings" | "--max-diagnostics" | "--json-diagnostics" => *self != Subcommand::Fmt,
+            _ => true
+        }
+    }
+}
+
+/// Source of input till code.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    Stdin,
+    File(String)
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Stdin => write!(f, "<stdin>"),
+            Input::File(path) => write!(f, "{}", path)
+        }
+    }
+}
+
+/// Kind of output produced when compiling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Emit { Tokens, Ast, Ir, Asm, Obj, Exe }
+
+impl Emit {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tokens" => Some(Emit::Tokens),
+            "ast" => Some(Emit::Ast),
+            "ir" => Some(Emit::Ir),
+            "asm" => Some(Emit::Asm),
+            "obj" => Some(Emit::Obj),
+            "exe" => Some(Emit::Exe),
+            _ => None
+        }
+    }
+
+    /// Is this output binary rather than text?
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Emit::Obj | Emit::Exe)
+    }
+
+    /// The extension given to a file containing this output, should such
+    /// output be written to a file by default.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Emit::Asm => Some("asm"),
+            Emit::Obj => Some("o"),
+            Emit::Exe => Some(""),
+            Emit::Tokens | Emit::Ast | Emit::Ir => None
+        }
+    }
+}
+
+/// Machine and operating system compiled for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target { X86_64Linux }
+
+impl Target {
+    const NAMES: &'static [(&'static str, Target)] = &[("x86_64-linux", Target::X86_64Linux)];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES.iter().find(|(target_name, _)| *target_name == name).map(|(_, target)| *target)
+    }
+}
+
+/// Options affecting the processing of input till code and the display of
+/// errors and warnings.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub inputs: Vec<Input>,
+    pub output: Option<String>,
+    pub emit: Emit,
+    pub target: Target,
+    /// Passed on to NASM as its `-O` option when assembling, rather than
+    /// affecting the code produced by till.
+    pub optimisation_level: u8,
+    /// Should progress messages not be displayed?
+    pub quiet: bool,
+    /// Should warnings be treated as errors?
+    pub deny_warnings: bool,
+    /// The maximum number of errors and warnings to display.
+    pub max_diagnostics: usize,
+    /// Should errors and warnings be written to stderr as JSON objects rather
+    /// than displayed as text?
+    pub json_diagnostics: bool
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            inputs: Vec::new(), output: None, emit: Emit::Asm, target: Target::X86_64Linux,
+            optimisation_level: 0, quiet: false, deny_warnings: false,
+            max_diagnostics: DEFAULT_MAX_DIAGNOSTICS, json_diagnostics: false
+        }
+    }
+}
+
+/// Parse the given command-line arguments (excluding the name of the program).
+/// Help and version options take precedence over any other arguments.
+pub fn parse<I: IntoIterator<Item=String>>(args: I) -> Result<Command> {
+    let args: Vec<String> = args.into_iter().collect();
+
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") { return Ok(Command::Help) }
+    if args.iter().any(|arg| arg == "-V" || arg == "--version") { return Ok(Command::Version) }
+
+    let mut args = args.into_iter();
+
+    let subcommand = match args.next() {
+        Some(name) => match name.as_str() {
+            "help" => return Ok(Command::Help),
+            "explain" => return parse_explain(args),
+            _ => Subcommand::from_name(&name).ok_or_else(|| {
+                if name.starts_with('-') { UsageError(format!("expected a command before option '{}'", name)) }
+                else { UsageError(format!("unknown command '{}'", name)) }
+            })?
+        },
+        None => return usage_error("no command given".to_string())
+    };
+
+    let mut options = Options::default();
+    let mut given = Vec::new();
+    let mut only_inputs_follow = false;
+
+    while let Some(arg) = args.next() {
+        if only_inputs_follow || arg == "-" || !arg.starts_with('-') {
+            let input = if arg == "-" { Input::Stdin } else { Input::File(arg) };
+
+            if input == Input::Stdin && options.inputs.contains(&Input::Stdin) {
+                return usage_error("stdin ('-') may only be given as an input once".to_string());
+            }
+            options.inputs.push(input);
+            continue;
+        }
+
+        // Options taking a value may be given as either `--option value` or
+        // `--option=value`:
+        let (name, inline_value) = match arg.find('=') {
+            Some(index) if arg.starts_with("--") => (arg[..index].to_string(), Some(arg[index + 1..].to_string())),
+            _ => (arg.clone(), None)
+        };
+        let mut value = |name: &str| inline_value.clone().or_else(|| args.next())
+            .ok_or_else(|| UsageError(format!("option '{}' must be followed by a value", name)));
+
+        let canonical_name = match name.as_str() {
+            "--" => { only_inputs_follow = true; continue }
+
+            "-o" | "--output" => {
+                options.output = Some(value("--output")?);
+                "--output"
+            }
+
+            "--emit" => {
+                let kind = value("--emit")?;
+                options.emit = Emit::from_name(&kind).ok_or_else(|| UsageError(format!(
+                    "unknown output kind '{}' given to --emit (expected tokens, ast, ir, asm, obj or exe)", kind
+                )))?;
+                "--emit"
+            }
+
+            "--target" => {
+                let target = value("--target")?;
+                options.target = Target::from_name(&target).ok_or_else(|| UsageError(format!(
+                    "unsupported target '{}' (supported targets: {})", target,
+                    Target::NAMES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+                )))?;
+                "--target"
+            }
+
+            "-q" | "--quiet" => { options.quiet = true; "--quiet" }
+            "--deny-warnings" => { options.deny_warnings = true; "--deny-warnings" }
+            "--json-diagnostics" => { options.json_diagnostics = true; "--json-diagnostics" }
+
+            "--max-diagnostics" => {
+                let max = value("--max-diagnostics")?;
+                options.max_diagnostics = max.parse().map_err(|_| UsageError(format!(
+                    "option '--max-diagnostics' must be followed by a number, not '{}'", max
+                )))?;
+                "--max-diagnostics"
+            }
+
+            level if level.starts_with("-O") => {
+                options.optimisation_level = match &level[2..] {
+                    "" => DEFAULT_OPTIMISATION_LEVEL,
+                    digit @ ("0" | "1" | "2" | "3") => digit.parse().unwrap(),
+                    other => return usage_error(format!("unknown optimisation level '{}' (expected -O0, -O1, -O2 or -O3)", other))
+                };
+                "-O"
+            }
+
+            _ => return usage_error(format!("unknown option '{}'", arg))
+        };
+
+        given.push(canonical_name);
+    }
+
+    validate(subcommand, &options, &given)?;
+
+    Ok(Command::Process(subcommand, options))
+}
+
+fn parse_explain(mut args: impl Iterator<Item=String>) -> Result<Command> {
+    match (args.next(), args.next()) {
+        (Some(code), None) => Ok(Command::Explain(code)),
+        (None, _) => usage_error("the 'explain' command must be given the code of an error or warning (e.g. 'till explain T0103')".to_string()),
+        (Some(_), Some(extra)) => usage_error(format!("unexpected argument '{}' - the 'explain' command takes a single code", extra))
+    }
+}
+
+/// Ensure the given options make sense for the given subcommand.
+fn validate(subcommand: Subcommand, options: &Options, given: &[&str]) -> Result<()> {
+    if let Some(option) = given.iter().find(|option| !subcommand.accepts(option)) {
+        return usage_error(format!("option '{}' cannot be used with the '{}' command", option, subcommand.name()));
+    }
+
+    match options.inputs.len() {
+        0 => return usage_error(format!("no input given to the '{}' command (give a file path, or '-' for stdin)", subcommand.name())),
+        1 => {}
+        _ if subcommand == Subcommand::Run => return usage_error("the 'run' command takes a single input".to_string()),
+        _ if options.output.is_some() => return usage_error("option '--output' cannot be used when several inputs are given".to_string()),
+        _ => {}
+    }
+
+    if options.emit.is_binary() && options.output.as_deref() == Some("-") {
+        return usage_error("binary output (obj or exe) cannot be written to stdout".to_string());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ Command, Subcommand, Options, Input, Emit, UsageError };
+
+    fn parse(args: &str) -> super::Result<Command> {
+        super::parse(args.split_whitespace().map(str::to_string))
+    }
+
+    fn file(path: &str) -> Input { Input::File(path.to_string()) }
+
+    #[test]
+    fn commands() {
+        assert_eq!(parse("--help"), Ok(Command::Help));
+        assert_eq!(parse("compile x.til --version"), Ok(Command::Version));
+        assert_eq!(parse("help"), Ok(Command::Help));
+        assert_eq!(parse("explain T0103"), Ok(Command::Explain("T0103".to_string())));
+
+        assert_eq!(parse("compile x.til"), Ok(Command::Process(Subcommand::Compile, Options { inputs: vec![file("x.til")], ..Options::default() })));
+        assert_eq!(parse("check a.til - b.til"), Ok(Command::Process(Subcommand::Check, Options { inputs: vec![file("a.til"), Input::Stdin, file("b.til")], ..Options::default() })));
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(
+            parse("compile -q x.til -o out --emit=exe -O --target x86_64-linux --max-diagnostics=5 --deny-warnings --json-diagnostics"),
+            Ok(Command::Process(Subcommand::Compile, Options {
+                inputs: vec![file("x.til")], output: Some("out".to_string()), emit: Emit::Exe,
+                optimisation_level: 2, quiet: true, deny_warnings: true, max_diagnostics: 5, json_diagnostics: true,
+                ..Options::default()
+            }))
+        );
+
+        match parse("compile --emit ir -O1 -- -weird.til") {
+            Ok(Command::Process(_, options)) => {
+                assert_eq!(options.emit, Emit::Ir);
+                assert_eq!(options.optimisation_level, 1);
+                assert_eq!(options.inputs, vec![file("-weird.til")]);
+            }
+            other => panic!("{:?}", other)
+        }
+    }
+
+    #[test]
+    fn usage_errors() {
+        let error = |message: &str| Err(UsageError(message.to_string()));
+
+        assert_eq!(parse(""), error("no command given"));
+        assert_eq!(parse("compiel x.til"), error("unknown command 'compiel'"));
+        assert_eq!(parse("x.til"), error("unknown command 'x.til'"));
+        assert_eq!(parse("-o out"), error("expected a command before option '-o'"));
+        assert_eq!(parse("compile"), error("no input given to the 'compile' command (give a file path, or '-' for stdin)"));
+        assert_eq!(parse("compile x.til --fast"), error("unknown option '--fast'"));
+        assert_eq!(parse("compile x.til -o"), error("option '--output' must be followed by a value"));
+        assert_eq!(parse("compile x.til --emit=bin"), error("unknown output kind 'bin' given to --emit (expected tokens, ast, ir, asm, obj or exe)"));
+        assert_eq!(parse("compile x.til --target arm"), error("unsupported target 'arm' (supported targets: x86_64-linux)"));
+        assert_eq!(parse("compile x.til -O4"), error("unknown optimisation level '4' (expected -O0, -O1, -O2 or -O3)"));
+        assert_eq!(parse("compile x.til --max-diagnostics many"), error("option '--max-diagnostics' must be followed by a number, not 'many'"));
+        assert_eq!(parse("compile a.til b.til -o out"), error("option '--output' cannot be used when several inputs are given"));
+        assert_eq!(parse("compile x.til --emit=obj -o -"), error("binary output (obj or exe) cannot be written to stdout"));
+        assert_eq!(parse("compile - -"), error("stdin ('-') may only be given as an input once"));
+        assert_eq!(parse("check x.til --emit=asm"), error("option '--emit' cannot be used with the 'check' command"));
+        assert_eq!(parse("fmt x.til -O2"), error("option '-O' cannot be used with the 'fmt' command"));
+        assert_eq!(parse("run a.til b.til"), error("the 'run' command takes a single input"));
+        assert_eq!(parse("explain"), error("the 'explain' command must be given the code of an error or warning (e.g. 'till explain T0103')"));
+        assert_eq!(parse("explain T0103 T0104"), error("unexpected argument 'T0104' - the 'explain' command takes a single code"));
+    }
+}
@@ -3,7 +3,7 @@
 
 use crate::checking;
 use super::Generator;
-use std::collections::HashMap;
+use std::{ collections::HashMap, convert::TryFrom };
 
 pub fn input(instructions: Vec<checking::Instruction>) -> String {
     GenerateElf64::new().execute(instructions)
@@ -12,13 +12,27 @@ pub fn input(instructions: Vec<checking::Instruction>) -> String {
 struct GenerateElf64 {
     text_section: Vec<Instruction>,
     rodata_section: Vec<Instruction>,
-    num_label_counter: usize,
+    bss_section: Vec<Instruction>,
+    literal_label_counter: usize,
     function_variable_locations: HashMap<checking::Id, Oprand>,
+    global_variable_locations: HashMap<checking::Id, Oprand>,
+    /// Number of values occupied by each variable.
+    variable_sizes: HashMap<checking::Id, usize>,
+    /// Largest number of values occupied by a record returned by a function.
+    record_return_size: usize,
     local_variable_num: usize,
     parameter_variable_num: usize,
     display_num_used: bool,
+    display_int_used: bool,
     display_bool_used: bool,
-    display_char_used: bool
+    display_char_used: bool,
+    display_string_used: bool,
+    modulo_used: bool,
+    power_used: bool,
+    int_power_used: bool,
+    array_bounds_check_used: bool,
+    int_division_check_used: bool,
+    char_range_check_used: bool
 }
 
 impl GenerateElf64 {
@@ -28,22 +42,37 @@ impl GenerateElf64 {
                 Instruction::Comment(format!("Target: {}", Self::TARGET_NAME)),
                 Instruction::Section("text".to_string()),
                 Instruction::Extern("printf".to_string()),
+                Instruction::Extern("strcmp".to_string()),
+                Instruction::Extern("exit".to_string()),
                 Instruction::Global("main".to_string())
             ],
             rodata_section: vec![Instruction::Section("rodata".to_string())],
-            num_label_counter: 0,
+            bss_section: vec![Instruction::Section("bss".to_string())],
+            literal_label_counter: 0,
             function_variable_locations: HashMap::new(),
+            global_variable_locations: HashMap::new(),
+            variable_sizes: HashMap::new(),
+            record_return_size: 0,
             local_variable_num: 0,
             parameter_variable_num: 0,
             display_num_used: false,
+            display_int_used: false,
             display_bool_used: false,
-            display_char_used: false
+            display_char_used: false,
+            display_string_used: false,
+            modulo_used: false,
+            power_used: false,
+            int_power_used: false,
+            array_bounds_check_used: false,
+            int_division_check_used: false,
+            char_range_check_used: false
         }
     }
 }
 
 const BYTES_IN_VALUE: usize = 8;
 const CARRY_FLAG_BIT_OFFSET: usize = 8;
+const PARITY_FLAG_BIT_OFFSET: usize = 10;
 const ZERO_FLAG_BIT_OFFSET: usize = 14;
 
 const POP_AND_CMP_WITH_ZERO_INSTRUCTIONS: &[Instruction] = &[
@@ -60,8 +89,7 @@ impl Generator for GenerateElf64 {
             checking::Instruction::Push(val) => {
                 let oprand = match val {
                     checking::Value::Num(num_val) => {
-                        let label = literal_label(self.num_label_counter);
-                        self.num_label_counter += 1;
+                        let label = self.new_literal_label();
 
                         self.rodata_section.extend(vec![
                             Instruction::Label(label.clone()),
@@ -71,8 +99,44 @@ impl Generator for GenerateElf64 {
                         Oprand::Address(Box::new(Oprand::Label(label)))
                     }
 
-                    checking::Value::Variable(var_id) =>
-                        self.function_variable_locations.get(&var_id).unwrap().clone(),
+                    checking::Value::String(string_val) => {
+                        let label = self.new_literal_label();
+
+                        self.rodata_section.extend(vec![
+                            Instruction::Label(label.clone()),
+                            Instruction::DeclareString(format!(r"{}\0", escape_string(&string_val)))
+                        ]);
+
+                        // Strings are represented by the address of their first
+                        // character:
+                        Oprand::Label(label)
+                    }
+
+                    checking::Value::Variable(var_id) => {
+                        let location = self.variable_location(var_id);
+                        let size = self.variable_sizes[&var_id];
+
+                        // Push every value of a record variable except the one
+                        // at its lowest address (which is pushed last):
+                        for i in (1..size).rev() {
+                            self.text_section.push(Instruction::Push(displaced(&location, i * BYTES_IN_VALUE)));
+                        }
+
+                        location
+                    }
+
+                    checking::Value::Int(int_val) => {
+                        // Push can only take a 32-bit immediate value so larger
+                        // values are moved into a register first:
+                        if i32::try_from(int_val).is_ok() { Oprand::Value(Val::Int(int_val as isize)) }
+                        else {
+                            self.text_section.push(Instruction::Mov {
+                                dest: Oprand::Register(Reg::Rax),
+                                src: Oprand::Value(Val::Int(int_val as isize))
+                            });
+                            Oprand::Register(Reg::Rax)
+                        }
+                    }
 
                     checking::Value::Char(chr_val) =>
                         Oprand::Value(Val::Int(chr_val as isize)),
@@ -85,9 +149,51 @@ impl Generator for GenerateElf64 {
             }
 
             checking::Instruction::Store(id) => {
-                let location = self.function_variable_locations.get(&id).unwrap();
+                let location = self.variable_location(id);
+                let size = self.variable_sizes[&id];
+
+                for i in 0..size {
+                    self.text_section.push(Instruction::Pop(displaced(&location, i * BYTES_IN_VALUE)));
+                }
+            }
+
+            checking::Instruction::Global(id) => {
+                let label = global_label(id);
+
+                // Global variables are zero-initialised:
+                self.bss_section.extend(vec![
+                    Instruction::Label(label.clone()),
+                    Instruction::Reserve(1)
+                ]);
+
+                self.global_variable_locations.insert(id, Oprand::Address(Box::new(Oprand::Label(label))));
+                self.variable_sizes.insert(id, 1);
+            }
+
+            checking::Instruction::GlobalRecord { id, size } => {
+                let label = global_label(id);
 
-                self.text_section.push(Instruction::Pop(location.clone()));
+                // Each field is zero-initialised:
+                self.bss_section.extend(vec![
+                    Instruction::Label(label.clone()),
+                    Instruction::Reserve(size)
+                ]);
+
+                self.global_variable_locations.insert(id, Oprand::Address(Box::new(Oprand::Label(label))));
+                self.variable_sizes.insert(id, size);
+            }
+
+            checking::Instruction::GlobalArray { id, length } => {
+                let label = global_label(id);
+
+                // As with global variables, each element is zero-initialised:
+                self.bss_section.extend(vec![
+                    Instruction::Label(label.clone()),
+                    Instruction::Reserve(length)
+                ]);
+
+                self.global_variable_locations.insert(id, Oprand::Address(Box::new(Oprand::Label(label))));
+                self.variable_sizes.insert(id, length);
             }
 
             checking::Instruction::Parameter(id) => {
@@ -100,6 +206,20 @@ impl Generator for GenerateElf64 {
                 );
 
                 self.parameter_variable_num += 1;
+                self.variable_sizes.insert(id, 1);
+            }
+
+            checking::Instruction::ParameterRecord { id, size } => {
+                self.function_variable_locations.insert(
+                    id,
+                    Oprand::AddressDisplaced(
+                        Box::new(Oprand::Register(Reg::BasePointer)),
+                        ((self.parameter_variable_num + 2) * BYTES_IN_VALUE) as isize
+                    )
+                );
+
+                self.parameter_variable_num += size;
+                self.variable_sizes.insert(id, size);
             }
 
             checking::Instruction::Local(id) => {
@@ -112,6 +232,102 @@ impl Generator for GenerateElf64 {
                 );
 
                 self.local_variable_num += 1;
+                self.variable_sizes.insert(id, 1);
+            }
+
+            checking::Instruction::LocalRecord { id, size } => {
+                self.local_variable_num += size;
+
+                let location = Oprand::AddressDisplaced(
+                    Box::new(Oprand::Register(Reg::BasePointer)),
+                    -(BYTES_IN_VALUE as isize) * self.local_variable_num as isize
+                );
+
+                self.function_variable_locations.insert(id, location.clone());
+                self.variable_sizes.insert(id, size);
+
+                self.text_section.extend(vec![
+                    // Set every field of the record to zero:
+                    Instruction::Lea { dest: Oprand::Register(Reg::DestIndex), src: location },
+                    Instruction::Mov { dest: Oprand::Register(Reg::Rcx), src: Oprand::Value(Val::Int(size as isize)) },
+                    Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+                    Instruction::RepStosq
+                ]);
+            }
+
+            checking::Instruction::LocalArray { id, length } => {
+                // Elements occupy contiguous stack slots with the first element
+                // at the lowest address:
+                self.local_variable_num += length;
+
+                let location = Oprand::AddressDisplaced(
+                    Box::new(Oprand::Register(Reg::BasePointer)),
+                    -(BYTES_IN_VALUE as isize) * self.local_variable_num as isize
+                );
+
+                self.function_variable_locations.insert(id, location);
+                self.variable_sizes.insert(id, length);
+
+                self.add_clear_array_instructions(id, length);
+            }
+
+            checking::Instruction::ClearArray { id, length } => self.add_clear_array_instructions(id, length),
+
+            checking::Instruction::PushElement { id, length, line_number } => {
+                self.add_element_address_instructions(id, length, line_number);
+
+                self.text_section.push(Instruction::Push(element_oprand()));
+            }
+
+            checking::Instruction::StoreElement { id, length, line_number } => {
+                // Pop the value to be stored into rax:
+                self.text_section.push(Instruction::Pop(Oprand::Register(Reg::Rax)));
+
+                self.add_element_address_instructions(id, length, line_number);
+
+                self.text_section.push(Instruction::Mov { dest: element_oprand(), src: Oprand::Register(Reg::Rax) });
+            }
+
+            checking::Instruction::Field { record_size, offset, size } => {
+                // The fields of a record on the stack are ordered such that the
+                // first field is at the highest address. The values of the field
+                // being accessed are moved so as to occupy the highest addresses
+                // of the record, with all other values then being discarded:
+                let field_displacement = (record_size - offset - size) * BYTES_IN_VALUE;
+                let result_displacement = (record_size - size) * BYTES_IN_VALUE;
+
+                for i in (0..size).rev() {
+                    self.text_section.extend(vec![
+                        Instruction::Mov {
+                            dest: Oprand::Register(Reg::Rax),
+                            src: Oprand::AddressDisplaced(
+                                Box::new(Oprand::Register(Reg::StackPointer)),
+                                (field_displacement + i * BYTES_IN_VALUE) as isize
+                            )
+                        },
+                        Instruction::Mov {
+                            dest: Oprand::AddressDisplaced(
+                                Box::new(Oprand::Register(Reg::StackPointer)),
+                                (result_displacement + i * BYTES_IN_VALUE) as isize
+                            ),
+                            src: Oprand::Register(Reg::Rax)
+                        }
+                    ]);
+                }
+
+                self.text_section.push(Instruction::Add {
+                    dest: Oprand::Register(Reg::StackPointer),
+                    src: Oprand::Value(Val::Int(result_displacement as isize))
+                });
+            }
+
+            checking::Instruction::StoreField { id, offset, size } => {
+                let record_size = self.variable_sizes[&id];
+                let location = displaced(&self.variable_location(id), (record_size - offset - size) * BYTES_IN_VALUE);
+
+                for i in 0..size {
+                    self.text_section.push(Instruction::Pop(displaced(&location, i * BYTES_IN_VALUE)));
+                }
             }
 
             checking::Instruction::Label(id) => { self.text_section.push(Instruction::Label(label(id))); }
@@ -150,6 +366,21 @@ impl Generator for GenerateElf64 {
                 ]);
             }
 
+            checking::Instruction::CallExpectingRecord { label, size } => {
+                self.text_section.push(Instruction::Call(label));
+
+                // Copy the returned record onto the stack:
+                for i in (0..size).rev() {
+                    self.text_section.push(Instruction::Push(record_return_oprand(i)));
+                }
+            }
+
+            checking::Instruction::Discard => {
+                self.text_section.push(
+                    Instruction::Add { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(BYTES_IN_VALUE as isize)) }
+                );
+            }
+
             checking::Instruction::ReturnVoid => self.add_return_instructions(),
 
             checking::Instruction::ReturnValue => {
@@ -158,21 +389,45 @@ impl Generator for GenerateElf64 {
                 self.add_return_instructions();
             }
 
+            checking::Instruction::ReturnRecord { size } => {
+                // Records are too large to be returned in a register so are
+                // instead placed in memory reserved for that purpose:
+                self.record_return_size = self.record_return_size.max(size);
+
+                for i in 0..size {
+                    self.text_section.push(Instruction::Pop(record_return_oprand(i)));
+                }
+
+                self.add_return_instructions();
+            }
+
             checking::Instruction::Display { value_type, line_number } => {
                 let (format_label, float_args_count) = match value_type {
-                    checking::Type::Char => {
+                    checking::DisplayType::Char => {
                         self.display_char_used = true;
                         // Pop character from stack into rdx (third argument):
                         self.text_section.push(Instruction::Pop(Oprand::Register(Reg::Rdx)));
                         ("display_char", 0)
                     }
-                    checking::Type::Bool => {
+                    checking::DisplayType::Bool => {
                         self.display_bool_used = true;
                         // Pop bool from stack into rdx (third argument):
                         self.text_section.push(Instruction::Pop(Oprand::Register(Reg::Rdx)));
                         ("display_bool", 0)
                     }
-                    checking::Type::Num => {
+                    checking::DisplayType::String => {
+                        self.display_string_used = true;
+                        // Pop string address from stack into rdx (third argument):
+                        self.text_section.push(Instruction::Pop(Oprand::Register(Reg::Rdx)));
+                        ("display_string", 0)
+                    }
+                    checking::DisplayType::Int => {
+                        self.display_int_used = true;
+                        // Pop integer from stack into rdx (third argument):
+                        self.text_section.push(Instruction::Pop(Oprand::Register(Reg::Rdx)));
+                        ("display_int", 0)
+                    }
+                    checking::DisplayType::Num => {
                         self.display_num_used = true;
                         // Pop and store float in xmm0 register (first floating-point argument):
                         self.text_section.extend(vec![
@@ -218,15 +473,30 @@ impl Generator for GenerateElf64 {
                 self.text_section.push(Instruction::Je(label(id)));
             }
 
-            checking::Instruction::Equals => {
+            checking::Instruction::Equals => self.add_equality_instructions(vec![]),
+
+            checking::Instruction::NotEquals => {
+                self.add_equality_instructions(vec![
+                    // Zero flag being 0 indicates not equal:
+                    Instruction::BitwiseNot(Oprand::Register(Reg::Rax))
+                ]);
+            }
+
+            checking::Instruction::StringEquals => {
                 self.text_section.extend(vec![
-                    // Take first value in comparison off the stack:
-                    Instruction::Pop(Oprand::Register(Reg::Rax)),
-                    // Subtract that value by the second top value on stack:
-                    Instruction::Sub {
-                        dest: Oprand::Register(Reg::Rax),
-                        src: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer)))
-                    },
+                    // Take addresses of the strings being compared off the stack
+                    // (second and first arguments):
+                    Instruction::Pop(Oprand::Register(Reg::SrcIndex)),
+                    Instruction::Pop(Oprand::Register(Reg::DestIndex)),
+                    // Preserve stack pointer:
+                    Instruction::Mov { dest: Oprand::Register(Reg::Rbx), src: Oprand::Register(Reg::StackPointer) },
+                    // Align stack to 16-byte boundary:
+                    Instruction::BitwiseAnd { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(-16)) },
+                    // Call strcmp function (returns 0 when the strings are equal):
+                    Instruction::Call("strcmp".to_string()),
+                    // Restore stack pointer:
+                    Instruction::Mov { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Register(Reg::Rbx) },
+                    Instruction::Cmp { dest: Oprand::Register(Reg::Eax), src: Oprand::Value(Val::Int(0)) },
                     // Push flags register onto the stack:
                     Instruction::PushFlags,
                     // Pop the flags register into rax:
@@ -235,10 +505,7 @@ impl Generator for GenerateElf64 {
                     Instruction::Shr { dest: Oprand::Register(Reg::Rax), shift_by: 6 },
                     Instruction::BitwiseAnd { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(1)) },
                     // Place the value of the zero flag onto the stack:
-                    Instruction::Mov {
-                        dest: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))),
-                        src: Oprand::Register(Reg::Rax)
-                    }
+                    Instruction::Push(Oprand::Register(Reg::Rax))
                 ]);
             }
 
@@ -247,6 +514,64 @@ impl Generator for GenerateElf64 {
             checking::Instruction::Multiply => self.add_arithmetic_instructions(Instruction::FpuMultiply),
             checking::Instruction::Divide => self.add_arithmetic_instructions(Instruction::FpuDivide),
 
+            checking::Instruction::Modulo => {
+                self.modulo_used = true;
+                self.add_subroutine_call_instructions("modulo");
+            }
+
+            checking::Instruction::Power => {
+                self.power_used = true;
+                self.add_subroutine_call_instructions("power");
+            }
+
+            checking::Instruction::IntAdd => {
+                self.add_int_arithmetic_instructions(vec![
+                    Instruction::Add { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Rbx) }
+                ], Reg::Rax);
+            }
+
+            checking::Instruction::IntSubtract => {
+                self.add_int_arithmetic_instructions(vec![
+                    Instruction::Sub { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Rbx) }
+                ], Reg::Rax);
+            }
+
+            checking::Instruction::IntMultiply => {
+                self.add_int_arithmetic_instructions(vec![
+                    Instruction::Imul { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Rbx) }
+                ], Reg::Rax);
+            }
+
+            checking::Instruction::IntDivide { line_number } => {
+                let operations = self.int_division_instructions(line_number);
+                self.add_int_arithmetic_instructions(operations, Reg::Rax);
+            }
+
+            checking::Instruction::IntModulo { line_number } => {
+                let operations = self.int_division_instructions(line_number);
+                self.add_int_arithmetic_instructions(operations, Reg::Rdx); // Remainder of division is placed in rdx.
+            }
+
+            checking::Instruction::IntPower => {
+                self.int_power_used = true;
+
+                self.text_section.extend(vec![
+                    Instruction::Call("int_power".to_string()),
+                    // Move stack pointer:
+                    Instruction::Add { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(BYTES_IN_VALUE as isize)) },
+                    // Place result on the stack:
+                    Instruction::Mov {
+                        dest: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))),
+                        src: Oprand::Register(Reg::Rax)
+                    }
+                ]);
+            }
+
+            checking::Instruction::IntGreaterThan => self.add_int_comparison_instructions(Cond::Greater),
+            checking::Instruction::IntLessThan => self.add_int_comparison_instructions(Cond::Less),
+            checking::Instruction::IntGreaterThanOrEqual => self.add_int_comparison_instructions(Cond::GreaterOrEqual),
+            checking::Instruction::IntLessThanOrEqual => self.add_int_comparison_instructions(Cond::LessOrEqual),
+
             checking::Instruction::GreaterThan => {
                 self.add_comparison_instructions(vec![
                     // Extract the carry flag bit (indicates greater than when set in this instance):
@@ -268,6 +593,67 @@ impl Generator for GenerateElf64 {
                 ]);
             }
 
+            checking::Instruction::GreaterThanOrEqual => {
+                self.add_comparison_instructions(vec![
+                    // Create second copy of FPU status word:
+                    Instruction::Mov { dest: Oprand::Register(Reg::Bx), src: Oprand::Register(Reg::Ax) },
+                    // Have carry flag as least significant bit of ax:
+                    Instruction::Shr { dest: Oprand::Register(Reg::Ax), shift_by: CARRY_FLAG_BIT_OFFSET },
+                    // Have zero flag as least significant bit of bx:
+                    Instruction::Shr { dest: Oprand::Register(Reg::Bx), shift_by: ZERO_FLAG_BIT_OFFSET },
+                    // Either carry flag or zero flag being 1 indicates greater than or equal:
+                    Instruction::BitwiseOr { dest: Oprand::Register(Reg::Ax), src: Oprand::Register(Reg::Bx) }
+                ]);
+            }
+
+            checking::Instruction::LessThanOrEqual => {
+                self.add_comparison_instructions(vec![
+                    // Have carry flag as least significant bit of ax:
+                    Instruction::Shr { dest: Oprand::Register(Reg::Ax), shift_by: CARRY_FLAG_BIT_OFFSET },
+                    // Carry flag being 0 indicates less than or equal:
+                    Instruction::BitwiseNot(Oprand::Register(Reg::Ax))
+                ]);
+            }
+
+            checking::Instruction::IntToNum => {
+                self.text_section.extend(vec![
+                    Instruction::Pop(Oprand::Register(Reg::Rax)),
+                    Instruction::Cvtsi2sd { dest: Oprand::Register(Reg::Xmm0), src: Oprand::Register(Reg::Rax) },
+                    Instruction::Movq { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Xmm0) },
+                    Instruction::Push(Oprand::Register(Reg::Rax))
+                ]);
+            }
+
+            checking::Instruction::NumToInt => {
+                self.text_section.extend(vec![
+                    Instruction::Pop(Oprand::Register(Reg::Rax)),
+                    Instruction::Movq { dest: Oprand::Register(Reg::Xmm0), src: Oprand::Register(Reg::Rax) },
+                    // Convert with truncation towards zero:
+                    Instruction::Cvttsd2si { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Xmm0) },
+                    Instruction::Push(Oprand::Register(Reg::Rax))
+                ]);
+            }
+
+            checking::Instruction::IntToBool => self.add_nonzero_test_instructions(vec![]),
+
+            checking::Instruction::IntToChar { line_number } => {
+                self.char_range_check_used = true;
+
+                // The integer is left on the stack as the character:
+                self.text_section.extend(vec![
+                    Instruction::Mov { dest: Oprand::Register(Reg::Rdx), src: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))) },
+                    Instruction::Mov { dest: Oprand::Register(Reg::SrcIndex), src: Oprand::Value(Val::Int(line_number as isize)) },
+                    Instruction::Call("char_range_check".to_string())
+                ]);
+            }
+
+            checking::Instruction::NumToBool => {
+                self.add_nonzero_test_instructions(vec![
+                    // Discard the sign bit so that negative zero is also considered zero:
+                    Instruction::Add { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Rax) }
+                ]);
+            }
+
             checking::Instruction::Not => {
                 self.text_section.extend(vec![
                     // Perform bitwise not on value on top of stack:
@@ -297,6 +683,20 @@ impl Generator for GenerateElf64 {
             ]);
         }
 
+        if self.display_string_used {
+            self.rodata_section.extend(vec![
+                Instruction::Label("display_string".to_string()),
+                Instruction::DeclareString(r#"Line %u string value: "%s"\n\0"#.to_string())
+            ]);
+        }
+
+        if self.display_int_used {
+            self.rodata_section.extend(vec![
+                Instruction::Label("display_int".to_string()),
+                Instruction::DeclareString(r"Line %u integer value: %lld\n\0".to_string())
+            ]);
+        }
+
         if self.display_num_used {
             self.rodata_section.extend(vec![
                 Instruction::Label("display_num".to_string()),
@@ -304,13 +704,115 @@ impl Generator for GenerateElf64 {
             ]);
         }
 
-        self.text_section.extend(self.rodata_section.into_iter());
+        if self.modulo_used { self.text_section.extend(modulo_subroutine()); }
+
+        if self.power_used { self.text_section.extend(power_subroutine()); }
+
+        if self.int_power_used { self.text_section.extend(int_power_subroutine()); }
+
+        if self.array_bounds_check_used {
+            self.text_section.extend(array_bounds_check_subroutine());
+
+            self.rodata_section.extend(vec![
+                Instruction::Label("array_bounds_failure".to_string()),
+                Instruction::DeclareString(r"Line %u array index %lld is out of bounds for an array of length %lld\n\0".to_string())
+            ]);
+        }
+
+        if self.int_division_check_used {
+            self.text_section.extend(int_division_check_subroutine());
+
+            self.rodata_section.extend(vec![
+                Instruction::Label("division_by_zero_failure".to_string()),
+                Instruction::DeclareString(r"Line %u integer division by zero\n\0".to_string())
+            ]);
+        }
+
+        if self.char_range_check_used {
+            self.text_section.extend(char_range_check_subroutine());
+
+            self.rodata_section.extend(vec![
+                Instruction::Label("char_range_failure".to_string()),
+                Instruction::DeclareString(r"Line %u integer %lld is not a Unicode code point so cannot be converted to a character\n\0".to_string())
+            ]);
+        }
+
+        self.text_section.extend(self.rodata_section);
+
+        if self.record_return_size > 0 {
+            self.bss_section.extend(vec![
+                Instruction::Label("record_return".to_string()),
+                Instruction::Reserve(self.record_return_size)
+            ]);
+        }
+
+        if !self.global_variable_locations.is_empty() || self.record_return_size > 0 {
+            self.text_section.extend(self.bss_section);
+        }
 
         self.text_section.into_iter().map(|x| x.intel_syntax()).collect::<Vec<String>>().join("")
     }
 }
 
 impl GenerateElf64 {
+    fn new_literal_label(&mut self) -> String {
+        let label = literal_label(self.literal_label_counter);
+        self.literal_label_counter += 1;
+        label
+    }
+
+    /// Get the location of a variable, be it a local variable or parameter of
+    /// the current function, or a global variable.
+    fn variable_location(&self, id: checking::Id) -> Oprand {
+        self.function_variable_locations.get(&id)
+            .or_else(|| self.global_variable_locations.get(&id))
+            .unwrap().clone()
+    }
+
+    /// Pop an index off the stack into rdx, terminating the program should it
+    /// be out of bounds for the specified array, and then load the address of
+    /// the first element of that array into rbx.
+    fn add_element_address_instructions(&mut self, id: checking::Id, length: usize, line_number: u64) {
+        self.array_bounds_check_used = true;
+
+        let location = self.variable_location(id);
+
+        self.text_section.extend(vec![
+            Instruction::Pop(Oprand::Register(Reg::Rdx)),
+            Instruction::Mov { dest: Oprand::Register(Reg::SrcIndex), src: Oprand::Value(Val::Int(line_number as isize)) },
+            Instruction::Mov { dest: Oprand::Register(Reg::Rcx), src: Oprand::Value(Val::Int(length as isize)) },
+            Instruction::Call("array_bounds_check".to_string()),
+            Instruction::Lea { dest: Oprand::Register(Reg::Rbx), src: location }
+        ]);
+    }
+
+    /// Divide rax by rbx, leaving the quotient in rax and the remainder in rdx.
+    /// The divisor is checked first so that division by zero is reported rather
+    /// than raising a hardware exception.
+    fn int_division_instructions(&mut self, line_number: u64) -> Vec<Instruction> {
+        self.int_division_check_used = true;
+
+        vec![
+            Instruction::Mov { dest: Oprand::Register(Reg::SrcIndex), src: Oprand::Value(Val::Int(line_number as isize)) },
+            Instruction::Call("int_division_check".to_string()),
+            // Sign-extend rax into rdx before division:
+            Instruction::Cqo,
+            Instruction::Idiv(Oprand::Register(Reg::Rbx))
+        ]
+    }
+
+    /// Set every element of the array with the given ID and length to zero.
+    fn add_clear_array_instructions(&mut self, id: checking::Id, length: usize) {
+        let location = self.variable_location(id);
+
+        self.text_section.extend(vec![
+            Instruction::Lea { dest: Oprand::Register(Reg::DestIndex), src: location },
+            Instruction::Mov { dest: Oprand::Register(Reg::Rcx), src: Oprand::Value(Val::Int(length as isize)) },
+            Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+            Instruction::RepStosq
+        ]);
+    }
+
     fn two_stack_items_to_fpu_stack(&mut self, operation: Instruction) {
         self.text_section.extend(vec![
             Instruction::FpuReset,
@@ -333,6 +835,68 @@ impl GenerateElf64 {
         );
     }
     
+    /// Pop the two integers on top of the stack into rax (second-to-top) and rbx
+    /// (top), perform the given operations, and then push the value of the
+    /// specified result register.
+    fn add_int_arithmetic_instructions(&mut self, operations: Vec<Instruction>, result: Reg) {
+        self.text_section.extend(vec![
+            Instruction::Pop(Oprand::Register(Reg::Rbx)),
+            Instruction::Pop(Oprand::Register(Reg::Rax))
+        ]);
+
+        self.text_section.extend(operations);
+
+        self.text_section.push(Instruction::Push(Oprand::Register(result)));
+    }
+
+    /// Compare the two integers on top of the stack (second-to-top against top),
+    /// replacing them with 1 if the given condition holds or 0 otherwise.
+    fn add_int_comparison_instructions(&mut self, condition: Cond) {
+        self.text_section.extend(vec![
+            Instruction::Pop(Oprand::Register(Reg::Rax)),
+            Instruction::Cmp {
+                dest: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))),
+                src: Oprand::Register(Reg::Rax)
+            },
+            // Set al to 1 if condition holds, 0 otherwise:
+            Instruction::Set(condition, Oprand::Register(Reg::Al)),
+            Instruction::Movzx { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Al) },
+            // Store result:
+            Instruction::Mov {
+                dest: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))),
+                src: Oprand::Register(Reg::Rax)
+            }
+        ]);
+    }
+
+    /// Pop the value on top of the stack into rax, perform the given operations,
+    /// and then push 1 should rax be nonzero or 0 otherwise.
+    fn add_nonzero_test_instructions(&mut self, operations: Vec<Instruction>) {
+        self.text_section.push(Instruction::Pop(Oprand::Register(Reg::Rax)));
+
+        self.text_section.extend(operations);
+
+        self.text_section.extend(vec![
+            Instruction::Cmp { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+            Instruction::Set(Cond::NotEqual, Oprand::Register(Reg::Al)),
+            Instruction::Movzx { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Al) },
+            Instruction::Push(Oprand::Register(Reg::Rax))
+        ]);
+    }
+
+    /// Call a subroutine that takes the two values on top of the stack as its
+    /// operands and leaves its result at the top of the FPU stack, replacing
+    /// those two values with that result.
+    fn add_subroutine_call_instructions(&mut self, subroutine_label: &str) {
+        self.text_section.extend(vec![
+            Instruction::Call(subroutine_label.to_string()),
+            // Move stack pointer:
+            Instruction::Add { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(BYTES_IN_VALUE as isize)) },
+            // Move result from FPU stack to regular stack:
+            Instruction::FpuPop(Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))))
+        ]);
+    }
+
     fn add_comparison_instructions(&mut self, operations: Vec<Instruction>) {
         self.two_stack_items_to_fpu_stack(Instruction::FpuCompare);
        
@@ -353,6 +917,39 @@ impl GenerateElf64 {
         ]);
     }
 
+    /// Compare the two values on top of the stack, placing the value of the
+    /// zero flag in rax before performing the given operations and then
+    /// replacing those values with the least significant bit of rax.
+    fn add_equality_instructions(&mut self, operations: Vec<Instruction>) {
+        self.text_section.extend(vec![
+            // Take first value in comparison off the stack:
+            Instruction::Pop(Oprand::Register(Reg::Rax)),
+            // Subtract that value by the second top value on stack:
+            Instruction::Sub {
+                dest: Oprand::Register(Reg::Rax),
+                src: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer)))
+            },
+            // Push flags register onto the stack:
+            Instruction::PushFlags,
+            // Pop the flags register into rax:
+            Instruction::Pop(Oprand::Register(Reg::Rax)),
+            // Extract the value of the zero flag:
+            Instruction::Shr { dest: Oprand::Register(Reg::Rax), shift_by: 6 }
+        ]);
+
+        self.text_section.extend(operations);
+
+        self.text_section.extend(vec![
+            // Ensure all bits except the least significant one are clear:
+            Instruction::BitwiseAnd { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(1)) },
+            // Place the result onto the stack:
+            Instruction::Mov {
+                dest: Oprand::Address(Box::new(Oprand::Register(Reg::StackPointer))),
+                src: Oprand::Register(Reg::Rax)
+            }
+        ]);
+    }
+
     fn add_return_instructions(&mut self) {
         self.text_section.extend(vec![
             // Restore stack pointer:
@@ -371,6 +968,7 @@ impl GenerateElf64 {
 /// Trait for conversion to Intel or AT&T assembly syntax.
 trait AssemblyDisplay {
     fn intel_syntax(self) -> String;
+    #[allow(dead_code)]
     fn at_and_t_syntax(self) -> String where Self: Sized { unimplemented!() }
 }
 
@@ -383,8 +981,13 @@ enum Instruction {
     Label(String),
     Declare(Val),
     DeclareString(String),
+    Reserve(usize),
     Mov { dest: Oprand, src: Oprand },
     Movq { dest: Oprand, src: Oprand },
+    Lea { dest: Oprand, src: Oprand },
+    RepStosq,
+    Cvtsi2sd { dest: Oprand, src: Oprand },
+    Cvttsd2si { dest: Oprand, src: Oprand },
     Add { dest: Oprand, src: Oprand },
     Sub { dest: Oprand, src: Oprand },
     Push(Oprand),
@@ -398,6 +1001,22 @@ enum Instruction {
     FpuSubtract,
     FpuMultiply,
     FpuDivide,
+    FpuRemainder,
+    FpuExchange,
+    FpuDuplicate,
+    FpuDiscard,
+    FpuDiscardSecond,
+    FpuSubtractFromSecond,
+    FpuAbsolute,
+    FpuNegate,
+    FpuTest,
+    FpuCompareAndPopTwice,
+    FpuLoadOne,
+    FpuLoadZero,
+    FpuRound,
+    FpuLog2,
+    FpuExp2MinusOne,
+    FpuScale,
     Ret(usize),
     Call(String),
     Jmp(String),
@@ -405,10 +1024,18 @@ enum Instruction {
     BitwiseAnd { dest: Oprand, src: Oprand },
     BitwiseOr { dest: Oprand, src: Oprand },
     BitwiseNot(Oprand),
+    Neg(Oprand),
+    Imul { dest: Oprand, src: Oprand },
+    Idiv(Oprand),
+    Cqo,
+    Movzx { dest: Oprand, src: Oprand },
+    Set(Cond, Oprand),
     PushFlags,
     Cmp { dest: Oprand, src: Oprand },
     Je(String),
-    Jne(String)
+    Jne(String),
+    Jl(String),
+    Jae(String)
 }
 
 impl AssemblyDisplay for Instruction {
@@ -421,8 +1048,13 @@ impl AssemblyDisplay for Instruction {
             Instruction::Label(x) => format!("{}:\n", x),
             Instruction::Declare(x) => format!("dq {}\n", x.intel_syntax()),
             Instruction::DeclareString(x) => format!("db `{}`\n", x),
+            Instruction::Reserve(x) => format!("resq {}\n", x),
             Instruction::Mov { dest, src } => format!("mov {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::Movq { dest, src } => format!("movq {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
+            Instruction::Lea { dest, src } => format!("lea {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
+            Instruction::RepStosq => "rep stosq\n".to_string(),
+            Instruction::Cvtsi2sd { dest, src } => format!("cvtsi2sd {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
+            Instruction::Cvttsd2si { dest, src } => format!("cvttsd2si {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::Add { dest, src } => format!("add {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::Sub { dest, src } => format!("sub {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::Push(x) => format!("push qword {}\n", x.intel_syntax()),
@@ -436,6 +1068,22 @@ impl AssemblyDisplay for Instruction {
             Instruction::FpuSubtract => "fsub\n".to_string(),
             Instruction::FpuMultiply => "fmul\n".to_string(),
             Instruction::FpuDivide => "fdiv\n".to_string(),
+            Instruction::FpuRemainder => "fprem\n".to_string(),
+            Instruction::FpuExchange => "fxch\n".to_string(),
+            Instruction::FpuDuplicate => "fld st0\n".to_string(),
+            Instruction::FpuDiscard => "fstp st0\n".to_string(),
+            Instruction::FpuDiscardSecond => "fstp st1\n".to_string(),
+            Instruction::FpuSubtractFromSecond => "fsub st1, st0\n".to_string(),
+            Instruction::FpuAbsolute => "fabs\n".to_string(),
+            Instruction::FpuNegate => "fchs\n".to_string(),
+            Instruction::FpuTest => "ftst\n".to_string(),
+            Instruction::FpuCompareAndPopTwice => "fcompp\n".to_string(),
+            Instruction::FpuLoadOne => "fld1\n".to_string(),
+            Instruction::FpuLoadZero => "fldz\n".to_string(),
+            Instruction::FpuRound => "frndint\n".to_string(),
+            Instruction::FpuLog2 => "fyl2x\n".to_string(),
+            Instruction::FpuExp2MinusOne => "f2xm1\n".to_string(),
+            Instruction::FpuScale => "fscale\n".to_string(),
             Instruction::Ret(x) => format!("ret {}\n", x),
             Instruction::Call(x) => format!("call {}\n", x),
             Instruction::Jmp(x) => format!("jmp {}\n", x),
@@ -443,14 +1091,38 @@ impl AssemblyDisplay for Instruction {
             Instruction::BitwiseAnd { dest, src } => format!("and qword {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::BitwiseOr { dest, src } => format!("or qword {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::BitwiseNot(x) => format!("not qword {}\n", x.intel_syntax()),
+            Instruction::Neg(x) => format!("neg qword {}\n", x.intel_syntax()),
+            Instruction::Imul { dest, src } => format!("imul {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
+            Instruction::Idiv(x) => format!("idiv qword {}\n", x.intel_syntax()),
+            Instruction::Cqo => "cqo\n".to_string(),
+            Instruction::Movzx { dest, src } => format!("movzx {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
+            Instruction::Set(condition, x) => format!("set{} {}\n", condition.intel_syntax(), x.intel_syntax()),
             Instruction::PushFlags => "pushfq\n".to_string(),
             Instruction::Cmp { dest, src } => format!("cmp {}, {}\n", dest.intel_syntax(), src.intel_syntax()),
             Instruction::Je(x) => format!("je {}\n", x),
-            Instruction::Jne(x) => format!("jne {}\n", x)
+            Instruction::Jne(x) => format!("jne {}\n", x),
+            Instruction::Jl(x) => format!("jl {}\n", x),
+            Instruction::Jae(x) => format!("jae {}\n", x)
         }
     }
 }
 
+/// Condition codes for the set instruction.
+#[derive(Clone)]
+enum Cond { NotEqual, Greater, Less, GreaterOrEqual, LessOrEqual }
+
+impl AssemblyDisplay for Cond {
+    fn intel_syntax(self) -> String {
+        match self {
+            Cond::NotEqual => "ne",
+            Cond::Greater => "g",
+            Cond::Less => "l",
+            Cond::GreaterOrEqual => "ge",
+            Cond::LessOrEqual => "le"
+        }.to_string()
+    }
+}
+
 #[derive(Clone)]
 enum Oprand {
     Label(String),
@@ -458,6 +1130,9 @@ enum Oprand {
     Register(Reg),
     Address(Box<Oprand>),
     AddressDisplaced(Box<Oprand>, isize),
+    /// Address of the value at the given index (second) from the given base
+    /// address (first).
+    AddressIndexed(Box<Oprand>, Box<Oprand>)
 }
 
 impl AssemblyDisplay for Oprand {
@@ -467,7 +1142,8 @@ impl AssemblyDisplay for Oprand {
             Oprand::Value(x) => x.intel_syntax(),
             Oprand::Register(x) => x.intel_syntax(),
             Oprand::Address(x) => format!("[{}]", x.intel_syntax()),
-            Oprand::AddressDisplaced(x, displacement) => format!("[{}{:+}]", x.intel_syntax(), displacement)
+            Oprand::AddressDisplaced(x, displacement) => format!("[{}{:+}]", x.intel_syntax(), displacement),
+            Oprand::AddressIndexed(base, index) => format!("[{}+{}*{}]", base.intel_syntax(), index.intel_syntax(), BYTES_IN_VALUE)
         }
     }
 }
@@ -485,15 +1161,18 @@ impl AssemblyDisplay for Val {
 }
 
 #[derive(Clone)]
-enum Reg { Rax, Ax, Rbx, Bx, Rdx, StackPointer, BasePointer, DestIndex, SrcIndex, Xmm0 }
+enum Reg { Rax, Eax, Ax, Al, Rbx, Bx, Rcx, Rdx, StackPointer, BasePointer, DestIndex, SrcIndex, Xmm0 }
 
 impl AssemblyDisplay for Reg {
     fn intel_syntax(self) -> String {
         match self {
             Reg::Rax => "rax",
+            Reg::Eax => "eax",
             Reg::Ax => "ax",
+            Reg::Al => "al",
             Reg::Rbx => "rbx",
             Reg::Bx => "bx",
+            Reg::Rcx => "rcx",
             Reg::Rdx => "rdx",
             Reg::StackPointer => "rsp",
             Reg::BasePointer => "rbp",
@@ -506,4 +1185,332 @@ impl AssemblyDisplay for Reg {
 
 fn label(id: usize) -> String { format!("label{}", id) }
 
+/// Instructions that, when the FPU status word is in rax, will jump to the given
+/// label should the specified condition code bit be set.
+fn jump_if_fpu_flag_set(flag_bit_offset: usize, to: &str) -> Vec<Instruction> {
+    vec![
+        Instruction::BitwiseAnd { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(1 << flag_bit_offset)) },
+        Instruction::Jne(to.to_string())
+    ]
+}
+
+/// As with `jump_if_fpu_flag_set` except the jump is taken when the bit is clear.
+fn jump_if_fpu_flag_clear(flag_bit_offset: usize, to: &str) -> Vec<Instruction> {
+    vec![
+        Instruction::BitwiseAnd { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(1 << flag_bit_offset)) },
+        Instruction::Je(to.to_string())
+    ]
+}
+
+/// Subroutine calculating the remainder of the second-to-top value on the stack
+/// (prior to the call) divided by the top value. The result is left on top of
+/// the FPU stack.
+fn modulo_subroutine() -> Vec<Instruction> {
+    let mut instructions = vec![
+        Instruction::Label("modulo".to_string()),
+        Instruction::FpuReset,
+        // Load the divisor and then the dividend onto the FPU stack:
+        Instruction::FpuPush(Oprand::AddressDisplaced(Box::new(Oprand::Register(Reg::StackPointer)), BYTES_IN_VALUE as isize)),
+        Instruction::FpuPush(Oprand::AddressDisplaced(Box::new(Oprand::Register(Reg::StackPointer)), 2 * BYTES_IN_VALUE as isize)),
+        Instruction::Label("modulo_loop".to_string()),
+        // Calculate the partial remainder of st0 / st1:
+        Instruction::FpuRemainder,
+        Instruction::FpuStatusReg(Oprand::Register(Reg::Ax))
+    ];
+
+    // The partial remainder is only complete once C2 is clear:
+    instructions.extend(jump_if_fpu_flag_set(PARITY_FLAG_BIT_OFFSET, "modulo_loop"));
+
+    instructions.extend(vec![
+        // Leave only the remainder on the FPU stack:
+        Instruction::FpuDiscardSecond,
+        Instruction::Ret(0)
+    ]);
+
+    instructions
+}
+
+/// Subroutine raising the second-to-top integer on the stack (prior to the call)
+/// to the power of the top integer by repeated squaring. The result is left in
+/// rax. A negative exponent results in zero unless the base is 1 or -1 (as the
+/// result of the division `1 / (base ^ -exponent)` is truncated).
+fn int_power_subroutine() -> Vec<Instruction> {
+    vec![
+        Instruction::Label("int_power".to_string()),
+        Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(1)) },
+        // Base in rbx, exponent in rcx:
+        Instruction::Mov {
+            dest: Oprand::Register(Reg::Rbx),
+            src: Oprand::AddressDisplaced(Box::new(Oprand::Register(Reg::StackPointer)), 2 * BYTES_IN_VALUE as isize)
+        },
+        Instruction::Mov {
+            dest: Oprand::Register(Reg::Rcx),
+            src: Oprand::AddressDisplaced(Box::new(Oprand::Register(Reg::StackPointer)), BYTES_IN_VALUE as isize)
+        },
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rcx), src: Oprand::Value(Val::Int(0)) },
+        Instruction::Jl("int_power_negative_exponent".to_string()),
+
+        Instruction::Label("int_power_loop".to_string()),
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rcx), src: Oprand::Value(Val::Int(0)) },
+        Instruction::Je("int_power_end".to_string()),
+        // Multiply result by the base should the lowest bit of the exponent be set:
+        Instruction::Mov { dest: Oprand::Register(Reg::Rdx), src: Oprand::Register(Reg::Rcx) },
+        Instruction::BitwiseAnd { dest: Oprand::Register(Reg::Rdx), src: Oprand::Value(Val::Int(1)) },
+        Instruction::Je("int_power_square".to_string()),
+        Instruction::Imul { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Rbx) },
+        Instruction::Label("int_power_square".to_string()),
+        Instruction::Imul { dest: Oprand::Register(Reg::Rbx), src: Oprand::Register(Reg::Rbx) },
+        Instruction::Shr { dest: Oprand::Register(Reg::Rcx), shift_by: 1 },
+        Instruction::Jmp("int_power_loop".to_string()),
+
+        Instruction::Label("int_power_negative_exponent".to_string()),
+        Instruction::Neg(Oprand::Register(Reg::Rcx)),
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rbx), src: Oprand::Value(Val::Int(1)) },
+        Instruction::Je("int_power_loop".to_string()),
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rbx), src: Oprand::Value(Val::Int(-1)) },
+        Instruction::Je("int_power_loop".to_string()),
+        Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+
+        Instruction::Label("int_power_end".to_string()),
+        Instruction::Ret(0)
+    ]
+}
+
+/// Subroutine raising the second-to-top value on the stack (prior to the call)
+/// to the power of the top value. The result is left on top of the FPU stack.
+///
+/// The absolute value of the base `a` raised to the exponent `b` is found as
+/// `2 ^ (b * log2(|a|))`, with that power of two being split into integer and
+/// fractional parts as `f2xm1` only accepts values between -1 and 1. A negative
+/// base then has its result negated if the exponent is odd, or is NaN if the
+/// exponent is not an integer. A base of zero is handled separately.
+fn power_subroutine() -> Vec<Instruction> {
+    let base = || Oprand::AddressDisplaced(Box::new(Oprand::Register(Reg::StackPointer)), 2 * BYTES_IN_VALUE as isize);
+    let exponent = || Oprand::AddressDisplaced(Box::new(Oprand::Register(Reg::StackPointer)), BYTES_IN_VALUE as isize);
+    let status_to_rax = || Instruction::FpuStatusReg(Oprand::Register(Reg::Ax));
+
+    let mut instructions = vec![
+        Instruction::Label("power".to_string()),
+        Instruction::FpuReset,
+        Instruction::FpuPush(exponent()),
+        Instruction::FpuPush(base()),
+        Instruction::FpuAbsolute,
+        // Compare absolute value of base with zero:
+        Instruction::FpuTest,
+        status_to_rax()
+    ];
+    instructions.extend(jump_if_fpu_flag_set(ZERO_FLAG_BIT_OFFSET, "power_zero_base"));
+
+    instructions.extend(vec![
+        // st0 = b * log2(|a|):
+        Instruction::FpuLog2,
+        // Split into integer part (st1) and fractional part (st0):
+        Instruction::FpuDuplicate,
+        Instruction::FpuRound,
+        Instruction::FpuSubtractFromSecond,
+        Instruction::FpuExchange,
+        // Raise 2 to the power of the fractional part:
+        Instruction::FpuExp2MinusOne,
+        Instruction::FpuLoadOne,
+        Instruction::FpuAdd,
+        // Multiply by 2 raised to the power of the integer part:
+        Instruction::FpuScale,
+        Instruction::FpuDiscardSecond,
+        // Determine whether the base is negative:
+        Instruction::FpuPush(base()),
+        Instruction::FpuTest,
+        status_to_rax(),
+        Instruction::FpuDiscard
+    ]);
+    instructions.extend(jump_if_fpu_flag_clear(CARRY_FLAG_BIT_OFFSET, "power_end"));
+
+    instructions.extend(vec![
+        // Check that the exponent is an integer:
+        Instruction::FpuPush(exponent()),
+        Instruction::FpuDuplicate,
+        Instruction::FpuRound,
+        Instruction::FpuCompareAndPopTwice,
+        status_to_rax()
+    ]);
+    instructions.extend(jump_if_fpu_flag_clear(ZERO_FLAG_BIT_OFFSET, "power_invalid"));
+
+    instructions.extend(vec![
+        // Calculate the exponent modulo 2 to determine whether it is odd:
+        Instruction::FpuLoadOne,
+        Instruction::FpuLoadOne,
+        Instruction::FpuAdd,
+        Instruction::FpuPush(exponent()),
+        Instruction::FpuRemainder,
+        Instruction::FpuTest,
+        status_to_rax(),
+        Instruction::FpuDiscard,
+        Instruction::FpuDiscard
+    ]);
+    instructions.extend(jump_if_fpu_flag_set(ZERO_FLAG_BIT_OFFSET, "power_end"));
+
+    instructions.extend(vec![
+        Instruction::FpuNegate,
+        Instruction::Jmp("power_end".to_string()),
+
+        Instruction::Label("power_zero_base".to_string()),
+        // Leave only the exponent on the FPU stack and compare it with zero:
+        Instruction::FpuDiscard,
+        Instruction::FpuTest,
+        status_to_rax(),
+        Instruction::FpuDiscard,
+        Instruction::Mov { dest: Oprand::Register(Reg::Rbx), src: Oprand::Register(Reg::Rax) },
+        // Zero raised to the power of zero is one:
+        Instruction::FpuLoadOne
+    ]);
+    instructions.extend(jump_if_fpu_flag_set(ZERO_FLAG_BIT_OFFSET, "power_end"));
+
+    instructions.extend(vec![
+        Instruction::FpuLoadZero,
+        Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Register(Reg::Rbx) }
+    ]);
+    instructions.extend(jump_if_fpu_flag_set(CARRY_FLAG_BIT_OFFSET, "power_zero_base_negative_exponent"));
+
+    instructions.extend(vec![
+        // Zero raised to a positive power is zero:
+        Instruction::FpuDiscardSecond,
+        Instruction::Jmp("power_end".to_string()),
+
+        Instruction::Label("power_zero_base_negative_exponent".to_string()),
+        // Zero raised to a negative power is infinity (1 / 0):
+        Instruction::FpuDivide,
+        Instruction::Jmp("power_end".to_string()),
+
+        Instruction::Label("power_invalid".to_string()),
+        // Negative base with a non-integer exponent results in NaN (0 / 0):
+        Instruction::FpuDiscard,
+        Instruction::FpuLoadZero,
+        Instruction::FpuLoadZero,
+        Instruction::FpuDivide,
+
+        Instruction::Label("power_end".to_string()),
+        Instruction::Ret(0)
+    ]);
+
+    instructions
+}
+
+/// Subroutine ensuring that the index in rdx is within the bounds of an array
+/// with the length in rcx. Should it not be, a message including the line number
+/// in rsi is displayed and the program is terminated.
+fn array_bounds_check_subroutine() -> Vec<Instruction> {
+    vec![
+        Instruction::Label("array_bounds_check".to_string()),
+        // Unsigned comparison so that negative indices are also out of bounds:
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rdx), src: Oprand::Register(Reg::Rcx) },
+        Instruction::Jae("array_index_out_of_bounds".to_string()),
+        Instruction::Ret(0),
+
+        Instruction::Label("array_index_out_of_bounds".to_string()),
+        // Line number, index and length are already in place as the second,
+        // third and fourth arguments:
+        Instruction::Mov { dest: Oprand::Register(Reg::DestIndex), src: Oprand::Label("array_bounds_failure".to_string()) },
+        Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+        // Align the stack as is required when calling a C function:
+        Instruction::BitwiseAnd { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(-16)) },
+        Instruction::Call("printf".to_string()),
+        Instruction::Mov { dest: Oprand::Register(Reg::DestIndex), src: Oprand::Value(Val::Int(1)) },
+        Instruction::Call("exit".to_string())
+    ]
+}
+
+/// Check the divisor in rbx before dividing the dividend in rax by it, exiting
+/// with an error message for the line number in rsi should it be zero. A
+/// divisor of -1 is replaced by negating the dividend and dividing by 1 instead,
+/// as dividing the smallest integer by -1 would otherwise overflow and raise a
+/// hardware exception. The quotient then wraps around as with other integer
+/// arithmetic.
+fn int_division_check_subroutine() -> Vec<Instruction> {
+    vec![
+        Instruction::Label("int_division_check".to_string()),
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rbx), src: Oprand::Value(Val::Int(0)) },
+        Instruction::Je("int_division_by_zero".to_string()),
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rbx), src: Oprand::Value(Val::Int(-1)) },
+        Instruction::Jne("int_division_check_end".to_string()),
+        Instruction::Neg(Oprand::Register(Reg::Rax)),
+        Instruction::Mov { dest: Oprand::Register(Reg::Rbx), src: Oprand::Value(Val::Int(1)) },
+        Instruction::Label("int_division_check_end".to_string()),
+        Instruction::Ret(0),
+
+        Instruction::Label("int_division_by_zero".to_string()),
+        // Line number is already in place as the second argument:
+        Instruction::Mov { dest: Oprand::Register(Reg::DestIndex), src: Oprand::Label("division_by_zero_failure".to_string()) },
+        Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+        // Align the stack as is required when calling a C function:
+        Instruction::BitwiseAnd { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(-16)) },
+        Instruction::Call("printf".to_string()),
+        Instruction::Mov { dest: Oprand::Register(Reg::DestIndex), src: Oprand::Value(Val::Int(1)) },
+        Instruction::Call("exit".to_string())
+    ]
+}
+
+/// Check the integer in rdx is a Unicode code point (from 0 to 0x10FFFF),
+/// exiting with an error message for the line number in rsi should it not be.
+fn char_range_check_subroutine() -> Vec<Instruction> {
+    vec![
+        Instruction::Label("char_range_check".to_string()),
+        // Unsigned comparison so that negative integers are also out of range:
+        Instruction::Cmp { dest: Oprand::Register(Reg::Rdx), src: Oprand::Value(Val::Int(0x110000)) },
+        Instruction::Jae("char_out_of_range".to_string()),
+        Instruction::Ret(0),
+
+        Instruction::Label("char_out_of_range".to_string()),
+        // Line number and integer are already in place as the second and third
+        // arguments:
+        Instruction::Mov { dest: Oprand::Register(Reg::DestIndex), src: Oprand::Label("char_range_failure".to_string()) },
+        Instruction::Mov { dest: Oprand::Register(Reg::Rax), src: Oprand::Value(Val::Int(0)) },
+        // Align the stack as is required when calling a C function:
+        Instruction::BitwiseAnd { dest: Oprand::Register(Reg::StackPointer), src: Oprand::Value(Val::Int(-16)) },
+        Instruction::Call("printf".to_string()),
+        Instruction::Mov { dest: Oprand::Register(Reg::DestIndex), src: Oprand::Value(Val::Int(1)) },
+        Instruction::Call("exit".to_string())
+    ]
+}
+
+/// Get the address the given number of bytes beyond the specified address.
+fn displaced(address: &Oprand, bytes: usize) -> Oprand {
+    match address {
+        Oprand::Address(base) => Oprand::AddressDisplaced(base.clone(), bytes as isize),
+        Oprand::AddressDisplaced(base, displacement) => Oprand::AddressDisplaced(base.clone(), displacement + bytes as isize),
+        _ => unreachable!()
+    }
+}
+
+/// Location of the value at the given index of a record being returned from a
+/// function.
+fn record_return_oprand(index: usize) -> Oprand {
+    Oprand::AddressDisplaced(Box::new(Oprand::Label("record_return".to_string())), (index * BYTES_IN_VALUE) as isize)
+}
+
+/// Operand for the array element at the index in rdx from the address in rbx
+/// (as prepared by `add_element_address_instructions`).
+fn element_oprand() -> Oprand {
+    Oprand::AddressIndexed(Box::new(Oprand::Register(Reg::Rbx)), Box::new(Oprand::Register(Reg::Rdx)))
+}
+
 fn literal_label(counter: usize) -> String { format!("literal{}", counter) }
+
+fn global_label(id: usize) -> String { format!("global{}", id) }
+
+/// Escape a string so that it may be placed between the backquotes of a NASM
+/// string constant.
+fn escape_string(s: &str) -> String {
+    let mut escaped = String::new();
+
+    for byte in s.bytes() {
+        match byte {
+            b'\\' => escaped.push_str(r"\\"),
+            b'`' => escaped.push_str(r"\`"),
+            b'\n' => escaped.push_str(r"\n"),
+            b'\t' => escaped.push_str(r"\t"),
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!(r"\x{:02x}", byte))
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,352 @@
+//! Contains code for the display of errors and warnings from every stage of
+//! compilation alongside the lines of source code they concern.
+
+use crate::{ stream, lexing, parsing, checking };
+use std::fmt;
+
+/// The number of spaces a tab character is displayed as within source lines.
+const TAB_WIDTH: usize = 4;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Severity with which a diagnostic is reported. A warning is reported as an
+/// error should warnings be denied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity { Warning, Error }
+
+impl Severity {
+    fn colour(&self) -> &'static str {
+        match self {
+            Severity::Warning => YELLOW,
+            Severity::Error => RED
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "WARNING"),
+            Severity::Error => write!(f, "ERROR")
+        }
+    }
+}
+
+/// Marks a span of source code, optionally with a message explaining its
+/// relevance. Only the first line of a span covering multiple lines is marked.
+#[derive(Debug, PartialEq)]
+pub struct Label {
+    pub span: stream::Span,
+    pub message: Option<String>
+}
+
+impl Label {
+    fn at(span: &stream::Span) -> Self {
+        Label { span: span.clone(), message: None }
+    }
+
+    fn with_message(span: &stream::Span, message: &str) -> Self {
+        Label { span: span.clone(), message: Some(message.to_string()) }
+    }
+
+    /// Create a label marking the given lexeme. Should that lexeme be a newline
+    /// followed by indentation then it is the indentation that is marked.
+    fn lexeme(lexeme: &lexing::Lexeme) -> Self {
+        Label::at(&lexeme.indentation_span())
+    }
+
+    /// The number of characters marked by this label on the first line of its
+    /// span (the remainder of that line for a span covering multiple lines).
+    fn width(&self, line: &str) -> usize {
+        let stream::Span { start, end } = &self.span;
+
+        if end.line_number == start.line_number {
+            (end.line_position + 1).saturating_sub(start.line_position) as usize
+        }
+        else {
+            (line.chars().count() + 1).saturating_sub(start.line_position as usize)
+        }
+    }
+}
+
+/// An error or warning from any stage of compilation, along with the source
+/// code it primarily concerns (if any particular source code) and any other
+/// source code relevant to it.
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub compilation_stage: &'static str,
+    pub code: &'static str,
+    pub message: String,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>
+}
+
+impl Diagnostic {
+    fn error<E: fmt::Display>(compilation_stage: &'static str, code: &'static str, e: &E, primary: Option<Label>) -> Self {
+        Diagnostic {
+            severity: Severity::Error, compilation_stage, code,
+            message: e.to_string(), primary, secondary: Vec::new()
+        }
+    }
+
+    pub fn from_lexing_failure(e: &lexing::Failure) -> Self {
+        let lexeme = match e {
+            lexing::Failure::UnexpectedChar(_, lexeme) | lexing::Failure::UnexpectedEof(lexeme) |
+            lexing::Failure::InconsistentIndentation(lexeme) => lexeme
+        };
+        Diagnostic::error("lexical", e.code(), e, Some(Label::lexeme(lexeme)))
+    }
+
+    pub fn from_parsing_failure(e: &parsing::Failure) -> Self {
+        let primary = match e {
+            parsing::Failure::UnexpectedToken(tok, _) | parsing::Failure::IntegerOutOfRange(tok) => Some(Label::lexeme(&tok.lexeme)),
+            _ => e.span().map(Label::at)
+        };
+        Diagnostic::error("syntax", e.code(), e, primary)
+    }
+
+    pub fn from_checking_failure(e: &checking::Failure) -> Self {
+        let mut diagnostic = Diagnostic::error("semantic", e.code(), e, e.span().map(Label::at));
+
+        match e {
+            checking::Failure::VariableRedeclaredToDifferentType(failure) =>
+                diagnostic.secondary.push(Label::with_message(&failure.previous_span, "previously declared here")),
+            checking::Failure::RedefinedExistingFunction(failure) =>
+                diagnostic.secondary.push(Label::with_message(&failure.previous_span, "previously defined here")),
+            checking::Failure::FunctionMayNotReturn(failure) =>
+                diagnostic.secondary.push(Label::with_message(&failure.fall_through_span, "may reach the end of the function after this statement")),
+            _ => {}
+        }
+
+        diagnostic
+    }
+
+    pub fn from_warning(warning: &checking::Warning, severity: Severity) -> Self {
+        Diagnostic {
+            severity, compilation_stage: "semantic", code: warning.code(),
+            message: warning.to_string(), primary: Some(Label::at(warning.span())), secondary: Vec::new()
+        }
+    }
+
+    /// Get the span of the source code this diagnostic primarily concerns, if
+    /// there is such a span.
+    pub fn span(&self) -> Option<&stream::Span> {
+        self.primary.as_ref().map(|label| &label.span)
+    }
+
+    /// Render this diagnostic as a JSON object on a single line, holding its
+    /// severity, code, message and the location of the source code it concerns
+    /// along with any related locations. Lines and columns begin at 1, with the
+    /// end of a location being the line and column of its final character.
+    /// Locations are null for a diagnostic not concerning particular code.
+    pub fn to_json(&self, file_name: &str) -> String {
+        let location = |span: Option<&stream::Span>| match span {
+            Some(stream::Span { start, end }) => format!(
+                "\"file\":{},\"start_line\":{},\"start_column\":{},\"end_line\":{},\"end_column\":{}",
+                json_string(file_name), start.line_number, start.line_position, end.line_number, end.line_position
+            ),
+            None => format!(
+                "\"file\":{},\"start_line\":null,\"start_column\":null,\"end_line\":null,\"end_column\":null",
+                json_string(file_name)
+            )
+        };
+
+        let related: Vec<String> = self.secondary.iter().map(|label| format!(
+            "{{\"message\":{},{}}}",
+            json_string(label.message.as_deref().unwrap_or("")), location(Some(&label.span))
+        )).collect();
+
+        format!(
+            "{{\"severity\":{},\"stage\":{},\"code\":{},\"message\":{},{},\"related\":[{}]}}",
+            json_string(&self.severity.to_string().to_lowercase()), json_string(self.compilation_stage),
+            json_string(self.code), json_string(&self.message), location(self.span()), related.join(",")
+        )
+    }
+
+    /// Render this diagnostic, displaying the name of the file and location
+    /// within it along with the lines of source code marked by its labels.
+    /// ANSI escape codes are used to colour the output should `colour` be true.
+    pub fn render(&self, file_name: &str, source: &str, colour: bool) -> String {
+        let paint = |text: &str, style: &str| {
+            if colour { format!("{}{}{}", style, text, RESET) } else { text.to_string() }
+        };
+
+        let header = format!("{} {} [{}]", self.compilation_stage.to_ascii_uppercase(), self.severity, self.code);
+
+        let mut output = format!("{}{}\n", paint(&header, self.severity.colour()), paint(&format!(": {}", self.message), BOLD));
+
+        let labels: Vec<(&Label, char, &str)> = self.primary.iter().map(|label| (label, '^', self.severity.colour()))
+            .chain(self.secondary.iter().map(|label| (label, '-', BLUE)))
+            .collect();
+
+        let gutter_width = labels.iter().map(|(label, ..)| label.span.start.line_number.to_string().len()).max().unwrap_or(0);
+        let gutter = |line_number: &str| paint(&format!("{:>width$} |", line_number, width = gutter_width), BLUE);
+
+        match &self.primary {
+            Some(label) => output += &format!("{}{} {}:{}:{}\n", " ".repeat(gutter_width), paint("-->", BLUE),
+                                              file_name, label.span.start.line_number, label.span.start.line_position),
+            None => output += &format!(" {} {}\n", paint("-->", BLUE), file_name)
+        }
+
+        for (label, mark, style) in labels {
+            let line_number = label.span.start.line_number;
+            let line = source.lines().nth((line_number as usize).saturating_sub(1)).unwrap_or("");
+            let (offset, width) = display_columns(line, label.span.start.line_position as usize, label.width(line));
+
+            let mut marks = mark.to_string().repeat(width);
+            if let Some(message) = &label.message { marks = format!("{} {}", marks, message); }
+
+            output += &format!("{}\n", gutter(""));
+            output += &format!("{} {}\n", gutter(&line_number.to_string()), line.replace('\t', &" ".repeat(TAB_WIDTH)));
+            output += &format!("{} {}{}\n", gutter(""), " ".repeat(offset), paint(&marks, style));
+        }
+
+        output
+    }
+}
+
+/// Quote the given text as a JSON string, escaping characters as necessary.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for chr in text.chars() {
+        match chr {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            chr if chr.is_control() => quoted.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => quoted.push(chr)
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Determine the offset from the start of a line and the width (both in
+/// displayed columns, with tabs being expanded) of the given number of
+/// characters starting at the given column (where the first character of the
+/// line is at column 1).
+fn display_columns(line: &str, column: usize, width: usize) -> (usize, usize) {
+    let display_width = |chr: char| if chr == '\t' { TAB_WIDTH } else { 1 };
+
+    let offset = line.chars().take(column.saturating_sub(1)).map(display_width).sum::<usize>() +
+                 column.saturating_sub(1).saturating_sub(line.chars().count());
+    let marked_width = line.chars().skip(column.saturating_sub(1)).take(width).map(display_width).sum::<usize>();
+
+    (offset, marked_width.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ stream::{ Position, Span }, checking, lexing::{ self, lexer }, parsing };
+
+    fn span(line_number: u64, start: u64, end: u64) -> Span {
+        Span {
+            start: Position { position: 0, line_number, line_position: start },
+            end: Position { position: 0, line_number, line_position: end }
+        }
+    }
+
+    #[test]
+    fn rendering() {
+        let source = "main()\n\tNum x = 1\n\tBool x = true\n";
+
+        let failure = checking::Failure::VariableRedeclaredToDifferentType(Box::new(checking::VariableRedeclaration {
+            span: span(3, 2, 14), identifier: "x".to_string(),
+            expected: checking::Type::Num, encountered: checking::Type::Bool,
+            previous_span: span(2, 2, 10)
+        }));
+
+        assert_eq!(
+            super::Diagnostic::from_checking_failure(&failure).render("test.til", source, false),
+            format!(concat!(
+                "SEMANTIC ERROR [T0003]: {}\n",
+                " --> test.til:3:2\n",
+                "  |\n",
+                "3 |     Bool x = true\n",
+                "  |     ^^^^^^^^^^^^^\n",
+                "  |\n",
+                "2 |     Num x = 1\n",
+                "  |     --------- previously declared here\n"
+            ), failure)
+        );
+    }
+
+    #[test]
+    fn json() {
+        let failure = checking::Failure::VariableRedeclaredToDifferentType(Box::new(checking::VariableRedeclaration {
+            span: span(3, 2, 14), identifier: "x".to_string(),
+            expected: checking::Type::Num, encountered: checking::Type::Bool,
+            previous_span: span(2, 2, 10)
+        }));
+
+        assert_eq!(
+            super::Diagnostic::from_checking_failure(&failure).to_json("dir/test.til"),
+            format!(concat!(
+                "{{\"severity\":\"error\",\"stage\":\"semantic\",\"code\":\"T0003\",\"message\":\"{}\",",
+                "\"file\":\"dir/test.til\",\"start_line\":3,\"start_column\":2,\"end_line\":3,\"end_column\":14,",
+                "\"related\":[{{\"message\":\"previously declared here\",",
+                "\"file\":\"dir/test.til\",\"start_line\":2,\"start_column\":2,\"end_line\":2,\"end_column\":10}}]}}"
+            ), failure)
+        );
+
+        assert_eq!(
+            super::Diagnostic::from_checking_failure(&checking::Failure::MainUndefined).to_json("test.til"),
+            format!(concat!(
+                "{{\"severity\":\"error\",\"stage\":\"semantic\",\"code\":\"T0108\",\"message\":\"{}\",",
+                "\"file\":\"test.til\",\"start_line\":null,\"start_column\":null,\"end_line\":null,\"end_column\":null,",
+                "\"related\":[]}}"
+            ), checking::Failure::MainUndefined)
+        );
+
+        assert_eq!(super::json_string("a \"b\"\n\\c\u{1}"), "\"a \\\"b\\\"\\n\\\\c\\u0001\"");
+    }
+
+    #[test]
+    fn lexeme_labels() {
+        let token = lexer::Token {
+            tok_type: lexer::TokenType::Identifier("value".to_string()),
+            lexeme: lexing::Lexeme { text: "value".to_string(), span: span(1, 9, 13) }
+        };
+        let failure = parsing::Failure::UnexpectedToken(token, "expression");
+
+        assert_eq!(
+            super::Diagnostic::from_parsing_failure(&failure).render("test.til", "display value value", false),
+            format!("SYNTAX ERROR [P0001]: {}\n --> test.til:1:9\n  |\n1 | display value value\n  |         ^^^^^\n", failure)
+        );
+
+        // The indentation following a newline is marked rather than the newline:
+        let indentation = lexing::Lexeme {
+            text: "\n\t\t".to_string(),
+            span: Span { start: Position { position: 7, line_number: 1, line_position: 7 }, end: Position { position: 9, line_number: 2, line_position: 2 } }
+        };
+        assert_eq!(super::Label::lexeme(&indentation).span, Span {
+            start: Position { position: 8, line_number: 2, line_position: 1 },
+            end: Position { position: 9, line_number: 2, line_position: 2 }
+        });
+
+        // Only the first line of a span covering multiple lines is marked:
+        let failure = checking::Failure::InvalidTopLevelStatement(Span {
+            start: Position { position: 0, line_number: 1, line_position: 1 },
+            end: Position { position: 0, line_number: 2, line_position: 9 }
+        });
+        assert_eq!(
+            super::Diagnostic::from_checking_failure(&failure).render("test.til", "while true\n\tdisplay 1", false),
+            format!("SEMANTIC ERROR [T0501]: {}\n --> test.til:1:1\n  |\n1 | while true\n  | ^^^^^^^^^^\n", failure)
+        );
+
+        // Failures without a position only display the file name:
+        assert_eq!(
+            super::Diagnostic::from_checking_failure(&checking::Failure::MainUndefined).render("test.til", "", false),
+            format!("SEMANTIC ERROR [T0108]: {}\n --> test.til\n", checking::Failure::MainUndefined)
+        );
+    }
+}
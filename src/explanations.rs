@@ -0,0 +1,935 @@
+//! Contains long-form explanations of every error and warning code, each
+//! alongside an example of till code resulting in that error or warning and a
+//! corrected version of that example.
+
+use std::fmt;
+
+/// Width at which the description of an explanation is wrapped.
+const LINE_WIDTH: usize = 80;
+
+/// Explanation of the error or warning with a given code.
+#[derive(Debug)]
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub erroneous: &'static str,
+    pub corrected: &'static str
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = |example: &str| example.lines()
+            .map(|line| if line.is_empty() { String::new() } else { format!("    {}", line) })
+            .collect::<Vec<_>>().join("\n");
+
+        writeln!(f, "{}: {}\n", self.code, self.title)?;
+        writeln!(f, "{}\n", wrap(self.description, LINE_WIDTH))?;
+        writeln!(f, "Erroneous example:\n\n{}\n", indent(self.erroneous))?;
+        write!(f, "Corrected example:\n\n{}", indent(self.corrected))
+    }
+}
+
+/// Get the explanation of the error or warning with the given code (ignoring
+/// case), should there be such an error or warning.
+pub fn lookup(code: &str) -> Option<&'static Explanation> {
+    EXPLANATIONS.iter().find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+}
+
+/// Break the given text into lines no longer than the given width (unless a
+/// single word is longer than that width).
+fn wrap(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() { line.push(' '); }
+        line.push_str(word);
+    }
+    lines.push(line);
+
+    lines.join("\n")
+}
+
+const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "L0001",
+        title: "Unexpected character",
+        description: "A character was encountered that cannot begin a token (such as `$` or `@`) or that cannot \
+                      continue the token being read. The character (or the remainder of a character or string \
+                      literal containing it) is skipped so that the rest of the file may still be checked for \
+                      errors.",
+        erroneous: "\
+main()
+    Int price = 5$
+    display price",
+        corrected: "\
+main()
+    Int price = 5
+    display price"
+    },
+
+    Explanation {
+        code: "L0002",
+        title: "Unexpected end of file",
+        description: "The end of the file was reached part-way through a token. This is usually the result of a \
+                      block comment that is missing its closing `]#`.",
+        erroneous: "\
+main()
+    display 1 #[ Display the number one",
+        corrected: "\
+main()
+    display 1 #[ Display the number one ]#"
+    },
+
+    Explanation {
+        code: "L0003",
+        title: "Inconsistent indentation",
+        description: "Indentation must consist of either only tabs or only spaces throughout a file. When spaces \
+                      are used, the number of spaces making up one level of indentation is taken from the first \
+                      indented line of the file, and every line must be indented by a multiple of that number.",
+        erroneous: "\
+main()
+    Int x = 1
+    if x > 0
+      display x",
+        corrected: "\
+main()
+    Int x = 1
+    if x > 0
+        display x"
+    },
+
+    Explanation {
+        code: "P0001",
+        title: "Unexpected token",
+        description: "The code does not follow the syntax of the till language, with a token being encountered \
+                      where it is not expected. The message states what was expected instead. The statement \
+                      containing the token is skipped so that the remainder of the file may still be checked for \
+                      errors.",
+        erroneous: "\
+main()
+    display (1 + 2
+    display 3",
+        corrected: "\
+main()
+    display (1 + 2)
+    display 3"
+    },
+
+    Explanation {
+        code: "P0002",
+        title: "Unexpected end of input",
+        description: "The end of the file was reached part-way through a statement, such as after the operator \
+                      of a binary expression or before the closing bracket of a function call.",
+        erroneous: "\
+main()
+    display 1 +",
+        corrected: "\
+main()
+    display 1 + 2"
+    },
+
+    Explanation {
+        code: "P0003",
+        title: "Unexpected indentation",
+        description: "A line is indented more than expected. Only the first line of a block (such as the body of \
+                      a function or of an 'if' statement) may be indented further than the line before it, and \
+                      every statement within a block must be at the same level of indentation.",
+        erroneous: "\
+main()
+    Int x = 1
+        display x",
+        corrected: "\
+main()
+    Int x = 1
+    display x"
+    },
+
+    Explanation {
+        code: "P0004",
+        title: "Variable declared using 'let' without an initial value",
+        description: "The type of a variable declared using `let` is that of its initial value, so such a \
+                      variable must always be given an initial value. Either give the variable an initial value \
+                      or declare it with an explicit type.",
+        erroneous: "\
+main()
+    let total
+    total = 5
+    display total",
+        corrected: "\
+main()
+    let total = 5
+    display total"
+    },
+
+    Explanation {
+        code: "P0005",
+        title: "Integer literal out of range",
+        description: "An integer literal is too large to be represented as an Int, which ranges from \
+                      -9223372036854775808 to 9223372036854775807. The literal 9223372036854775808 may only be \
+                      written as the entire operand of the unary minus operator `~` so as to give the minimum Int \
+                      (`~9223372036854775808`). Use a Num literal (with a decimal point) for larger values.",
+        erroneous: "\
+main()
+    Int big = 10000000000000000000
+    display big",
+        corrected: "\
+main()
+    Num big = 10000000000000000000.0
+    display big"
+    },
+
+    Explanation {
+        code: "T0001",
+        title: "Variable not in scope",
+        description: "A variable was used that has not been declared, or that was declared in a scope that is not \
+                      accessible from where it is used. A variable declared within a block (such as that of an \
+                      'if' statement) may only be used within that block.",
+        erroneous: "\
+main()
+    if true
+        Int x = 1
+    display x",
+        corrected: "\
+main()
+    Int x = 0
+    if true
+        x = 1
+    display x"
+    },
+
+    Explanation {
+        code: "T0002",
+        title: "Use of a variable that may not have been assigned a value",
+        description: "A variable must be assigned a value along every possible path through the program before it \
+                      is used. A variable assigned a value in the block of an 'if' statement but not its 'else' \
+                      block, or only within the block of a 'while' statement, cannot be used afterwards.",
+        erroneous: "\
+main()
+    Int x
+    if 1 > 0
+        x = 1
+    display x",
+        corrected: "\
+main()
+    Int x
+    if 1 > 0
+        x = 1
+    else
+        x = 0
+    display x"
+    },
+
+    Explanation {
+        code: "T0003",
+        title: "Variable redeclared to a different type",
+        description: "A variable may be redeclared within the same scope only with the same type as it was \
+                      originally declared with. Use a different identifier for the new variable instead.",
+        erroneous: "\
+main()
+    Num x = 1.5
+    Bool x = true
+    display x",
+        corrected: "\
+main()
+    Num x = 1.5
+    Bool flag = true
+    display x
+    display flag"
+    },
+
+    Explanation {
+        code: "T0101",
+        title: "Call to an undefined function",
+        description: "A function was called that has not been defined with the given number and types of \
+                      arguments. As functions may be overloaded, a function is identified by both its name and \
+                      the types of its parameters, so calling a function with arguments of the wrong type also \
+                      results in this error.",
+        erroneous: "\
+square(Int x) -> Int
+    return x * x
+
+main()
+    display square(2.5)",
+        corrected: "\
+square(Int x) -> Int
+    return x * x
+
+main()
+    display square(3)"
+    },
+
+    Explanation {
+        code: "T0102",
+        title: "Function defined more than once",
+        description: "A function with the same name and parameter types as another function accessible from the \
+                      same scope was defined. Functions may share a name only should they differ in the number or \
+                      types of their parameters.",
+        erroneous: "\
+greet()
+    display \"Hello\"
+
+greet()
+    display \"Hi\"
+
+main()
+    greet()",
+        corrected: "\
+greet()
+    display \"Hello\"
+
+greet(String name)
+    display name
+
+main()
+    greet()
+    greet(\"Ada\")"
+    },
+
+    Explanation {
+        code: "T0103",
+        title: "Function without a return value used in an expression",
+        description: "A function defined without a return type does not produce a value, so cannot be called as \
+                      part of an expression. Such a function may only be called as a statement in its own right.",
+        erroneous: "\
+log(String message)
+    display message
+
+main()
+    String result = log(\"Starting\")
+    display result",
+        corrected: "\
+log(String message)
+    display message
+
+main()
+    log(\"Starting\")"
+    },
+
+    Explanation {
+        code: "T0104",
+        title: "Function without a return type returns a value",
+        description: "A function that returns a value must state the type of that value after `->` in its \
+                      definition.",
+        erroneous: "\
+double(Int x)
+    return x * 2
+
+main()
+    display double(4)",
+        corrected: "\
+double(Int x) -> Int
+    return x * 2
+
+main()
+    display double(4)"
+    },
+
+    Explanation {
+        code: "T0105",
+        title: "Function returns a value of the wrong type",
+        description: "Every value returned by a function must be of the return type stated in its definition, and \
+                      a function with a return type must return a value. Note that `Int` and `Num` are distinct \
+                      types - dividing two `Int` values results in an `Int`.",
+        erroneous: "\
+half(Int x) -> Num
+    return x / 2
+
+main()
+    display half(5)",
+        corrected: "\
+half(Num x) -> Num
+    return x / 2
+
+main()
+    display half(5)"
+    },
+
+    Explanation {
+        code: "T0106",
+        title: "Function may not return a value",
+        description: "Every possible path through the body of a function with a return type must end in a \
+                      `return` statement. A function whose body only returns within 'if' and 'elif' clauses \
+                      without an 'else' clause, or within a 'while' statement, may reach the end of its body \
+                      without returning.",
+        erroneous: "\
+sign(Int x) -> Int
+    if x < 0
+        return ~1
+    elif x > 0
+        return 1
+
+main()
+    display sign(5)",
+        corrected: "\
+sign(Int x) -> Int
+    if x < 0
+        return ~1
+    elif x > 0
+        return 1
+    return 0
+
+main()
+    display sign(5)"
+    },
+
+    Explanation {
+        code: "T0107",
+        title: "Function defined within another function",
+        description: "Functions may only be defined at the top level of a program, not within the body of \
+                      another function.",
+        erroneous: "\
+main()
+    square(Int x) -> Int
+        return x * x
+    display square(3)",
+        corrected: "\
+square(Int x) -> Int
+    return x * x
+
+main()
+    display square(3)"
+    },
+
+    Explanation {
+        code: "T0108",
+        title: "No main function",
+        description: "Every program must define a function named `main` that takes no parameters and has no \
+                      return type. This is the function run when the program starts.",
+        erroneous: "\
+start()
+    display \"Hello\"",
+        corrected: "\
+main()
+    display \"Hello\""
+    },
+
+    Explanation {
+        code: "T0201",
+        title: "Type does not exist",
+        description: "A type was used that is neither one of the primitive types (`Int`, `Num`, `Char`, `Bool` \
+                      and `String`) nor a record or enum type defined in the program.",
+        erroneous: "\
+main()
+    Float x = 1.5
+    display x",
+        corrected: "\
+main()
+    Num x = 1.5
+    display x"
+    },
+
+    Explanation {
+        code: "T0202",
+        title: "Value of an unexpected type",
+        description: "A value was used where a value of a different type is expected. Note that `Int` and `Num` \
+                      are distinct types - an `Int` value may be converted to a `Num` by writing `Num(value)`.",
+        erroneous: "\
+main()
+    Int count = 3
+    Num average = 7.5 / count
+    display average",
+        corrected: "\
+main()
+    Int count = 3
+    Num average = 7.5 / Num(count)
+    display average"
+    },
+
+    Explanation {
+        code: "T0203",
+        title: "Invalid conversion",
+        description: "A value cannot be converted to the given type. Values cannot be converted to or from \
+                      `String`, nor between `Char` and `Bool`, nor from a `Num` directly to a `Char`.",
+        erroneous: "\
+main()
+    Char letter = Char(97.0)
+    display letter",
+        corrected: "\
+main()
+    Char letter = Char(Int(97.0))
+    display letter"
+    },
+
+    Explanation {
+        code: "T0204",
+        title: "Wrong number of values given when creating a value",
+        description: "A record value must be created with exactly one value for each of its fields, and an enum \
+                      value with exactly one value for each of the fields of its variant. A conversion takes \
+                      exactly one value.",
+        erroneous: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point origin = Point(0.0)
+    display origin.x",
+        corrected: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point origin = Point(0.0, 0.0)
+    display origin.x"
+    },
+
+    Explanation {
+        code: "T0205",
+        title: "Type defined more than once",
+        description: "A record or enum type was defined with the same name as an existing type, or an enum \
+                      variant was given the same name as a type or another variant. Every type and variant must \
+                      have a distinct name.",
+        erroneous: "\
+type Point
+    Num x
+    Num y
+
+type Point
+    Num x
+    Num y
+    Num z
+
+main()
+    Point p = Point(1.0, 2.0)
+    display p.x",
+        corrected: "\
+type Point
+    Num x
+    Num y
+
+type Point3D
+    Num x
+    Num y
+    Num z
+
+main()
+    Point p = Point(1.0, 2.0)
+    display p.x"
+    },
+
+    Explanation {
+        code: "T0206",
+        title: "Field defined more than once",
+        description: "Every field of a record type, or of a variant of an enum type, must have a distinct name.",
+        erroneous: "\
+type Rectangle
+    Num width
+    Num width
+
+main()
+    Rectangle r = Rectangle(2.0, 3.0)
+    display r.width",
+        corrected: "\
+type Rectangle
+    Num width
+    Num height
+
+main()
+    Rectangle r = Rectangle(2.0, 3.0)
+    display r.width * r.height"
+    },
+
+    Explanation {
+        code: "T0207",
+        title: "Field does not exist",
+        description: "A field was accessed that is not one of the fields of the record type of the value being \
+                      accessed, or a field was accessed on a value that is not a record.",
+        erroneous: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point p = Point(1.0, 2.0)
+    display p.z",
+        corrected: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point p = Point(1.0, 2.0)
+    display p.y"
+    },
+
+    Explanation {
+        code: "T0208",
+        title: "Record or enum value used where not allowed",
+        description: "Record and enum values cannot be compared using `==` or `!=`, nor displayed. Compare or \
+                      display their fields instead, or use a 'match' statement for an enum value.",
+        erroneous: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point a = Point(1.0, 2.0)
+    Point b = Point(1.0, 2.0)
+    display a == b",
+        corrected: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point a = Point(1.0, 2.0)
+    Point b = Point(1.0, 2.0)
+    display a.x == b.x and a.y == b.y"
+    },
+
+    Explanation {
+        code: "T0209",
+        title: "Type defined within a function",
+        description: "Record and enum types may only be defined at the top level of a program, not within the \
+                      body of a function.",
+        erroneous: "\
+main()
+    type Point
+        Num x
+        Num y
+    Point p = Point(1.0, 2.0)
+    display p.x",
+        corrected: "\
+type Point
+    Num x
+    Num y
+
+main()
+    Point p = Point(1.0, 2.0)
+    display p.x"
+    },
+
+    Explanation {
+        code: "T0301",
+        title: "Array of records or enums",
+        description: "The elements of an array must be of a primitive type (`Int`, `Num`, `Char`, `Bool` or \
+                      `String`). Consider using an array for each field instead.",
+        erroneous: "\
+type Point
+    Num x
+    Num y
+
+main()
+    [Point; 3] points
+    display points[0].x",
+        corrected: "\
+main()
+    [Num; 3] xs
+    [Num; 3] ys
+    display xs[0] + ys[0]"
+    },
+
+    Explanation {
+        code: "T0302",
+        title: "Array used without an index",
+        description: "An array must always be indexed - arrays cannot be assigned, compared or displayed as a \
+                      whole, nor passed to or returned from functions.",
+        erroneous: "\
+main()
+    [Int; 3] scores
+    display scores",
+        corrected: "\
+main()
+    [Int; 3] scores
+    display scores[0]"
+    },
+
+    Explanation {
+        code: "T0303",
+        title: "Index of a value that is not an array",
+        description: "Only arrays may be indexed using square brackets.",
+        erroneous: "\
+main()
+    Int total = 10
+    display total[0]",
+        corrected: "\
+main()
+    Int total = 10
+    display total"
+    },
+
+    Explanation {
+        code: "T0304",
+        title: "Array with too many elements",
+        description: "An array may have at most 65536 elements. Use several smaller arrays instead.",
+        erroneous: "\
+main()
+    [Int; 100000] counts
+    display counts[0]",
+        corrected: "\
+main()
+    [Int; 50000] low_counts
+    [Int; 50000] high_counts
+    display low_counts[0] + high_counts[0]"
+    },
+
+    Explanation {
+        code: "T0401",
+        title: "Match on a value that is not an enum",
+        description: "A 'match' statement determines which variant of an enum a value is, so may only be used \
+                      with enum values. Use 'if' and 'elif' clauses to handle values of other types.",
+        erroneous: "\
+main()
+    Int day = 2
+    match day
+        Monday
+            display \"Start of the week\"",
+        corrected: "\
+enum Day
+    Monday
+    Tuesday
+
+main()
+    Day day = Tuesday
+    match day
+        Monday
+            display \"Start of the week\"
+        Tuesday
+            display \"Second day of the week\""
+    },
+
+    Explanation {
+        code: "T0402",
+        title: "Match arm for a variant that does not exist",
+        description: "Every arm of a 'match' statement must be for one of the variants of the type of enum value \
+                      being matched.",
+        erroneous: "\
+enum Light
+    Red
+    Green
+
+main()
+    Light light = Red
+    match light
+        Red
+            display \"Stop\"
+        Blue
+            display \"Go\"",
+        corrected: "\
+enum Light
+    Red
+    Green
+
+main()
+    Light light = Red
+    match light
+        Red
+            display \"Stop\"
+        Green
+            display \"Go\""
+    },
+
+    Explanation {
+        code: "T0403",
+        title: "Variant handled by more than one match arm",
+        description: "Every variant of an enum must be handled by exactly one arm of a 'match' statement.",
+        erroneous: "\
+enum Light
+    Red
+    Green
+
+main()
+    Light light = Red
+    match light
+        Red
+            display \"Stop\"
+        Green
+            display \"Go\"
+        Red
+            display \"Wait\"",
+        corrected: "\
+enum Light
+    Red
+    Green
+
+main()
+    Light light = Red
+    match light
+        Red
+            display \"Stop\"
+        Green
+            display \"Go\""
+    },
+
+    Explanation {
+        code: "T0404",
+        title: "Wrong number of variables in a match arm",
+        description: "An arm of a 'match' statement must give a variable for each field of the variant it \
+                      handles, with each variable being of the type of the corresponding field.",
+        erroneous: "\
+enum Shape
+    Circle(Num radius)
+    Square(Num side)
+
+main()
+    Shape shape = Circle(1.5)
+    match shape
+        Circle
+            display \"A circle\"
+        Square(Num side)
+            display side * side",
+        corrected: "\
+enum Shape
+    Circle(Num radius)
+    Square(Num side)
+
+main()
+    Shape shape = Circle(1.5)
+    match shape
+        Circle(Num radius)
+            display 3.14 * radius * radius
+        Square(Num side)
+            display side * side"
+    },
+
+    Explanation {
+        code: "T0405",
+        title: "Match does not handle every variant",
+        description: "Every variant of an enum must be handled by an arm of a 'match' statement. The message lists \
+                      the variants without an arm.",
+        erroneous: "\
+enum Shape
+    Circle(Num radius)
+    Square(Num side)
+
+main()
+    Shape shape = Circle(1.5)
+    match shape
+        Circle(Num radius)
+            display 3.14 * radius * radius",
+        corrected: "\
+enum Shape
+    Circle(Num radius)
+    Square(Num side)
+
+main()
+    Shape shape = Circle(1.5)
+    match shape
+        Circle(Num radius)
+            display 3.14 * radius * radius
+        Square(Num side)
+            display side * side"
+    },
+
+    Explanation {
+        code: "T0501",
+        title: "Statement not allowed at the top level",
+        description: "Only global variable declarations and the definitions of functions and types may appear at \
+                      the top level of a program. Other statements must be placed within a function, such as \
+                      `main`.",
+        erroneous: "\
+display \"Hello\"
+
+main()
+    display \"World\"",
+        corrected: "\
+main()
+    display \"Hello\"
+    display \"World\""
+    },
+
+    Explanation {
+        code: "W0001",
+        title: "Unused variable",
+        description: "A variable (local or global) is declared yet never used. Remove the variable, or begin its \
+                      identifier with an underscore should it be intentionally unused.",
+        erroneous: "\
+main()
+    Int count
+    display 5",
+        corrected: "\
+main()
+    Int count = 5
+    display count"
+    },
+
+    Explanation {
+        code: "W0002",
+        title: "Variable assigned a value that is never read",
+        description: "A variable (local or global) is assigned a value yet that value is never read. Remove the \
+                      variable, or begin its identifier with an underscore should it be intentionally unused.",
+        erroneous: "\
+main()
+    Int total = 0
+    total = 5
+    display 5",
+        corrected: "\
+main()
+    Int total = 0
+    total = 5
+    display total"
+    },
+
+    Explanation {
+        code: "W0003",
+        title: "Unused parameter",
+        description: "A function parameter is never used. Remove the parameter, or begin its identifier with an \
+                      underscore should it be intentionally unused.",
+        erroneous: "\
+greet(String name)
+    display \"Hello\"
+
+main()
+    greet(\"Ada\")",
+        corrected: "\
+greet(String name)
+    display name
+
+main()
+    greet(\"Ada\")"
+    },
+
+    Explanation {
+        code: "W0004",
+        title: "Unused function",
+        description: "A function is never called from `main`, either directly or by way of other functions. \
+                      Remove the function, or begin its identifier with an underscore should it be \
+                      intentionally unused.",
+        erroneous: "\
+helper()
+    display 1
+
+main()
+    display 2",
+        corrected: "\
+helper()
+    display 1
+
+main()
+    helper()
+    display 2"
+    }
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    #[test]
+    fn examples() {
+        let codes = |source: &str| crate::analyse(source, false).1.iter().map(|diagnostic| diagnostic.code).collect::<Vec<_>>();
+
+        for explanation in super::EXPLANATIONS {
+            assert!(codes(explanation.erroneous).contains(&explanation.code), "{}: {:?}", explanation.code, codes(explanation.erroneous));
+            assert_eq!(codes(explanation.corrected), Vec::<&str>::new(), "{}", explanation.code);
+        }
+
+        let unique: HashSet<_> = super::EXPLANATIONS.iter().map(|explanation| explanation.code).collect();
+        assert_eq!(unique.len(), super::EXPLANATIONS.len());
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(super::lookup("t0103").map(|explanation| explanation.title), Some("Function without a return value used in an expression"));
+        assert!(super::lookup("T9999").is_none());
+
+        assert_eq!(super::wrap("aa bb cc dd", 5), "aa bb\ncc dd");
+        assert_eq!(super::wrap("abcdefgh ij", 5), "abcdefgh\nij");
+    }
+}
@@ -0,0 +1,128 @@
+//! Contains code for the formatting of till code. Formatting is performed on
+//! the token stream (including comments) rather than on the AST so that
+//! comments are preserved.
+
+use crate::lexing::lexer::{ Token, TokenType };
+
+/// Format the given tokens (which should include comment tokens) as till code.
+/// Each level of indentation is a single tab, tokens on the same line are
+/// separated by at most a single space, and consecutive blank lines are
+/// reduced to one. Comments and literals are kept as they were written.
+pub fn format(tokens: &[Token]) -> String {
+    let mut output = String::new();
+    let mut level = 0;
+    let mut previous: Option<&Token> = None; // Previous token on the current line.
+    let mut previous_end_line = None;
+
+    for (index, tok) in tokens.iter().enumerate() {
+        match tok.tok_type {
+            TokenType::Newline(new_level) => {
+                level = new_level;
+                output.push('\n');
+                if tok.lexeme.text.matches('\n').count() > 1 { output.push('\n'); }
+                previous = None;
+            }
+
+            _ => {
+                let start_line = tok.lexeme.span.start.line_number;
+
+                // A comment on a line of its own takes the indentation of the
+                // line of code following it:
+                if let (TokenType::Comment(_), Some(end_line)) = (&tok.tok_type, previous_end_line) {
+                    if start_line > end_line && previous.is_some() {
+                        output.push('\n');
+                        if start_line > end_line + 1 { output.push('\n'); }
+                        previous = None;
+                    }
+                    if previous.is_none() { level = following_level(&tokens[index..]).unwrap_or(level); }
+                }
+
+                match previous {
+                    Some(prev) if needs_space(&prev.tok_type, &tok.tok_type) => output.push(' '),
+                    Some(_) => {}
+                    None => output.push_str(&"\t".repeat(level))
+                }
+
+                output.push_str(&tok.lexeme.text);
+                previous = Some(tok);
+            }
+        }
+
+        previous_end_line = Some(tok.lexeme.span.end.line_number);
+    }
+
+    format!("{}\n", output.trim_end())
+}
+
+/// Get the indentation level of the next line of code, if there is such a line.
+fn following_level(tokens: &[Token]) -> Option<usize> {
+    tokens.iter().find_map(|tok| match tok.tok_type {
+        TokenType::Newline(level) => Some(level),
+        _ => None
+    })
+}
+
+/// Should a space be placed between two adjacent tokens on the same line?
+fn needs_space(previous: &TokenType, next: &TokenType) -> bool {
+    match (previous, next) {
+        (TokenType::BracketOpen, _) | (TokenType::SquareBracketOpen, _) | (TokenType::Dot, _) |
+        (TokenType::Tilde, _) | (TokenType::ExclaimationMark, _) => false,
+
+        (_, TokenType::BracketClose) | (_, TokenType::SquareBracketClose) | (_, TokenType::Comma) |
+        (_, TokenType::Dot) | (_, TokenType::Semicolon) => false,
+
+        // Function calls, constructions and indexing:
+        (TokenType::Identifier(_), TokenType::BracketOpen) | (TokenType::TypeIdentifier(_), TokenType::BracketOpen) |
+        (TokenType::Identifier(_), TokenType::SquareBracketOpen) => false,
+
+        _ => true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ lexing::lexer, stream::Stream };
+
+    fn format(source: &str) -> String {
+        let config = lexer::Config { keep_comments: true, ..lexer::Config::default() };
+        let tokens: Vec<lexer::Token> = lexer::input_with_config(Stream::from_str(source), config).map(Result::unwrap).collect();
+        super::format(&tokens)
+    }
+
+    #[test]
+    fn formatting() {
+        let source = concat!(
+            "\n",
+            "# Add two numbers:\n",
+            "add(Num  x,Num y)->Num\n",
+            "  return x+y   # Sum\n",
+            "\n\n\n",
+            "main( )\n",
+            "  [Int;3]   values\n",
+            "  values[ 0 ]= ~ 1\n",
+            "      # Display the result:\n",
+            "  if !(values[0]>=2)and true\n",
+            "    display add(1 ,Num(values[0])).x #[ block\n",
+            "comment ]#\n",
+            "  display 'a'\n"
+        );
+
+        let expected = concat!(
+            "# Add two numbers:\n",
+            "add(Num x, Num y) -> Num\n",
+            "\treturn x + y # Sum\n",
+            "\n",
+            "main()\n",
+            "\t[Int; 3] values\n",
+            "\tvalues[0] = ~1\n",
+            "\t# Display the result:\n",
+            "\tif !(values[0] >= 2) and true\n",
+            "\t\tdisplay add(1, Num(values[0])).x #[ block\n",
+            "comment ]#\n",
+            "\tdisplay 'a'\n"
+        );
+
+        assert_eq!(format(source), expected);
+        assert_eq!(format(expected), expected);
+    }
+}
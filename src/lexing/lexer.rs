@@ -1,7 +1,7 @@
 //! Lexing code specific to the lexical analysis of the till language.
 
 use crate::stream;
-use std::{ fmt, collections::HashMap };
+use std::{ fmt, collections::{ HashMap, VecDeque } };
 
 pub type Token = super::GenericToken<TokenType>;
 
@@ -12,12 +12,23 @@ impl fmt::Display for Token {
             TokenType::Newline(0) => "newline",
             TokenType::Identifier(_) => "identifier",
             TokenType::TypeIdentifier(_) => "type identifier",
+            TokenType::IntegerLiteral(_) |
             TokenType::NumberLiteral(_) |
-            TokenType::CharLiteral(_) => "literal",
+            TokenType::CharLiteral(_) |
+            TokenType::StringLiteral(_) => "literal",
             TokenType::IfKeyword |
+            TokenType::ElifKeyword |
+            TokenType::ElseKeyword |
             TokenType::WhileKeyword |
+            TokenType::AndKeyword |
+            TokenType::OrKeyword |
             TokenType::TrueKeyword |
-            TokenType::FalseKeyword => "keyword",
+            TokenType::FalseKeyword |
+            TokenType::TypeKeyword |
+            TokenType::EnumKeyword |
+            TokenType::MatchKeyword |
+            TokenType::LetKeyword => "keyword",
+            TokenType::Comment(_) => "comment",
             _ => "token"
         };
 
@@ -25,39 +36,206 @@ impl fmt::Display for Token {
     }
 }
 
-pub type TokenStream<'a> = super::GenericTokenStream<'a, TokenType, StateKey>;
+/// Iterator that yields till tokens. Wraps the generic token stream so as to
+/// determine the indentation level of each `Newline` token (as this depends on
+/// the indentation style used by the input as a whole) and to handle comments.
+pub struct TokenStream<'a> {
+    tokens: super::GenericTokenStream<'a, TokenType, StateKey>,
+    /// Tokens read from the generic token stream that are yet to be yielded:
+    lookahead: VecDeque<super::Result<Token>>,
+    indent_style: Option<IndentStyle>,
+    /// Has a token been yielded yet?
+    started: bool,
+    config: Config
+}
+
+/// Options altering the behaviour of the till lexer.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// The number of spaces making up a single level of indentation. When not
+    /// specified, this is taken from the first line indented using spaces.
+    pub indent_width: Option<usize>,
+    /// Should comment tokens be yielded rather than discarded? Useful for
+    /// tooling that works on the token stream.
+    pub keep_comments: bool
+}
+
+/// The indentation style of an input, established by the first indented line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum IndentStyle {
+    Tabs,
+    /// Value is the number of spaces in a single level of indentation.
+    Spaces(usize)
+}
+
+const BLOCK_COMMENT_START: &str = "#[";
+const BLOCK_COMMENT_END: &str = "]#";
+
+impl Iterator for TokenStream<'_> {
+    type Item = super::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_raw()? {
+                Ok(Token { tok_type: TokenType::Newline(_), lexeme }) => {
+                    // A newline followed by a line containing only a comment is
+                    // discarded so that the comment's indentation is irrelevant.
+                    // Any newlines at the very start of the input are discarded
+                    // also:
+                    if !self.started || self.comment_only_line_follows() { continue }
+
+                    return Some(self.indentation_level(&lexeme).map(|level| Token { tok_type: TokenType::Newline(level), lexeme }))
+                }
+
+                Ok(Token { tok_type: TokenType::Comment(_), .. }) if !self.config.keep_comments => {}
+
+                other => {
+                    self.started = true;
+                    return Some(other)
+                }
+            }
+        }
+    }
+}
+
+impl TokenStream<'_> {
+    /// Take the next token from the lookahead buffer or, if empty, from the
+    /// generic token stream.
+    fn next_raw(&mut self) -> Option<super::Result<Token>> {
+        self.lookahead.pop_front().or_else(|| self.read_token())
+    }
+
+    /// Peek the token a given number of tokens ahead of the next.
+    fn peek_raw(&mut self, offset: usize) -> Option<&super::Result<Token>> {
+        while self.lookahead.len() <= offset {
+            let tok = self.read_token()?;
+            self.lookahead.push_back(tok);
+        }
+        self.lookahead.get(offset)
+    }
+
+    /// Read a token from the generic token stream. As block comments may be
+    /// nested, they cannot be recognised by the lexer automaton alone - the
+    /// automaton only recognises the start of a block comment with the
+    /// remainder of the comment read here.
+    fn read_token(&mut self) -> Option<super::Result<Token>> {
+        match self.tokens.next()? {
+            Ok(Token { lexeme, .. }) if lexeme.text == BLOCK_COMMENT_START => Some(self.read_block_comment(lexeme)),
+            other => Some(other)
+        }
+    }
+
+    /// Read the remainder of a block comment, including any nested block
+    /// comments, from the input stream.
+    fn read_block_comment(&mut self, start: super::Lexeme) -> super::Result<Token> {
+        let super::Lexeme { mut text, span: stream::Span { start, mut end } } = start;
+        let mut depth = 1;
+
+        while depth > 0 {
+            let chr = match self.tokens.strm.peek() {
+                Some(chr) => chr,
+                None => return Err(super::Failure::UnexpectedEof(super::Lexeme { text, span: stream::Span { start, end } }))
+            };
+            end = self.tokens.strm.get_next_pos();
+            self.tokens.strm.advance();
+            text.push(chr);
+
+            if text.ends_with(BLOCK_COMMENT_START) { depth += 1 }
+            else if text.ends_with(BLOCK_COMMENT_END) { depth -= 1 }
+        }
+
+        let contents = text[BLOCK_COMMENT_START.len()..text.len() - BLOCK_COMMENT_END.len()].to_string();
+
+        Ok(Token {
+            tok_type: TokenType::Comment(contents),
+            lexeme: super::Lexeme { text, span: stream::Span { start, end } }
+        })
+    }
+
+    /// Is the next line made up of only a comment?
+    fn comment_only_line_follows(&mut self) -> bool {
+        matches!(self.peek_raw(0), Some(Ok(Token { tok_type: TokenType::Comment(_), .. }))) &&
+        matches!(self.peek_raw(1), None | Some(Ok(Token { tok_type: TokenType::Newline(_), .. })))
+    }
+
+    /// Determine the indentation level of the final line of a newline lexeme,
+    /// ensuring that the indentation is consistent with that of any previous
+    /// lines.
+    fn indentation_level(&mut self, lexeme: &super::Lexeme) -> super::Result<usize> {
+        // Ignore any empty lines, only consider final populated line:
+        let line = lexeme.text.rsplit('\n').next().unwrap();
+        let inconsistent = || super::Failure::InconsistentIndentation(lexeme.clone());
+
+        if line.is_empty() { return Ok(0) }
+
+        if line.chars().all(|c| c == '\t') {
+            match self.indent_style.get_or_insert(IndentStyle::Tabs) {
+                IndentStyle::Tabs => Ok(line.len()),
+                IndentStyle::Spaces(_) => Err(inconsistent())
+            }
+        }
+        else if line.chars().all(|c| c == ' ') {
+            let width = self.config.indent_width.unwrap_or(line.len());
+
+            match self.indent_style.get_or_insert(IndentStyle::Spaces(width)) {
+                IndentStyle::Spaces(width) if line.len().is_multiple_of(*width) => Ok(line.len() / *width),
+                _ => Err(inconsistent())
+            }
+        }
+        else { Err(inconsistent()) } // Tabs and spaces mixed on the same line.
+    }
+}
 
 /// All lexing tokens yielded by the till lexer.
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     Newline(usize), // Value is indentation level of the new line.
+    Comment(String), // Value is the text of the comment excluding delimiters.
 
     Identifier(String),
     TypeIdentifier(String),
 
+    IntegerLiteral(u64),
     NumberLiteral(f64),
     CharLiteral(char),
+    StringLiteral(String),
     FalseKeyword, // false
     ReturnKeyword, // return
     IfKeyword, // if
+    ElifKeyword, // elif
+    ElseKeyword, // else
     WhileKeyword, // while
+    AndKeyword, // and
+    OrKeyword, // or
     TrueKeyword, // true
     DisplayKeyword,
+    TypeKeyword, // type
+    EnumKeyword, // enum
+    MatchKeyword, // match
+    LetKeyword, // let
 
     BracketOpen, // (
     BracketClose, // )
+    SquareBracketOpen, // [
+    SquareBracketClose, // ]
 
     DoubleEquals, // ==
+    NotEquals, // !=
+    GreaterThanOrEqual, // >=
+    LessThanOrEqual, // <=
     Arrow, // ->
 
     GreaterThan, // >
     LessThan, // <
     Comma, // ,
+    Semicolon, // ;
+    Dot, // .
     Equals, // =
     Plus, // +
     Minus, // -
     Slash, // /
     Star, // *
+    Percent, // %
     Caret, // ^
     ExclaimationMark, // !
     Tilde // ~
@@ -71,15 +249,27 @@ pub enum StateKey {
     IdentifierOrKeyword, TypeIdentifier,
     Newline,
     BeginChar, CharEnd, CharEscapeSequence, CharLiteral,
+    StringBody, StringEscapeSequence, StringLiteral,
+    Comment, LineComment, BlockComment,
     Minus,
-    Equals,
+    Equals, GreaterThan, LessThan, ExclaimationMark,
     Other
 }
 
 pub fn input<'a>(strm: stream::Stream) -> TokenStream<'a> {
-    super::GenericTokenStream {
-        strm,
-        settings: &TILL_SETTINGS
+    input_with_config(strm, Config::default())
+}
+
+pub fn input_with_config<'a>(strm: stream::Stream, config: Config) -> TokenStream<'a> {
+    TokenStream {
+        tokens: super::GenericTokenStream {
+            strm,
+            settings: &TILL_SETTINGS
+        },
+        lookahead: VecDeque::new(),
+        indent_style: None,
+        started: false,
+        config
     }
 }
 
@@ -114,6 +304,14 @@ lazy_static::lazy_static! {
                         match_by: super::Match::ByChar('\''),
                         to: super::Dest::To(StateKey::BeginChar)
                     },
+                    super::Transition {
+                        match_by: super::Match::ByChar('"'),
+                        to: super::Dest::To(StateKey::StringBody)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByChar('#'),
+                        to: super::Dest::To(StateKey::Comment)
+                    },
                     super::Transition {
                         match_by: super::Match::ByChar('-'),
                         to: super::Dest::To(StateKey::Minus)
@@ -123,7 +321,19 @@ lazy_static::lazy_static! {
                         to: super::Dest::To(StateKey::Equals)
                     },
                     super::Transition {
-                        match_by: super::Match::ByChars(vec!['(', ')', '[', ']', '>', '<', ',', '+', '/', '*', '^', '!', '~']),
+                        match_by: super::Match::ByChar('>'),
+                        to: super::Dest::To(StateKey::GreaterThan)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByChar('<'),
+                        to: super::Dest::To(StateKey::LessThan)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByChar('!'),
+                        to: super::Dest::To(StateKey::ExclaimationMark)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByChars(vec!['(', ')', '[', ']', ',', ';', '.', '+', '/', '*', '%', '^', '~']),
                         to: super::Dest::To(StateKey::Other)
                     }
                 ]
@@ -135,7 +345,7 @@ lazy_static::lazy_static! {
         states.insert(
             StateKey::Integer,
             super::State {
-                parse: super::Parse::ByFunction(&parse_number_literal),
+                parse: super::Parse::ByFunction(&parse_integer_literal),
                 transitions: vec![
                     super::Transition {
                         match_by: super::Match::ByChar('.'),
@@ -183,11 +393,19 @@ lazy_static::lazy_static! {
                 parse: super::Parse::ByFunction(&|lexeme| {
                     match lexeme {
                         "if" => TokenType::IfKeyword,
+                        "elif" => TokenType::ElifKeyword,
+                        "else" => TokenType::ElseKeyword,
                         "while" => TokenType::WhileKeyword,
+                        "and" => TokenType::AndKeyword,
+                        "or" => TokenType::OrKeyword,
                         "true" => TokenType::TrueKeyword,
                         "false" => TokenType::FalseKeyword,
                         "return" => TokenType::ReturnKeyword,
                         "display" => TokenType::DisplayKeyword,
+                        "type" => TokenType::TypeKeyword,
+                        "enum" => TokenType::EnumKeyword,
+                        "match" => TokenType::MatchKeyword,
+                        "let" => TokenType::LetKeyword,
                         x => TokenType::Identifier(x.to_string())
                     }
                 }),
@@ -220,13 +438,12 @@ lazy_static::lazy_static! {
         states.insert(
             StateKey::Newline,
             super::State {
-                parse: super::Parse::ByFunction(&|lexeme| {
-                    let line = lexeme.split('\n').last().unwrap(); // Ignore any empty lines, only consider final populated line.
-                    TokenType::Newline(line.matches('\t').count())
-                }),
+                // Indentation level is determined by `TokenStream` as it depends
+                // on the indentation style used by the rest of the input:
+                parse: super::Parse::To(TokenType::Newline(0)),
                 transitions: vec![
                     super::Transition {
-                        match_by: super::Match::ByChars(vec!['\n', '\t']),
+                        match_by: super::Match::ByChars(vec!['\n', '\t', ' ']),
                         to: super::Dest::ToSelf
                     }
                 ]
@@ -301,6 +518,103 @@ lazy_static::lazy_static! {
             }
         );
 
+        /* STRING LITERALS */
+
+        states.insert(
+            StateKey::StringBody,
+            super::State {
+                parse: super::Parse::Invalid,
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByChar('"'),
+                        to: super::Dest::To(StateKey::StringLiteral)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByChar('\\'),
+                        to: super::Dest::To(StateKey::StringEscapeSequence)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByFunction(&|c| *c != '\n'),
+                        to: super::Dest::ToSelf
+                    }
+                ]
+            }
+        );
+
+        states.insert(
+            StateKey::StringEscapeSequence,
+            super::State {
+                parse: super::Parse::Invalid,
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByChars(vec!['n', 't', '\\', '\'', '"']),
+                        to: super::Dest::To(StateKey::StringBody)
+                    }
+                ]
+            }
+        );
+
+        states.insert(
+            StateKey::StringLiteral,
+            super::State {
+                parse: super::Parse::ByFunction(&|lexeme| {
+                    let mut value = String::new();
+                    let mut chars = lexeme[1..lexeme.len() - 1].chars(); // Exclude quotation marks.
+
+                    while let Some(chr) = chars.next() {
+                        value.push(
+                            if chr == '\\' { char_to_escape_sequence(chars.next().unwrap()) }
+                            else { chr }
+                        );
+                    }
+
+                    TokenType::StringLiteral(value)
+                }),
+                transitions: vec![]
+            }
+        );
+
+        /* COMMENTS */
+
+        states.insert(
+            StateKey::Comment,
+            super::State {
+                parse: super::Parse::To(TokenType::Comment(String::new())),
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByChar('['), // Lexeme will be: #[
+                        to: super::Dest::To(StateKey::BlockComment)
+                    },
+                    super::Transition {
+                        match_by: super::Match::ByFunction(&|c| *c != '\n'),
+                        to: super::Dest::To(StateKey::LineComment)
+                    }
+                ]
+            }
+        );
+
+        states.insert(
+            StateKey::LineComment,
+            super::State {
+                parse: super::Parse::ByFunction(&|lexeme| TokenType::Comment(lexeme[1..].to_string())),
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByFunction(&|c| *c != '\n'),
+                        to: super::Dest::ToSelf
+                    }
+                ]
+            }
+        );
+
+        states.insert(
+            StateKey::BlockComment,
+            super::State {
+                // Remainder of the block comment is read by `TokenStream`:
+                parse: super::Parse::To(TokenType::Comment(String::new())),
+                transitions: vec![]
+            }
+        );
+
         /* MINUS */
 
         states.insert(
@@ -331,6 +645,44 @@ lazy_static::lazy_static! {
             }
         );
 
+        states.insert(
+            StateKey::GreaterThan,
+            super::State {
+                parse: super::Parse::To(TokenType::GreaterThan),
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByChar('='), // Lexeme will be: >=
+                        to: super::Dest::To(StateKey::Other)
+                    }
+                ]
+            }
+        );
+
+        states.insert(
+            StateKey::LessThan,
+            super::State {
+                parse: super::Parse::To(TokenType::LessThan),
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByChar('='), // Lexeme will be: <=
+                        to: super::Dest::To(StateKey::Other)
+                    }
+                ]
+            }
+        );
+
+        states.insert(
+            StateKey::ExclaimationMark,
+            super::State {
+                parse: super::Parse::To(TokenType::ExclaimationMark),
+                transitions: vec![
+                    super::Transition {
+                        match_by: super::Match::ByChar('='), // Lexeme will be: !=
+                        to: super::Dest::To(StateKey::Other)
+                    }
+                ]
+            }
+        );
 
         /* OTHER TOKENS */
 
@@ -341,17 +693,22 @@ lazy_static::lazy_static! {
                     match lexeme {
                         "->" => TokenType::Arrow,
                         "==" => TokenType::DoubleEquals,
+                        "!=" => TokenType::NotEquals,
+                        ">=" => TokenType::GreaterThanOrEqual,
+                        "<=" => TokenType::LessThanOrEqual,
 
                         "(" => TokenType::BracketOpen,
                         ")" => TokenType::BracketClose,
-                        ">" => TokenType::GreaterThan,
-                        "<" => TokenType::LessThan,
+                        "[" => TokenType::SquareBracketOpen,
+                        "]" => TokenType::SquareBracketClose,
                         "," => TokenType::Comma,
+                        ";" => TokenType::Semicolon,
+                        "." => TokenType::Dot,
                         "+" => TokenType::Plus,
                         "/" => TokenType::Slash,
                         "*" => TokenType::Star,
+                        "%" => TokenType::Percent,
                         "^" => TokenType::Caret,
-                        "!" => TokenType::ExclaimationMark,
                         "~" => TokenType::Tilde,
                         _ => panic!()
                     }
@@ -364,16 +721,31 @@ lazy_static::lazy_static! {
 
         let match_ignored = super::Match::ByChar(' ');
 
-        super::LexerSettings { states, initial_state_key, match_ignored }
+        // Should a lexical error occur part-way through a character or string
+        // literal, lexing resumes after the closing quotation mark so that the
+        // rest of the literal is not lexed as code:
+        let resynchronise = vec![
+            (StateKey::BeginChar, '\''), (StateKey::CharEnd, '\''), (StateKey::CharEscapeSequence, '\''),
+            (StateKey::StringBody, '"'), (StateKey::StringEscapeSequence, '"')
+        ].into_iter().collect();
+
+        super::LexerSettings { states, initial_state_key, match_ignored, resynchronise }
     };
 }
 
-fn match_digit(c: &char) -> bool { c.is_digit(10) }
+fn match_digit(c: &char) -> bool { c.is_ascii_digit() }
 
 fn match_alphanumeric_or_underscore(c: &char) -> bool { c.is_ascii_alphanumeric() || *c == '_' }
 
 fn parse_number_literal(s: &str) -> TokenType { TokenType::NumberLiteral(s.parse().unwrap()) }
 
+/// Parse a literal without a decimal point as the magnitude of an integer, with
+/// the parser deciding whether it is within the range of an Int. A literal too
+/// large to be represented at all is given the maximum value.
+fn parse_integer_literal(s: &str) -> TokenType {
+    TokenType::IntegerLiteral(s.parse().unwrap_or(u64::MAX))
+}
+
 fn char_to_escape_sequence(chr: char) -> char {
     match chr {
         'n' => '\n',
@@ -385,14 +757,14 @@ fn char_to_escape_sequence(chr: char) -> char {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::{ GenericToken, Failure };
-    use crate::stream::Stream;
+    use super::super::Failure;
+    use crate::stream::{ Stream, Span, Position };
 
     // Define some helper methods to make tests easier to write. These methods on
     // TokenStream will only exist in test builds of the program.
     impl TokenStream<'_> {
         fn assert_next(&mut self, expected_tok_type: TokenType) -> &mut Self {
-            if let Some(Ok(GenericToken { tok_type, .. })) = self.next() {
+            if let Some(Ok(Token { tok_type, .. })) = self.next() {
                 assert_eq!(tok_type, expected_tok_type);
             }
             else { panic!("Expected Ok(LexToken(..))"); }
@@ -415,6 +787,13 @@ mod tests {
             self
         }
     
+        fn assert_inconsistent_indentation_next(&mut self) -> &mut Self {
+            if let Some(Err(Failure::InconsistentIndentation(..))) = self.next() {}
+            else { panic!("Expected Err(LexFailure::InconsistentIndentation(..))"); }
+
+            self
+        }
+
         fn assert_end_of_stream(&mut self) {
             assert_eq!(self.next(), None);
         }
@@ -423,18 +802,30 @@ mod tests {
     #[test]
     fn ignored_characters() {
         input(Stream::from_str("  5 6.2   "))
-        .assert_next(TokenType::NumberLiteral(5.0))
+        .assert_next(TokenType::IntegerLiteral(5))
         .assert_next(TokenType::NumberLiteral(6.2))
         .assert_end_of_stream();
     }
 
     #[test]
     fn number_literals() {
-        input(Stream::from_str("12.3 12."))
+        input(Stream::from_str("12.3 12 12.0 100000000000000000000.0 12."))
         .assert_next(TokenType::NumberLiteral(12.3))
+        .assert_next(TokenType::IntegerLiteral(12))
+        .assert_next(TokenType::NumberLiteral(12.0))
+        .assert_next(TokenType::NumberLiteral(1e20))
         .assert_unexpected_eof_next();
     }
 
+    #[test]
+    fn integer_literals() {
+        input(Stream::from_str("9223372036854775807 9223372036854775808 100000000000000000000"))
+        .assert_next(TokenType::IntegerLiteral(9223372036854775807))
+        .assert_next(TokenType::IntegerLiteral(9223372036854775808))
+        .assert_next(TokenType::IntegerLiteral(u64::MAX))
+        .assert_end_of_stream();
+    }
+
     #[test]
     fn identifiers() {
         input(Stream::from_str("someTHIng _with5and6   Type Nice1_"))
@@ -446,26 +837,92 @@ mod tests {
 
     #[test]
     fn keywords() {
-        input(Stream::from_str("if  while  true false  return"))
+        input(Stream::from_str("if elif else  while and or  true false  return type enum match let"))
         .assert_next(TokenType::IfKeyword)
+        .assert_next(TokenType::ElifKeyword)
+        .assert_next(TokenType::ElseKeyword)
         .assert_next(TokenType::WhileKeyword)
+        .assert_next(TokenType::AndKeyword)
+        .assert_next(TokenType::OrKeyword)
         .assert_next(TokenType::TrueKeyword)
         .assert_next(TokenType::FalseKeyword)
-        .assert_next(TokenType::ReturnKeyword);
+        .assert_next(TokenType::ReturnKeyword)
+        .assert_next(TokenType::TypeKeyword)
+        .assert_next(TokenType::EnumKeyword)
+        .assert_next(TokenType::MatchKeyword)
+        .assert_next(TokenType::LetKeyword);
     }
 
     #[test]
     fn indentation() {
         input(Stream::from_str("0\n\t1\n\t\t2\n0   \n\t\t\n\t"))
-        .assert_next(TokenType::NumberLiteral(0.0))
+        .assert_next(TokenType::IntegerLiteral(0))
         .assert_next(TokenType::Newline(1))
-        .assert_next(TokenType::NumberLiteral(1.0))
+        .assert_next(TokenType::IntegerLiteral(1))
         .assert_next(TokenType::Newline(2))
-        .assert_next(TokenType::NumberLiteral(2.0))
+        .assert_next(TokenType::IntegerLiteral(2))
         .assert_next(TokenType::Newline(0))
-        .assert_next(TokenType::NumberLiteral(0.0))
+        .assert_next(TokenType::IntegerLiteral(0))
+
+        .assert_next(TokenType::Newline(1))
+        .assert_end_of_stream();
+    }
+
+    #[test]
+    fn space_indentation() {
+        input(Stream::from_str("0\n  1\n    2\n\n      \n  1"))
+        .assert_next(TokenType::IntegerLiteral(0))
+        .assert_next(TokenType::Newline(1))
+        .assert_next(TokenType::IntegerLiteral(1))
+        .assert_next(TokenType::Newline(2))
+        .assert_next(TokenType::IntegerLiteral(2))
+        .assert_next(TokenType::Newline(1))
+        .assert_next(TokenType::IntegerLiteral(1))
+        .assert_end_of_stream();
+
+        input_with_config(Stream::from_str("0\n    1\n        2"), Config { indent_width: Some(2), ..Config::default() })
+        .assert_next(TokenType::IntegerLiteral(0))
+        .assert_next(TokenType::Newline(2))
+        .assert_next(TokenType::IntegerLiteral(1))
+        .assert_next(TokenType::Newline(4));
+    }
+
+    #[test]
+    fn inconsistent_indentation() {
+        input(Stream::from_str("0\n    1\n      2\n\t3\n \t4"))
+        .assert_next(TokenType::IntegerLiteral(0))
+        .assert_next(TokenType::Newline(1))
+        .assert_next(TokenType::IntegerLiteral(1))
+        .assert_inconsistent_indentation_next()
+        .assert_next(TokenType::IntegerLiteral(2))
+        .assert_inconsistent_indentation_next()
+        .assert_next(TokenType::IntegerLiteral(3))
+        .assert_inconsistent_indentation_next();
+    }
+
+    #[test]
+    fn comments() {
+        input(Stream::from_str("# Header comment\n\n0 # Trailing\n\t1\n# Wrong indent\n   # Also wrong\n\t2 #[ Block #[ nested ]# ]# 3"))
+        .assert_next(TokenType::IntegerLiteral(0))
+        .assert_next(TokenType::Newline(1))
+        .assert_next(TokenType::IntegerLiteral(1))
+        .assert_next(TokenType::Newline(1))
+        .assert_next(TokenType::IntegerLiteral(2))
+        .assert_next(TokenType::IntegerLiteral(3))
+        .assert_end_of_stream();
 
+        input(Stream::from_str("#[ Never #[ closed ]#"))
+        .assert_unexpected_eof_next();
+    }
+
+    #[test]
+    fn kept_comments() {
+        input_with_config(Stream::from_str("0 #[ a\nb ]#\n\t# c\n\t1"), Config { keep_comments: true, ..Config::default() })
+        .assert_next(TokenType::IntegerLiteral(0))
+        .assert_next(TokenType::Comment(" a\nb ".to_string()))
+        .assert_next(TokenType::Comment(" c".to_string()))
         .assert_next(TokenType::Newline(1))
+        .assert_next(TokenType::IntegerLiteral(1))
         .assert_end_of_stream();
     }
 
@@ -479,6 +936,15 @@ mod tests {
         .assert_next(TokenType::CharLiteral('\n'));
     }
 
+    #[test]
+    fn string_literals() {
+        input(Stream::from_str("\"\" \"Hello, world!\" \"\\\"quoted\\\"\\n\" \"unterminated\n\""))
+        .assert_next(TokenType::StringLiteral("".to_string()))
+        .assert_next(TokenType::StringLiteral("Hello, world!".to_string()))
+        .assert_next(TokenType::StringLiteral("\"quoted\"\n".to_string()))
+        .assert_unexpected_char_next('\n');
+    }
+
     #[test]
     fn minus_and_arrow() {
         input(Stream::from_str("- ->"))
@@ -493,16 +959,33 @@ mod tests {
         .assert_next(TokenType::DoubleEquals);
     }
 
+    #[test]
+    fn comparison_operators() {
+        input(Stream::from_str("> >= < <= ! != !!="))
+        .assert_next(TokenType::GreaterThan)
+        .assert_next(TokenType::GreaterThanOrEqual)
+        .assert_next(TokenType::LessThan)
+        .assert_next(TokenType::LessThanOrEqual)
+        .assert_next(TokenType::ExclaimationMark)
+        .assert_next(TokenType::NotEquals)
+        .assert_next(TokenType::ExclaimationMark)
+        .assert_next(TokenType::NotEquals);
+    }
+
     #[test]
     fn other_tokens() {
-        input(Stream::from_str("() > < , + / * ^ ! ~"))
+        input(Stream::from_str("() [] > < , ; . + / * % ^ ! ~"))
         .assert_next(TokenType::BracketOpen).assert_next(TokenType::BracketClose)
+        .assert_next(TokenType::SquareBracketOpen).assert_next(TokenType::SquareBracketClose)
         .assert_next(TokenType::GreaterThan)
         .assert_next(TokenType::LessThan)
         .assert_next(TokenType::Comma)
+        .assert_next(TokenType::Semicolon)
+        .assert_next(TokenType::Dot)
         .assert_next(TokenType::Plus)
         .assert_next(TokenType::Slash)
         .assert_next(TokenType::Star)
+        .assert_next(TokenType::Percent)
         .assert_next(TokenType::Caret)
         .assert_next(TokenType::ExclaimationMark)
         .assert_next(TokenType::Tilde);
@@ -514,5 +997,42 @@ mod tests {
         .assert_unexpected_char_next('a')
         .assert_next(TokenType::Identifier("a".to_string()))
         .assert_unexpected_eof_next();
+
+        // A character that cannot begin any token is skipped:
+        input(Stream::from_str("1 $ 2"))
+        .assert_next(TokenType::IntegerLiteral(1))
+        .assert_unexpected_char_next('$')
+        .assert_next(TokenType::IntegerLiteral(2))
+        .assert_end_of_stream();
+        // The remainder of a character or string literal containing an error
+        // is skipped, up to the end of the line should it not be closed:
+        input(Stream::from_str("\"a\\qb\" 'xy' 5 \"c\\d\n6"))
+        .assert_unexpected_char_next('q')
+        .assert_unexpected_char_next('y')
+        .assert_next(TokenType::IntegerLiteral(5))
+        .assert_unexpected_char_next('d')
+        .assert_next(TokenType::Newline(0))
+        .assert_next(TokenType::IntegerLiteral(6))
+        .assert_end_of_stream();
+    }
+
+    #[test]
+    fn lexeme_spans() {
+        let spans: Vec<(usize, usize, usize, usize)> = input(Stream::from_str("Num xy\n\t#[ a\nb ]# 'c'")).map(|result| {
+            let span = result.unwrap().lexeme.span;
+            (span.start.line_number as usize, span.start.line_position as usize, span.end.line_number as usize, span.end.line_position as usize)
+        }).collect();
+
+        assert_eq!(spans, vec![(1, 1, 1, 3), (1, 5, 1, 6), (1, 7, 2, 1), (3, 6, 3, 8)]);
+
+        // The indentation of a newline lexeme begins at the start of its final line:
+        let newline = super::super::Lexeme { text: "\n\n\t\t".to_string(), span: Span {
+            start: Position { position: 4, line_number: 1, line_position: 4 },
+            end: Position { position: 7, line_number: 3, line_position: 2 }
+        }};
+        assert_eq!(newline.indentation_span(), Span {
+            start: Position { position: 6, line_number: 3, line_position: 1 },
+            end: Position { position: 7, line_number: 3, line_position: 2 }
+        });
     }
 }
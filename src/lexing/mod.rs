@@ -7,40 +7,73 @@ use crate::stream;
 use std::{ fmt, hash::Hash, collections::HashMap };
 
 /// Represents a token holding a token type and a lexeme.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GenericToken<TokenType> {
     pub tok_type: TokenType,
     pub lexeme: Lexeme
 }
 
-/// Holds the raw lexeme string, as well as the position in the input stream the
-/// lexeme is from.
-#[derive(Debug, PartialEq)]
+/// Holds the raw lexeme string, as well as the span of the input stream from
+/// the first to the last character of the lexeme.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Lexeme {
     pub text: String,
-    pub pos: stream::Position
+    pub span: stream::Span
+}
+
+impl Lexeme {
+    /// Get the span of the indentation following the final newline character
+    /// of this lexeme. Should this lexeme not begin with a newline or not be
+    /// followed by any indentation, the span of the entire lexeme is returned.
+    pub fn indentation_span(&self) -> stream::Span {
+        let final_line = self.text.rsplit('\n').next().unwrap();
+
+        if !self.text.starts_with('\n') || final_line.is_empty() { return self.span.clone() }
+
+        let start = stream::Position {
+            position: self.span.end.position + 1 - final_line.chars().count() as u64,
+            line_number: self.span.end.line_number,
+            line_position: 1
+        };
+        stream::Span { start, end: self.span.end.clone() }
+    }
 }
 
 impl fmt::Display for Lexeme {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?} at {}", self.text, self.pos)
+        write!(f, "{:?} at {}", self.text, self.span)
     }
 }
 
-/// Represents the two type of lexical analysis errors: the encountering of an
-/// unexpected character, and the reaching of the end of an input stream when it
-/// is not expected.
+/// Represents the types of lexical analysis errors: the encountering of an
+/// unexpected character, the reaching of the end of an input stream when it is
+/// not expected, and the use of indentation inconsistent with that of the rest
+/// of the input.
 #[derive(Debug, PartialEq)]
 pub enum Failure {
     UnexpectedChar(char, Lexeme),
-    UnexpectedEof(Lexeme)
+    UnexpectedEof(Lexeme),
+    InconsistentIndentation(Lexeme)
 }
 
 impl fmt::Display for Failure {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Failure::UnexpectedChar(unexpected_char, lexeme) => write!(f, "Encountered unexpected character {:?} while analysing lexeme {}", unexpected_char, lexeme),
-            Failure::UnexpectedEof(lexeme) => write!(f, "Encountered unexpected end of stream while analysing {}", lexeme)
+            Failure::UnexpectedEof(lexeme) => write!(f, "Encountered unexpected end of stream while analysing {}", lexeme),
+            Failure::InconsistentIndentation(lexeme) => write!(f, "Encountered indentation that mixes tabs and spaces or does not match the indentation used elsewhere in the input at {}", lexeme.span)
+        }
+    }
+}
+
+impl Failure {
+    /// Get the code identifying this kind of failure. These codes do not change
+    /// between versions so may be relied upon by tools consuming them.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Failure::UnexpectedChar(..) => "L0001",
+            Failure::UnexpectedEof(_) => "L0002",
+            Failure::InconsistentIndentation(_) => "L0003"
         }
     }
 }
@@ -61,6 +94,8 @@ where StateKey: Eq + Copy + Hash + fmt::Debug, TokenType: Clone + fmt::Debug {
     fn next(&mut self) -> Option<Self::Item> {
         let mut current_key = self.settings.initial_state_key;
         let mut text = String::new();
+        let mut start = self.strm.get_next_pos();
+        let mut end = start.clone();
 
         let mut unexpected_char: Option<char> = None;
 
@@ -70,6 +105,8 @@ where StateKey: Eq + Copy + Hash + fmt::Debug, TokenType: Clone + fmt::Debug {
             let state = self.settings.get_state(current_key);
 
             if let Some(new_key) = attempt_state_transition(current_key, &state.transitions, chr) {
+                if text.is_empty() { start = self.strm.get_next_pos(); }
+                end = self.strm.get_next_pos();
                 text.push(chr);
                 self.strm.advance();
                 log::trace!("Character added to lexeme string: {:?}", text);
@@ -94,15 +131,47 @@ where StateKey: Eq + Copy + Hash + fmt::Debug, TokenType: Clone + fmt::Debug {
 
         if !text.is_empty() {
             log::trace!("Attempting to parse lexeme...");
-            Some(attempt_parse_lexeme_to_token(
-                Lexeme { text, pos: self.strm.get_pos().clone() },
+            let result = attempt_parse_lexeme_to_token(
+                Lexeme { text, span: stream::Span { start, end } },
                 unexpected_char, self.settings.get_state(current_key)
-            ))
+            );
+
+            if result.is_err() { self.resynchronise(current_key); }
+
+            Some(result)
+        }
+        else if let Some(chr) = unexpected_char {
+            // The character cannot begin any token so is skipped, allowing
+            // lexing to continue after the failure is reported:
+            log::trace!("Skipping character {:?} as it cannot begin a token", chr);
+            let pos = self.strm.get_next_pos();
+            self.strm.advance();
+            Some(Err(Failure::UnexpectedChar(chr, Lexeme { text: chr.to_string(), span: stream::Span { start: pos.clone(), end: pos } })))
         }
         else { None } // Reached end of stream.
     }
 }
 
+impl<TokenType, StateKey> GenericTokenStream<'_, TokenType, StateKey>
+where StateKey: Eq + Hash {
+    /// Having failed to produce a token while in the given state, skip the
+    /// remainder of the lexeme should that state be part-way through a
+    /// delimited lexeme (e.g. a string literal). Characters are skipped up to
+    /// and including the closing delimiter, or up to the end of the line.
+    fn resynchronise(&mut self, failed_key: StateKey) {
+        if let Some(closing) = self.settings.resynchronise.get(&failed_key) {
+            while let Some(chr) = self.strm.peek() {
+                if chr == '\n' { break }
+
+                log::trace!("Skipping character {:?} so as to resynchronise", chr);
+                self.strm.advance();
+
+                if chr == *closing { break }
+            }
+        }
+    }
+}
+
 /// Attempt to transition state given a vector of transitions and the current
 /// input character. Will return `Some` holding the next state key should an
 /// appropriate transition be found (whether to the current state or elsewhere).
@@ -129,7 +198,7 @@ where StateKey: Copy + fmt::Debug {
 
 fn attempt_parse_lexeme_to_token<TokenType, StateKey>(lexeme: Lexeme, next_chr: Option<char>, final_state: &State<TokenType, StateKey>) -> Result<GenericToken<TokenType>>
 where TokenType: fmt::Debug + Clone {
-    match final_state.parse.lexeme_string_to_token_type::<StateKey>(&lexeme.text) {
+    match final_state.parse.lexeme_string_to_token_type(&lexeme.text) {
         Some(tok_type) => {
             log::info!("Lexeme {} parsed to token type: {:?}", lexeme, tok_type);
             Ok(GenericToken { tok_type, lexeme })
@@ -156,7 +225,10 @@ struct LexerSettings<'a, TokenType, StateKey> {
     /// The key which indicates the starting/initial state:
     initial_state_key: StateKey,
     /// Indicates any characters that can be ignored when in the initial state:
-    match_ignored: Match<'a>
+    match_ignored: Match<'a>,
+    /// The closing delimiter of each state part-way through a delimited lexeme,
+    /// used to skip the remainder of such a lexeme after a lexical error:
+    resynchronise: HashMap<StateKey, char>
 }
 
 impl<TokenType, StateKey> LexerSettings<'_, TokenType, StateKey>
@@ -193,7 +265,7 @@ enum Parse<'a, TokenType> {
 
 impl<TokenType> Parse<'_, TokenType>
 where TokenType: Clone {
-    fn lexeme_string_to_token_type<StateKey>(&self, lexeme_text: &str) -> Option<TokenType> {
+    fn lexeme_string_to_token_type(&self, lexeme_text: &str) -> Option<TokenType> {
         match self {
             Parse::To(tok) => Some(tok.clone()),
             Parse::ByFunction(func) => Some(func(lexeme_text)),
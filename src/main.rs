@@ -19,99 +19,413 @@ mod lexing;
 mod parsing;
 mod checking;
 mod codegen;
+mod diagnostics;
+mod explanations;
+mod formatter;
+mod cli;
 
 use stream::Stream;
+use cli::{ Subcommand, Input, Emit, Options };
 use std::{
-    io::prelude::*,
-    env, fs, io, fmt,
+    io::{ prelude::*, IsTerminal },
+    env, fs, io, fmt, process,
     path::{ Path, PathBuf }
 };
 
 fn main() {
-    println!("-- Till Compiler {} --", env!("CARGO_PKG_VERSION"));
-
     // Only enable logging if debug build:
     #[cfg(debug_assertions)]
     pretty_env_logger::init_timed();
 
-    let args: Vec<String> = env::args().collect();
+    let command = cli::parse(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("Error: {}\nRun 'till --help' for usage information.", e);
+        process::exit(2);
+    });
+
+    let succeeded = match command {
+        cli::Command::Help => { println!("{}", cli::USAGE); true }
+        cli::Command::Version => { println!("till {}", env!("CARGO_PKG_VERSION")); true }
+        cli::Command::Explain(code) => explain(&code),
+
+        cli::Command::Process(subcommand, options) => {
+            if !options.quiet { eprintln!("-- Till Compiler {} --", env!("CARGO_PKG_VERSION")); }
+
+            // Every input is processed even should an earlier one fail:
+            let results: Vec<bool> = options.inputs.iter().map(|input| process_input(subcommand, input, &options)).collect();
+            results.into_iter().all(|succeeded| succeeded)
+        }
+    };
+
+    if !succeeded { process::exit(1); }
+}
 
-    match args.len() {
-        3 => read_compile_write(&args[1], &args[2]),
-        2 => read_compile_write(&args[1], "out.asm"),
-        _ => interactive()
+/// Display the long-form explanation of the error or warning with the given
+/// code, returning false should no explanation exist for it.
+fn explain(code: &str) -> bool {
+    match explanations::lookup(code) {
+        Some(explanation) => { println!("{}", explanation); true }
+        None => { eprintln!("There is no error or warning with the code '{}'", code); false }
     }
 }
 
-/// Read till code from the file at the specified input path, compile that code,
-/// and then write the resulting machine code to the file at the specified output
-/// path.
-fn read_compile_write(relative_in: &str, relative_out: &str) {
-    let in_path = to_full_path(relative_in);
-    let out_path = to_full_path(relative_out);
+/// Read the given input and then carry out the given subcommand on it. Returns
+/// false should there be any errors.
+fn process_input(subcommand: Subcommand, input: &Input, options: &Options) -> bool {
+    let source = match read_input(input, options) {
+        Some(source) => source,
+        None => return false
+    };
 
-    match fs::File::open(&in_path) {
-        Ok(file) => {
-            println!("Opening input file: {}", in_path.display());
+    match subcommand {
+        Subcommand::Compile => compile(&source, input, options),
+        Subcommand::Check => check(&source, input, options),
+        Subcommand::Run => run(&source, input, options),
+        Subcommand::Fmt => format(&source, input, options)
+    }
+}
 
-            let asm = compile(Stream::from_file(file));
+/// Read till code from a file or from stdin until EOF.
+fn read_input(input: &Input, options: &Options) -> Option<String> {
+    match input {
+        Input::File(relative) => {
+            let in_path = to_full_path(relative);
 
-            match fs::File::create(&out_path) {
-                Ok(mut out_file) => {
-                    match out_file.write_all(asm.as_bytes()) {
-                        Ok(_) => println!("Writing to output file: {}", out_path.display()),
-                        Err(e) => display_file_error(e, out_path.display())
-                    }
+            match fs::read_to_string(&in_path) {
+                Ok(source) => {
+                    if !options.quiet { eprintln!("Opening input file: {}", in_path.display()); }
+                    Some(source)
                 }
-                
-                Err(e) => display_file_error(e, out_path.display())
+                Err(e) => { display_file_error(e, in_path.display()); None }
+            }
+        }
+
+        Input::Stdin => {
+            if !options.quiet && io::stdin().is_terminal() {
+                eprintln!("Please type your code and then press Ctrl-D to finish...");
+            }
+
+            let mut buf = String::new();
+            match io::stdin().lock().read_to_string(&mut buf) {
+                Ok(_) => Some(buf),
+                Err(e) => { display_file_error(e, input); None }
+            }
+        }
+    }
+}
+
+/// Compile the given till code, producing the kind of output specified by the
+/// options. All errors and warnings encountered are displayed.
+fn compile(source: &str, input: &Input, options: &Options) -> bool {
+    let mut diagnostics = Vec::new();
+
+    let output = match options.emit {
+        Emit::Tokens => {
+            let tokens = lex(source, &mut diagnostics);
+            tokens.iter().map(|tok| format!("{}:{} {:?}\n", tok.lexeme.span.start.line_number, tok.lexeme.span.start.line_position, tok.tok_type)).collect()
+        }
+
+        Emit::Ast => {
+            let tokens = lex(source, &mut diagnostics);
+            parse(tokens, &mut diagnostics).iter().map(|stmt| format!("{:#?}\n", stmt)).collect()
+        }
+
+        Emit::Ir | Emit::Asm | Emit::Obj | Emit::Exe => {
+            let (final_ir, checking_diagnostics) = analyse(source, options.deny_warnings);
+            diagnostics = checking_diagnostics;
+
+            match (final_ir, options.emit) {
+                (Some(final_ir), Emit::Ir) => final_ir.iter().map(|instruction| format!("{:?}\n", instruction)).collect(),
+                (Some(final_ir), _) => generate(final_ir, options),
+                (None, _) => String::new()
+            }
+        }
+    };
+
+    let succeeded = !display_diagnostics(diagnostics, &input.to_string(), source, options);
+
+    // Any errors will have prevented output from being produced:
+    succeeded && match options.output.as_deref().map(Path::new).map(Path::to_path_buf).or_else(|| default_output_path(input, options.emit)) {
+        Some(out_path) if options.emit.is_binary() => match TempDir::new() {
+            Ok(temp_dir) => build_native(&output, options.emit, &out_path, &temp_dir, options),
+            Err(e) => { display_file_error(e, env::temp_dir().display()); false }
+        },
+        Some(out_path) if out_path != Path::new("-") => write_output(&output, &out_path, options),
+        _ => write_stdout(&output)
+    }
+}
+
+/// Check the given till code for errors and warnings without producing any
+/// output.
+fn check(source: &str, input: &Input, options: &Options) -> bool {
+    let (_, diagnostics) = analyse(source, options.deny_warnings);
+    !display_diagnostics(diagnostics, &input.to_string(), source, options)
+}
+
+/// Compile the given till code to an executable and then run it, exiting with
+/// the exit status of that executable.
+fn run(source: &str, input: &Input, options: &Options) -> bool {
+    let (final_ir, diagnostics) = analyse(source, options.deny_warnings);
+
+    let final_ir = match final_ir {
+        Some(final_ir) if !display_diagnostics(diagnostics, &input.to_string(), source, options) => final_ir,
+        _ => return false
+    };
+
+    let temp_dir = match TempDir::new() {
+        Ok(temp_dir) => temp_dir,
+        Err(e) => { display_file_error(e, env::temp_dir().display()); return false }
+    };
+
+    let exe_path = temp_dir.0.join("program");
+    if !build_native(&generate(final_ir, options), Emit::Exe, &exe_path, &temp_dir, options) { return false }
+
+    if !options.quiet { eprintln!("Running: {}", input); }
+
+    let status = process::Command::new(&exe_path).status();
+    drop(temp_dir); // Exiting would prevent the directory from being removed.
+
+    match status {
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(e) => { eprintln!("Failed to run the compiled program: {}", e); false }
+    }
+}
+
+/// Format the given till code. A file is formatted in place unless an output
+/// path is given, while code from stdin is written to stdout. Code is only
+/// formatted should it contain no lexical or syntax errors.
+fn format(source: &str, input: &Input, options: &Options) -> bool {
+    let mut diagnostics = Vec::new();
+    parse(lex(source, &mut diagnostics), &mut diagnostics);
+
+    if display_diagnostics(diagnostics, &input.to_string(), source, options) { return false }
+
+    let config = lexing::lexer::Config { keep_comments: true, ..lexing::lexer::Config::default() };
+    let tokens: Vec<lexing::lexer::Token> = lexing::lexer::input_with_config(Stream::from_str(source), config).filter_map(Result::ok).collect();
+    let formatted = formatter::format(&tokens);
+
+    let out_path = match (&options.output, input) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Input::File(path)) => PathBuf::from(path),
+        (None, Input::Stdin) => PathBuf::from("-")
+    };
+
+    if out_path == Path::new("-") { write_stdout(&formatted) }
+    else if options.output.is_none() && formatted == source {
+        if !options.quiet { eprintln!("Already formatted: {}", input); }
+        true
+    }
+    else { write_output(&formatted, &out_path, options) }
+}
+
+/// Generate assembly code for the target given in the options.
+fn generate(final_ir: Vec<checking::Instruction>, options: &Options) -> String {
+    match options.target {
+        cli::Target::X86_64Linux => codegen::genelf64::input(final_ir)
+    }
+}
+
+/// Directory for intermediate files that is removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create a new directory with an unpredictable name in the system's
+    /// temporary directory, accessible only by the current user. An existing
+    /// directory is never reused as another user could have created it (and
+    /// so could replace the files placed in it).
+    fn new() -> io::Result<Self> {
+        use std::{ os::unix::fs::DirBuilderExt, hash::{ BuildHasher, Hasher }, collections::hash_map::RandomState };
+
+        const ATTEMPTS: usize = 16;
+
+        for _ in 0..ATTEMPTS {
+            // Each `RandomState` is randomly seeded:
+            let suffix = RandomState::new().build_hasher().finish();
+            let path = env::temp_dir().join(format!("till-{}-{:016x}", process::id(), suffix));
+
+            match fs::DirBuilder::new().mode(0o700).create(&path) {
+                Ok(()) => return Ok(TempDir(path)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e)
             }
         }
-        Err(e) => display_file_error(e, in_path.display())
+
+        Err(io::Error::new(io::ErrorKind::AlreadyExists, "could not create a uniquely named temporary directory"))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Assemble the given assembly code into an object file using NASM, and then
+/// (should an executable be wanted) link that object file with the C standard
+/// library using the system C compiler. The optimisation level is passed on to
+/// the assembler.
+fn build_native(asm: &str, emit: Emit, out_path: &Path, temp_dir: &TempDir, options: &Options) -> bool {
+    let asm_path = temp_dir.0.join("program.asm");
+    let obj_path = if emit == Emit::Obj { out_path.to_path_buf() } else { temp_dir.0.join("program.o") };
+
+    if let Err(e) = fs::write(&asm_path, asm) {
+        display_file_error(e, asm_path.display());
+        return false;
+    }
+
+    let nasm_optimisation = match options.optimisation_level {
+        0 => "-O0",
+        1 => "-O1",
+        _ => "-Ox"
+    };
+
+    let succeeded = run_tool("nasm", &["-f".as_ref(), "elf64".as_ref(), nasm_optimisation.as_ref(), "-o".as_ref(), obj_path.as_os_str(), asm_path.as_os_str()]) &&
+                    (emit == Emit::Obj || run_tool("cc", &[obj_path.as_os_str(), "-o".as_ref(), out_path.as_os_str()]));
+
+    if succeeded && !options.quiet { eprintln!("Writing to output file: {}", to_full_path(&out_path.to_string_lossy()).display()); }
+
+    succeeded
+}
+
+/// Run an external program with the given arguments, returning whether it
+/// succeeded.
+fn run_tool(program: &str, args: &[&std::ffi::OsStr]) -> bool {
+    match process::Command::new(program).args(args).status() {
+        Ok(status) if status.success() => true,
+        Ok(status) => { eprintln!("Program '{}' failed ({})", program, status); false }
+        Err(e) => { eprintln!("Could not run program '{}' (is it installed?): {}", program, e); false }
     }
 }
 
-/// Read input from stdin until EOF encountered and then compile that input as
-/// till code.
-fn interactive() {
-    println!("Please type your code and then press Ctrl-D to compile...");
+/// Get the path output is written to when no output path is given: a file in
+/// the current directory named after the input file (or `out` for stdin) with
+/// the extension appropriate to the kind of output. Output lacking an extension
+/// is written to stdout.
+fn default_output_path(input: &Input, emit: Emit) -> Option<PathBuf> {
+    let extension = emit.extension()?;
 
-    let mut buf = String::new();
+    let stem = match input {
+        Input::File(path) => Path::new(path).file_stem().map_or("out".into(), |stem| stem.to_string_lossy()),
+        Input::Stdin => "out".into()
+    };
 
-    match io::stdin().lock().read_to_string(&mut buf) {
+    Some(Path::new(stem.as_ref()).with_extension(extension))
+}
+
+/// Write the given output to the file at the given path.
+fn write_output(output: &str, relative_out: &Path, options: &Options) -> bool {
+    let out_path = to_full_path(&relative_out.to_string_lossy());
+
+    match fs::write(&out_path, output) {
         Ok(_) => {
-            let asm = compile(Stream::from_str(&buf));
-            println!("\n{}", asm);
+            if !options.quiet { eprintln!("Writing to output file: {}", out_path.display()); }
+            true
         }
-        Err(e) => display_file_error(e, "<stdin>")
+        Err(e) => { display_file_error(e, out_path.display()); false }
+    }
+}
+
+/// Write the given output to stdout. Output cut short by stdout being closed
+/// (e.g. when piped into `head`) is not considered an error.
+fn write_stdout(output: &str) -> bool {
+    match io::stdout().lock().write_all(output.as_bytes()) {
+        Ok(_) => true,
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => true,
+        Err(e) => { eprintln!("Failed to write to stdout: {}", e); false }
     }
 }
 
-/// Perform lexical, syntactic, and semantic analysis on the till code from a
-/// given input stream and then generate elf64 Intel-syntax assembly code.
-fn compile(strm: Stream) -> String {
-    let tokens = lexing::lexer::input(strm).filter_map(|x| display_any_failures(x, "lexical"));
-    let syntax_tree = parsing::parser::input(tokens).filter_map(|x| display_any_failures(x, "syntax"));
-    let final_ir = display_any_failures(checking::checker::input(syntax_tree), "semantic").unwrap();
-    codegen::genelf64::input(final_ir)
+/// Split the given till code into tokens, adding any lexical errors to the
+/// given diagnostics.
+fn lex(source: &str, diagnostics: &mut Vec<diagnostics::Diagnostic>) -> Vec<lexing::lexer::Token> {
+    lexing::lexer::input(Stream::from_str(source)).filter_map(|result| {
+        result.map_err(|e| diagnostics.push(diagnostics::Diagnostic::from_lexing_failure(&e))).ok()
+    }).collect()
+}
+
+/// Parse the given tokens into statements, adding any syntax errors to the
+/// given diagnostics. Syntax errors resulting from lexical errors already in the
+/// given diagnostics are left out.
+fn parse(tokens: Vec<lexing::lexer::Token>, diagnostics: &mut Vec<diagnostics::Diagnostic>) -> Vec<parsing::Statement> {
+    // Every diagnostic at this point is a lexical error:
+    let lexical_failures = diagnostics.iter().filter_map(|diagnostic| diagnostic.span().cloned()).collect();
+
+    parsing::parser::input(tokens.into_iter(), lexical_failures).filter_map(|result| {
+        result.map_err(|e| diagnostics.push(diagnostics::Diagnostic::from_parsing_failure(&e))).ok()
+    }).collect()
+}
+
+/// Perform lexical, syntactic, and semantic analysis on the given till code,
+/// yielding every error and warning encountered along with the final IR should
+/// there be no errors (warnings being considered errors should `deny_warnings`
+/// be set). Semantic analysis is only performed should there be no lexical or
+/// syntax errors.
+fn analyse(source: &str, deny_warnings: bool) -> (Option<Vec<checking::Instruction>>, Vec<diagnostics::Diagnostic>) {
+    let mut diagnostics = Vec::new();
+
+    let tokens = lex(source, &mut diagnostics);
+    let syntax_tree = parse(tokens, &mut diagnostics);
+
+    let mut checked_ir = None;
+
+    if diagnostics.is_empty() {
+        match checking::checker::input(syntax_tree) {
+            Ok((final_ir, warnings)) => {
+                let severity = if deny_warnings { diagnostics::Severity::Error } else { diagnostics::Severity::Warning };
+
+                if !deny_warnings || warnings.is_empty() { checked_ir = Some(final_ir); }
+
+                diagnostics.extend(warnings.iter().map(|warning| diagnostics::Diagnostic::from_warning(warning, severity)));
+            }
+
+            Err(failures) => diagnostics.extend(failures.iter().map(diagnostics::Diagnostic::from_checking_failure))
+        }
+    }
+
+    (checked_ir, diagnostics)
 }
 
-/// Helper function that displays any errors and exits should one be encountered.
-fn display_any_failures<T, E: fmt::Display>(value: Result<T, E>, compilation_stage: &str) -> Option<T> {
-    if let Err(e) = &value {
-        println!("{} ERROR: {}", compilation_stage.to_ascii_uppercase(), e);
-        std::process::exit(0);
+/// Display the given errors and warnings on stderr in the order of the spans
+/// they concern (with any not concerning a particular span displayed last), up
+/// to the maximum number given in the options. Output is coloured should stderr
+/// be a terminal. Should JSON diagnostics be enabled, every error and warning
+/// is instead written as a JSON object on a line of its own. Returns whether
+/// there were any errors.
+fn display_diagnostics(mut diagnostics: Vec<diagnostics::Diagnostic>, file_name: &str, source: &str, options: &Options) -> bool {
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.span().is_none(), diagnostic.span().cloned()));
+
+    let any_errors = diagnostics.iter().any(|diagnostic| diagnostic.severity == diagnostics::Severity::Error);
+
+    if options.json_diagnostics {
+        for diagnostic in &diagnostics { eprintln!("{}", diagnostic.to_json(file_name)); }
+        return any_errors;
+    }
+
+    let max_diagnostics = options.max_diagnostics;
+
+    let colour = io::stderr().is_terminal();
+
+    for diagnostic in diagnostics.iter().take(max_diagnostics) {
+        eprintln!("{}", diagnostic.render(file_name, source, colour));
+    }
+
+    if diagnostics.len() > max_diagnostics {
+        eprintln!("{} further error(s) and warning(s) not displayed", diagnostics.len() - max_diagnostics);
     }
-    value.ok()
+
+    if let Some(diagnostic) = diagnostics.first() {
+        eprintln!("For a detailed explanation of an error or warning, run 'till explain CODE' (e.g. 'till explain {}')", diagnostic.code);
+    }
+
+    any_errors
 }
 
 /// Display a given file input/output error.
 fn display_file_error<T: fmt::Display>(e: std::io::Error, path: T) {
     match e.kind() {
-        io::ErrorKind::NotFound => println!("File not found at: {}", path),
-        io::ErrorKind::PermissionDenied => println!("Lack required permissions to access file at: {}", path),
+        io::ErrorKind::NotFound => eprintln!("File not found at: {}", path),
+        io::ErrorKind::PermissionDenied => eprintln!("Lack required permissions to access file at: {}", path),
         kind => {
-            println!("Error occured when attempting to access file at: {}", path);
+            eprintln!("Error occured when attempting to access file at: {}", path);
             log::error!("File error kind: {:?}", kind);
         }
     }
@@ -11,10 +11,13 @@ use std::fmt;
 /// token, and the encountering of the end of the token stream when it is not
 /// expected.
 #[derive(Debug, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum Failure {
     UnexpectedToken(lexer::Token, &'static str),
     UnexpectedStreamEnd(&'static str),
-    UnexpectedIndent { expected_indent: usize, encountered_indent: usize, pos: stream::Position }
+    UnexpectedIndent { expected_indent: usize, encountered_indent: usize, span: stream::Span },
+    MissingInitialiser { identifier: String, span: stream::Span },
+    IntegerOutOfRange(lexer::Token)
 }
 
 impl fmt::Display for Failure {
@@ -22,8 +25,37 @@ impl fmt::Display for Failure {
         match self {
             Failure::UnexpectedToken(tok, expected) => write!(f, "Expected {} yet encountered unexpected {}", expected, tok),
             Failure::UnexpectedStreamEnd(expected) => write!(f, "Encountered the end of the token stream yet expected {}", expected),
-            Failure::UnexpectedIndent { expected_indent, encountered_indent, pos } =>
-                write!(f, "Encountered an unexpected change in indentation from the expected level of {} to an indentation level of {} tabs at {}", expected_indent, encountered_indent, pos)
+            Failure::UnexpectedIndent { expected_indent, encountered_indent, span } =>
+                write!(f, "Encountered an unexpected change in indentation from the expected level of {} to an indentation level of {} at {}", expected_indent, encountered_indent, span),
+            Failure::MissingInitialiser { identifier, span } =>
+                write!(f, "Variable '{}' declared using 'let' at {} must be given an initial value from which to infer its type", identifier, span),
+            Failure::IntegerOutOfRange(tok) =>
+                write!(f, "Encountered integer literal {} which is out of the range of an Int (from {} to {})", tok.lexeme, i64::MIN, i64::MAX)
+        }
+    }
+}
+
+impl Failure {
+    /// Get the code identifying this kind of failure. These codes do not change
+    /// between versions so may be relied upon by tools consuming them.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Failure::UnexpectedToken(..) => "P0001",
+            Failure::UnexpectedStreamEnd(_) => "P0002",
+            Failure::UnexpectedIndent { .. } => "P0003",
+            Failure::MissingInitialiser { .. } => "P0004",
+            Failure::IntegerOutOfRange(_) => "P0005"
+        }
+    }
+
+    /// Get the span of the input stream at which this failure occurred, if
+    /// there is one (there is not should the end of the token stream be
+    /// reached).
+    pub fn span(&self) -> Option<&stream::Span> {
+        match self {
+            Failure::UnexpectedToken(tok, _) | Failure::IntegerOutOfRange(tok) => Some(&tok.lexeme.span),
+            Failure::UnexpectedStreamEnd(_) => None,
+            Failure::UnexpectedIndent { span, .. } | Failure::MissingInitialiser { span, .. } => Some(span)
         }
     }
 }
@@ -31,41 +63,115 @@ impl fmt::Display for Failure {
 type Result<T> = std::result::Result<T, Failure>;
 
 /// Represents a parsed till statement. An AST is comprised of a collection of
-/// `Statement` instances.
+/// `Statement` instances. Every statement holds the span of the input stream it
+/// was parsed from (including any blocks it contains).
 #[derive(Debug, PartialEq)]
 pub enum Statement {
     If {
+        span: stream::Span,
         condition: Expression,
-        block: Block
+        block: Block,
+        else_block: Option<Block>
     },
 
     While {
+        span: stream::Span,
         condition: Expression,
         block: Block
     },
 
+    Match {
+        span: stream::Span,
+        expr: Expression,
+        arms: Vec<MatchArm>
+    },
+
     FunctionDefinition {
-        pos: stream::Position,
+        span: stream::Span,
         identifier: String,
         parameters: Vec<Parameter>,
         return_type: Option<String>,
         body: Block
     },
 
+    FunctionCall {
+        span: stream::Span,
+        identifier: String,
+        args: Vec<Expression>
+    },
+
     VariableDeclaration {
+        span: stream::Span,
         var_type: String,
         identifier: String,
         value: Option<Expression>
     },
 
+    /// Declaration of a variable whose type is that of its initial value.
+    InferredDeclaration {
+        span: stream::Span,
+        identifier: String,
+        value: Expression
+    },
+
+    RecordDefinition {
+        span: stream::Span,
+        identifier: String,
+        fields: Vec<Field>
+    },
+
+    EnumDefinition {
+        span: stream::Span,
+        identifier: String,
+        variants: Vec<Variant>
+    },
+
+    ArrayDeclaration {
+        span: stream::Span,
+        element_type: String,
+        length: usize,
+        identifier: String
+    },
+
     VariableAssignment {
+        span: stream::Span,
         identifier: String,
         assign_to: Expression
     },
 
-    Return(Option<Expression>),
+    ElementAssignment {
+        span: stream::Span,
+        identifier: String,
+        index: Expression,
+        assign_to: Expression
+    },
 
-    Display(Expression)
+    FieldAssignment {
+        span: stream::Span,
+        identifier: String,
+        fields: Vec<String>,
+        assign_to: Expression
+    },
+
+    Return(stream::Span, Option<Expression>),
+
+    Display(stream::Span, Expression)
+}
+
+impl Statement {
+    /// Get the span of the input stream this statement was parsed from.
+    pub fn span(&self) -> &stream::Span {
+        match self {
+            Statement::If { span, .. } | Statement::While { span, .. } |
+            Statement::Match { span, .. } | Statement::VariableAssignment { span, .. } |
+            Statement::FunctionDefinition { span, .. } | Statement::FunctionCall { span, .. } |
+            Statement::ArrayDeclaration { span, .. } | Statement::ElementAssignment { span, .. } |
+            Statement::RecordDefinition { span, .. } | Statement::FieldAssignment { span, .. } |
+            Statement::EnumDefinition { span, .. } | Statement::VariableDeclaration { span, .. } |
+            Statement::InferredDeclaration { span, .. } |
+            Statement::Return(span, _) | Statement::Display(span, _) => span
+        }
+    }
 }
 
 pub type Block = Vec<Statement>;
@@ -75,26 +181,69 @@ pub type Block = Vec<Statement>;
 pub struct Parameter {
     pub param_type: String,
     pub identifier: String,
-    pub pos: stream::Position
+    pub span: stream::Span
+}
+
+/// Field of a record type definition.
+#[derive(Debug, PartialEq)]
+pub struct Field {
+    pub field_type: String,
+    pub identifier: String,
+    pub span: stream::Span
+}
+
+/// Variant of an enum type definition, with any number of fields.
+#[derive(Debug, PartialEq)]
+pub struct Variant {
+    pub identifier: String,
+    pub fields: Vec<Field>,
+    pub span: stream::Span
 }
 
-/// Represents a till expression.
+/// Arm of a match statement, naming the variant it handles and introducing a
+/// variable for each of that variant's fields.
 #[derive(Debug, PartialEq)]
+pub struct MatchArm {
+    pub variant: String,
+    pub bindings: Vec<Parameter>,
+    pub block: Block,
+    pub span: stream::Span
+}
+
+/// Represents a till expression. As with statements, every expression holds
+/// the span of the input stream it was parsed from.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Expression {
-    Equal(Box<Expression>, Box<Expression>),
-    GreaterThan(Box<Expression>, Box<Expression>),
-    LessThan(Box<Expression>, Box<Expression>),
-    Add(Box<Expression>, Box<Expression>),
-    Subtract(Box<Expression>, Box<Expression>),
-    Multiply(Box<Expression>, Box<Expression>),
-    Divide(Box<Expression>, Box<Expression>),
-
-    BooleanNot(Box<Expression>),
-    UnaryMinus(Box<Expression>),
-
-    NumberLiteral { pos: stream::Position, value: f64 },
-    CharLiteral { pos: stream::Position, value: char },
-    BooleanLiteral { pos: stream::Position, value: bool },
-    Variable { pos: stream::Position, identifier: String },
-    FunctionCall { pos: stream::Position, identifier: String, args: Vec<Expression> }
-}
\ No newline at end of file
+    Or(stream::Span, Box<Expression>, Box<Expression>),
+    And(stream::Span, Box<Expression>, Box<Expression>),
+    Equal(stream::Span, Box<Expression>, Box<Expression>),
+    NotEqual(stream::Span, Box<Expression>, Box<Expression>),
+    GreaterThan(stream::Span, Box<Expression>, Box<Expression>),
+    LessThan(stream::Span, Box<Expression>, Box<Expression>),
+    GreaterThanOrEqual(stream::Span, Box<Expression>, Box<Expression>),
+    LessThanOrEqual(stream::Span, Box<Expression>, Box<Expression>),
+    Add(stream::Span, Box<Expression>, Box<Expression>),
+    Subtract(stream::Span, Box<Expression>, Box<Expression>),
+    Multiply(stream::Span, Box<Expression>, Box<Expression>),
+    Divide(stream::Span, Box<Expression>, Box<Expression>),
+    Modulo(stream::Span, Box<Expression>, Box<Expression>),
+    Power(stream::Span, Box<Expression>, Box<Expression>),
+
+    BooleanNot(stream::Span, Box<Expression>),
+    UnaryMinus(stream::Span, Box<Expression>),
+
+    IntegerLiteral { span: stream::Span, value: i64 },
+    NumberLiteral { span: stream::Span, value: f64 },
+    CharLiteral { span: stream::Span, value: char },
+    StringLiteral { span: stream::Span, value: String },
+    BooleanLiteral { span: stream::Span, value: bool },
+    Variable { span: stream::Span, identifier: String },
+    FunctionCall { span: stream::Span, identifier: String, args: Vec<Expression> },
+    Index { span: stream::Span, identifier: String, index: Box<Expression> },
+    /// Access of a field of a record.
+    FieldAccess { span: stream::Span, record: Box<Expression>, field: String },
+    /// Creation of a value of the named type from the given arguments. This is
+    /// either the conversion of a single value to a primitive type or the
+    /// construction of a record from a value for each of its fields.
+    Construction { span: stream::Span, type_identifier: String, args: Vec<Expression> }
+}
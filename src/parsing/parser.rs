@@ -2,16 +2,35 @@
 //! instances.
 
 use crate::{ stream, lexing::lexer };
-use std::iter;
+use std::{ collections::VecDeque, convert::TryFrom };
 
 /// Returns an iterator that yields abstract syntax representations for each
-/// TILL statement parsed from the given token stream.
-pub fn input<T: Iterator<Item=lexer::Token>>(tokens: T) -> StatementStream<T> {
-    StatementStream { tokens: tokens.peekable() }
+/// TILL statement parsed from the given token stream. The stream is missing any
+/// tokens that failed to be lexed at the given spans - a failure to parse a
+/// statement containing such a lexical failure is not yielded as it is most
+/// likely a consequence of the missing token.
+pub fn input<T: Iterator<Item=lexer::Token>>(tokens: T, lexical_failures: Vec<stream::Span>) -> StatementStream<T> {
+    StatementStream {
+        tokens, lookahead: VecDeque::new(), failures: VecDeque::new(), previous_end: stream::Position::new(),
+        lexical_failures
+    }
 }
 
+/// Function that constructs a binary expression from its span and its two
+/// sub-expressions.
+type MakeExprFunc = fn(stream::Span, Box<super::Expression>, Box<super::Expression>) -> super::Expression;
+
 pub struct StatementStream<T: Iterator<Item=lexer::Token>> {
-    tokens: iter::Peekable<T>
+    tokens: T,
+    /// Tokens that have been peeked but not yet consumed:
+    lookahead: VecDeque<lexer::Token>,
+    /// Failures to parse statements within blocks that were recovered from
+    /// and are yet to be yielded:
+    failures: VecDeque<super::Failure>,
+    /// Position of the final character of the token most recently consumed:
+    previous_end: stream::Position,
+    /// Spans at which tokens failed to be lexed:
+    lexical_failures: Vec<stream::Span>
 }
 
 impl<T: Iterator<Item=lexer::Token>> Iterator for StatementStream<T> {
@@ -19,20 +38,33 @@ impl<T: Iterator<Item=lexer::Token>> Iterator for StatementStream<T> {
 
     /// Return the next AST statement parsed from the given token stream.
     /// Returns `None` in the case of the token stream having reached its end.
+    /// Should a statement fail to parse, parsing resumes from the next line
+    /// at the same indentation level as that statement.
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(failure) = self.failures.pop_front() {
+            return Some(Err(failure));
+        }
+
         log::info!("Attempting to parse next statement from token stream...");
 
         if self.more_tokens_in_stream() {
+            let after = self.previous_end.clone();
             let stmt = self.statement(0, "top-level statement");
             
             match &stmt {
                 Ok(valid_stmt) => log::info!("Parsed next statement from token stream:\n{:#?}", valid_stmt),
-                Err(e) => log::info!("Failed to parse next statement from token stream due to error: {}", e)
+                Err(e) => {
+                    log::info!("Failed to parse next statement from token stream due to error: {}", e);
+                    self.skip_to_line_at_indent(e, 0);
+                }
             }
 
             let _ = self.consume_token_if_type(&lexer::TokenType::Newline(0), "top-level statement");
 
-            Some(stmt)
+            match stmt {
+                Err(e) if self.follows_lexical_failure(&e, &after) => self.next(),
+                _ => Some(stmt)
+            }
         }
         else {
             log::info!("Token stream is already empty so returning None");
@@ -43,14 +75,63 @@ impl<T: Iterator<Item=lexer::Token>> Iterator for StatementStream<T> {
 
 impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
     fn more_tokens_in_stream(&mut self) -> bool {
-        self.tokens.peek().is_some()
+        self.peek_nth_token(0).is_some()
+    }
+
+    /// Having failed to parse a statement at the given indentation level, skip
+    /// tokens up until the newline preceding the next line at that indentation
+    /// level or lower (or the end of the token stream) so that parsing may
+    /// resume from there.
+    fn skip_to_line_at_indent(&mut self, failure: &super::Failure, indent: usize) {
+        // Should the failure be the result of encountering such a newline then
+        // it is returned to the stream:
+        if let super::Failure::UnexpectedToken(lexer::Token { tok_type: lexer::TokenType::Newline(x), lexeme }, _) = failure {
+            if *x <= indent {
+                self.lookahead.push_front(lexer::Token { tok_type: lexer::TokenType::Newline(*x), lexeme: lexeme.clone() });
+                return;
+            }
+        }
+
+        while let Some(tok) = self.peek_nth_token(0) {
+            if let lexer::TokenType::Newline(x) = tok.tok_type {
+                if x <= indent { break }
+            }
+            log::trace!("Skipping token: {:?}", tok);
+            self.lookahead.pop_front();
+        }
+    }
+
+    /// Did a token fail to be lexed between the given position (the end of the
+    /// token preceding the statement that failed to parse) and the point of the
+    /// given failure?
+    fn follows_lexical_failure(&self, failure: &super::Failure, after: &stream::Position) -> bool {
+        self.lexical_failures.iter().any(|span| {
+            span.start > *after && failure.span().is_none_or(|failure_span| span.start <= failure_span.start)
+        })
+    }
+
+    /// Record a failure to parse a statement in a block so that it may be
+    /// yielded once the current top-level statement has been parsed, unless
+    /// that failure follows a lexical failure in the same statement.
+    fn push_failure(&mut self, failure: super::Failure, after: &stream::Position) {
+        if !self.follows_lexical_failure(&failure, after) { self.failures.push_back(failure); }
+    }
+
+    /// See the token a given number of tokens ahead of the next token without
+    /// advancing the position in the token stream.
+    fn peek_nth_token(&mut self, n: usize) -> Option<&lexer::Token> {
+        while self.lookahead.len() <= n {
+            let tok = self.tokens.next()?;
+            self.lookahead.push_back(tok);
+        }
+        self.lookahead.get(n)
     }
 
     /// Will see what token is next without advancing the position in the token
     /// stream. Will error if the end of the token stream is reached.
     fn peek_token(&mut self, failure_msg: &'static str) -> super::Result<&lexer::Token> {
         // Could use Result::ok_or but want to log tokens as accessed by the parser.
-        match self.tokens.peek() {
+        match self.peek_nth_token(0) {
             Some(tok) => {
                 log::trace!("Peeked token: {:?}", tok);
                 Ok(tok)
@@ -62,15 +143,32 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
     /// Take the next token and advance the position in the token stream. Will
     /// error if the end of the token stream is reached.
     fn consume_token(&mut self, failure_msg: &'static str) -> super::Result<lexer::Token> {
-        match self.tokens.next() {
+        match self.lookahead.pop_front().or_else(|| self.tokens.next()) {
             Some(tok) => {
                 log::trace!("Consumed token: {:?}", tok);
+                self.previous_end = tok.lexeme.span.end.clone();
                 Ok(tok)
             }
             None => Err(super::Failure::UnexpectedStreamEnd(failure_msg))
         }
     }
 
+    /// Get the position of the first character of the next token, or the end
+    /// of the token most recently consumed should the token stream be at its
+    /// end.
+    fn next_token_start(&mut self) -> stream::Position {
+        match self.peek_nth_token(0) {
+            Some(tok) => tok.lexeme.span.start.clone(),
+            None => self.previous_end.clone()
+        }
+    }
+
+    /// Get the span from the given position up to and including the final
+    /// character of the token most recently consumed.
+    fn span_from(&self, start: stream::Position) -> stream::Span {
+        stream::Span { start, end: self.previous_end.clone() }
+    }
+
     /// Will consume a token, returning said token if it is of the specified
     /// token type. If it is not of that type or if the end of the token stream
     /// is reached, an error will be returned.
@@ -103,7 +201,7 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
 
     /// Parse a TILL statement.
     ///
-    /// `<stmt> ::= <if> | <while> | <function> | <declaration> | <assignment> | <return> | <display>`
+    /// `<stmt> ::= <if> | <while> | <match> | <function> | <record> | <enum> | <call> | <declaration> | <inferred_declaration> | <array_declaration> | <assignment> | <return> | <display>`
     fn statement(&mut self, current_indent: usize, stmt_type_name: &'static str) -> super::Result<super::Statement> {
         log::trace!("Parsing statement...");
 
@@ -115,25 +213,50 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
             // While loop statement:
             lexer::TokenType::WhileKeyword => self.while_stmt(current_indent),
 
-            // Function definition or variable assignment:
+            // Function definition, function call, or variable assignment:
             lexer::TokenType::Identifier(x) => {
                 let identifier = x.to_string();
 
-                // Consume identifier token and take its stream position:
-                let pos = self.consume_token("").unwrap().lexeme.pos;
+                // Consume identifier token and take the stream position at which
+                // it starts:
+                let start = self.consume_token("").unwrap().lexeme.span.start;
 
                 if self.check_type_of_peeked_token(&lexer::TokenType::BracketOpen, "statement")? {
-                    self.define_function_stmt(current_indent, identifier, pos)
+                    if self.function_definition_follows(current_indent) {
+                        self.define_function_stmt(current_indent, identifier, start)
+                    }
+                    else { self.function_call_stmt(identifier, start) }
                 }
                 else if self.check_type_of_peeked_token(&lexer::TokenType::Equals, "statement")? {
-                    self.assignment_stmt(identifier)
+                    self.assignment_stmt(identifier, start)
+                }
+                else if self.check_type_of_peeked_token(&lexer::TokenType::SquareBracketOpen, "statement")? {
+                    self.element_assignment_stmt(identifier, start)
+                }
+                else if self.check_type_of_peeked_token(&lexer::TokenType::Dot, "statement")? {
+                    self.field_assignment_stmt(identifier, start)
                 }
                 else { Err(super::Failure::UnexpectedToken(self.consume_token("statement")?, "statement")) }
             }
 
+            // Match statement:
+            lexer::TokenType::MatchKeyword => self.match_stmt(current_indent),
+
+            // Record type definition:
+            lexer::TokenType::TypeKeyword => self.record_definition_stmt(current_indent),
+
+            // Enum type definition:
+            lexer::TokenType::EnumKeyword => self.enum_definition_stmt(current_indent),
+
             // Variable declaration:
             lexer::TokenType::TypeIdentifier(_) => self.variable_declaration_stmt(),
 
+            // Variable declaration with inferred type:
+            lexer::TokenType::LetKeyword => self.inferred_declaration_stmt(),
+
+            // Array declaration:
+            lexer::TokenType::SquareBracketOpen => self.array_declaration_stmt(),
+
             // Return:
             lexer::TokenType::ReturnKeyword => self.return_stmt(),
 
@@ -144,36 +267,74 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
         }
     }
 
-    /// Parse an if statement.
+    /// Parse an if statement. An elif clause is treated as an if statement
+    /// contained within the else block of the preceding if or elif.
     ///
-    /// `<if> ::= "if" <expr> <block>`
+    /// `<if> ::= "if" <expr> <block> <else>?`
     fn if_stmt(&mut self, current_indent: usize) -> super::Result<super::Statement> {
         // Consume the if keyword token:
-        self.consume_token_of_expected_type(&lexer::TokenType::IfKeyword, "if keyword")?;
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::IfKeyword, "if keyword")?.lexeme.span.start;
 
-        Ok(super::Statement::If {
-            condition: self.expression()?,
-            block: self.block(current_indent)?
-        })
+        self.if_stmt_body(current_indent, start)
+    }
+
+    /// Parse the condition, block, and any else or elif clauses of an if
+    /// statement. The if or elif keyword token (which starts at the given
+    /// position) is assumed to have already been consumed.
+    ///
+    /// `<else> ::= newlines ("elif" <expr> <block> <else>? | "else" <block>)`
+    fn if_stmt_body(&mut self, current_indent: usize, start: stream::Position) -> super::Result<super::Statement> {
+        let condition = self.expression()?;
+        let block = self.block(current_indent)?;
+
+        // An else or elif clause must be at the same indentation level as the
+        // if statement:
+        let clause = {
+            if self.check_type_of_peeked_token(&lexer::TokenType::Newline(current_indent), "").unwrap_or(false) {
+                self.peek_nth_token(1).map(|tok| tok.tok_type.clone())
+            }
+            else { None }
+        };
+
+        let else_block = match clause {
+            Some(lexer::TokenType::ElifKeyword) => {
+                self.consume_token("").unwrap(); // Newline.
+                let elif_start = self.consume_token("").unwrap().lexeme.span.start; // Elif keyword.
+
+                Some(vec![self.if_stmt_body(current_indent, elif_start)?])
+            }
+
+            Some(lexer::TokenType::ElseKeyword) => {
+                self.consume_token("").unwrap(); // Newline.
+                self.consume_token("").unwrap(); // Else keyword.
+
+                Some(self.block(current_indent)?)
+            }
+
+            _ => None
+        };
+
+        Ok(super::Statement::If { span: self.span_from(start), condition, block, else_block })
     }
 
     /// Parse a while loop statement.
     ///
     /// `<while> ::= "while" <expr> <block>`
     fn while_stmt(&mut self, current_indent: usize) -> super::Result<super::Statement> {
-        self.consume_token_of_expected_type(&lexer::TokenType::WhileKeyword, "while keyword")?;
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::WhileKeyword, "while keyword")?.lexeme.span.start;
 
-        Ok(super::Statement::While {
-            condition: self.expression()?,
-            block: self.block(current_indent)?
-        })
+        let condition = self.expression()?;
+        let block = self.block(current_indent)?;
+
+        Ok(super::Statement::While { span: self.span_from(start), condition, block })
     }
 
-    /// Parse a function definition statement. The function name identifier is
-    /// assumed to have already have been consumed.
+    /// Parse a function definition statement. The function name identifier
+    /// (which starts at the given position) is assumed to have already have
+    /// been consumed.
     ///
     /// `<function> ::= identifier "(" (<param> ("," <param>)*)? ")" ("->" <type>)? <block>`
-    fn define_function_stmt(&mut self, current_indent: usize, identifier: String, pos: stream::Position) -> super::Result<super::Statement> {
+    fn define_function_stmt(&mut self, current_indent: usize, identifier: String, start: stream::Position) -> super::Result<super::Statement> {
         self.consume_token_of_expected_type(&lexer::TokenType::BracketOpen, "open bracket ( token")?;
 
         let mut parameters = Vec::new();
@@ -191,24 +352,106 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
         self.consume_token_of_expected_type(&lexer::TokenType::BracketClose, "close bracket ) token")?;
 
         let return_type = if self.consume_token_if_type(&lexer::TokenType::Arrow, "function definition")?.is_some() {
-            Some(self.consume_type_identifier("function return type")?)
+            Some(self.consume_type_identifier("function return type")?.0)
         }
         else { None };
 
+        let body = self.block(current_indent)?;
+
         Ok(super::Statement::FunctionDefinition {
-            pos, identifier, parameters, return_type,
-            body: self.block(current_indent)?
+            span: self.span_from(start), identifier, parameters, return_type, body
         })
     }
 
 
+    /// Parse the definition of a record type, made up of one or more fields
+    /// each on their own line.
+    ///
+    /// `<record> ::= "type" typeidentifier newlines indentincr (<field> newlines)* <field> indentdecr`
+    fn record_definition_stmt(&mut self, current_indent: usize) -> super::Result<super::Statement> {
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::TypeKeyword, "type keyword")?.lexeme.span.start;
+
+        let identifier = self.consume_type_identifier("record type identifier")?.0;
+        let fields = self.indented_lines(current_indent + 1, "increase indent for start of record fields", Self::parse_field)?;
+
+        Ok(super::Statement::RecordDefinition { span: self.span_from(start), identifier, fields })
+    }
+
+    /// Parse the definition of an enum type, made up of one or more variants
+    /// each on their own line.
+    ///
+    /// `<enum> ::= "enum" typeidentifier newlines indentincr (<variant> newlines)* <variant> indentdecr`
+    fn enum_definition_stmt(&mut self, current_indent: usize) -> super::Result<super::Statement> {
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::EnumKeyword, "enum keyword")?.lexeme.span.start;
+
+        let identifier = self.consume_type_identifier("enum type identifier")?.0;
+        let variants = self.indented_lines(current_indent + 1, "increase indent for start of enum variants", Self::parse_variant)?;
+
+        Ok(super::Statement::EnumDefinition { span: self.span_from(start), identifier, variants })
+    }
+
+    /// Parse a match statement, made up of an expression followed by one or
+    /// more arms each on their own line.
+    ///
+    /// `<match> ::= "match" <expr> newlines indentincr (<arm> newlines)* <arm> indentdecr`
+    fn match_stmt(&mut self, current_indent: usize) -> super::Result<super::Statement> {
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::MatchKeyword, "match keyword")?.lexeme.span.start;
+
+        let expr = self.expression()?;
+
+        let arms_indent = current_indent + 1;
+        let arms = self.indented_lines(arms_indent, "increase indent for start of match arms", |prsr| {
+            let (variant, variant_span) = prsr.consume_type_identifier("enum variant identifier")?;
+            let bindings = prsr.optional_bracketed_list(Self::parse_parameter)?;
+            let block = prsr.block(arms_indent)?;
+
+            Ok(super::MatchArm { variant, bindings, block, span: prsr.span_from(variant_span.start) })
+        })?;
+
+        Ok(super::Statement::Match { span: self.span_from(start), expr, arms })
+    }
+
+    /// Determine whether the open bracket token that is next in the stream
+    /// begins the parameters of a function definition rather than the
+    /// arguments of a function call. A definition either has parameters
+    /// (which begin with a type identifier followed by an identifier) or has
+    /// its closing bracket immediately followed by a return type or a block.
+    fn function_definition_follows(&mut self, current_indent: usize) -> bool {
+        let tok_type_at = |prsr: &mut Self, n| prsr.peek_nth_token(n).map(|tok| tok.tok_type.clone());
+
+        match tok_type_at(self, 1) {
+            Some(lexer::TokenType::TypeIdentifier(_)) =>
+                matches!(tok_type_at(self, 2), Some(lexer::TokenType::Identifier(_))),
+
+            Some(lexer::TokenType::BracketClose) => match tok_type_at(self, 2) {
+                Some(lexer::TokenType::Arrow) => true,
+                // A block is indented further than the current statement:
+                Some(lexer::TokenType::Newline(indent)) => indent > current_indent,
+                _ => false
+            },
+
+            _ => false
+        }
+    }
+
+    /// Parse a function call statement. The function name identifier (which
+    /// starts at the given position) is assumed to have already been consumed.
+    /// Any value returned by the function is discarded.
+    ///
+    /// `<call> ::= identifier "(" <exprs>? ")"`
+    fn function_call_stmt(&mut self, identifier: String, start: stream::Position) -> super::Result<super::Statement> {
+        let args = self.function_call_args()?;
+
+        Ok(super::Statement::FunctionCall { span: self.span_from(start), identifier, args })
+    }
+
     /// Parse a variable declaration statement that may optionally include an
     /// initial assignment value for that variable.
     ///
     /// `<declaration> ::= <type> identifier ("=" <expr>)?`
     fn variable_declaration_stmt(&mut self) -> super::Result<super::Statement> {
-        let var_type = self.consume_type_identifier("variable type")?;
-        let (identifier, _) = self.consume_identifier("variable identifier")?;
+        let (var_type, type_span) = self.consume_type_identifier("variable type")?;
+        let identifier = self.consume_identifier("variable identifier")?.0;
 
         // Variable declaration can optionally include a value for said variable:
         let value = if self.consume_token_if_type(&lexer::TokenType::Equals, "").unwrap_or(None).is_some() {
@@ -216,47 +459,183 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
         }
         else { None };
 
-        Ok(super::Statement::VariableDeclaration { var_type, identifier, value })
+        Ok(super::Statement::VariableDeclaration { span: self.span_from(type_span.start), var_type, identifier, value })
+    }
+
+    /// Parse the declaration of a variable whose type is inferred from the
+    /// initial value it must be given.
+    ///
+    /// `<inferred_declaration> ::= "let" identifier "=" <expr>`
+    fn inferred_declaration_stmt(&mut self) -> super::Result<super::Statement> {
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::LetKeyword, "let keyword")?.lexeme.span.start;
+        let identifier = self.consume_identifier("variable identifier")?.0;
+
+        if self.consume_token_if_type(&lexer::TokenType::Equals, "").unwrap_or(None).is_none() {
+            return Err(super::Failure::MissingInitialiser { identifier, span: self.span_from(start) });
+        }
+
+        let value = self.expression()?;
+
+        Ok(super::Statement::InferredDeclaration { span: self.span_from(start), identifier, value })
+    }
+
+    /// Parse the declaration of an array with a fixed number of elements.
+    ///
+    /// `<array_declaration> ::= "[" <type> ";" integer "]" identifier`
+    fn array_declaration_stmt(&mut self) -> super::Result<super::Statement> {
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::SquareBracketOpen, "open square bracket [ token")?.lexeme.span.start;
+        let element_type = self.consume_type_identifier("array element type")?.0;
+        self.consume_token_of_expected_type(&lexer::TokenType::Semicolon, "semicolon ; token following array element type")?;
+
+        let length_tok = self.consume_token("array length")?;
+        let length = match length_tok.tok_type {
+            lexer::TokenType::IntegerLiteral(value) => value as usize,
+            _ => return Err(super::Failure::UnexpectedToken(length_tok, "integer array length"))
+        };
+
+        self.consume_token_of_expected_type(&lexer::TokenType::SquareBracketClose, "closing square bracket ] token")?;
+        let identifier = self.consume_identifier("array identifier")?.0;
+
+        Ok(super::Statement::ArrayDeclaration { span: self.span_from(start), element_type, length, identifier })
     }
 
     /// Parse a variable assignment statement. The identifier token is already
     /// assumed to have been consumed and the identifier string from said token
-    /// passed to this method.
+    /// (along with the position at which it starts) passed to this method.
     ///
     /// `<assignment> ::= identifier "=" <expr>`
-    fn assignment_stmt(&mut self, identifier: String) -> super::Result<super::Statement> {
+    fn assignment_stmt(&mut self, identifier: String, start: stream::Position) -> super::Result<super::Statement> {
         self.consume_token_of_expected_type(&lexer::TokenType::Equals, "equals = after identifier")?;
+        let assign_to = self.expression()?;
 
-        Ok(super::Statement::VariableAssignment {
-            identifier,
-            assign_to: self.expression()?
-        })
+        Ok(super::Statement::VariableAssignment { span: self.span_from(start), identifier, assign_to })
+    }
+
+    /// Parse an assignment to a single element of an array. The identifier
+    /// token is assumed to have already been consumed.
+    ///
+    /// `<assignment> ::= identifier "[" <expr> "]" "=" <expr>`
+    fn element_assignment_stmt(&mut self, identifier: String, start: stream::Position) -> super::Result<super::Statement> {
+        let index = self.array_index()?;
+        self.consume_token_of_expected_type(&lexer::TokenType::Equals, "equals = after array index")?;
+        let assign_to = self.expression()?;
+
+        Ok(super::Statement::ElementAssignment { span: self.span_from(start), identifier, index, assign_to })
+    }
+
+    /// Parse an assignment to a field of a record (which may itself be a field
+    /// of a record, and so on). The identifier token is assumed to have already
+    /// been consumed.
+    ///
+    /// `<assignment> ::= identifier ("." identifier)+ "=" <expr>`
+    fn field_assignment_stmt(&mut self, identifier: String, start: stream::Position) -> super::Result<super::Statement> {
+        let mut fields = Vec::new();
+
+        while self.consume_token_if_type(&lexer::TokenType::Dot, "field access")?.is_some() {
+            fields.push(self.consume_identifier("field identifier")?.0);
+        }
+
+        self.consume_token_of_expected_type(&lexer::TokenType::Equals, "equals = after field identifier")?;
+        let assign_to = self.expression()?;
+
+        Ok(super::Statement::FieldAssignment { span: self.span_from(start), identifier, fields, assign_to })
     }
 
     /// Parse a function return statement.
     ///
     /// `<return> ::= "return" <expr>?`
     fn return_stmt(&mut self) -> super::Result<super::Statement> {
-        self.consume_token_of_expected_type(&lexer::TokenType::ReturnKeyword, "return keyword")?;
+        let keyword_span = self.consume_token_of_expected_type(&lexer::TokenType::ReturnKeyword, "return keyword")?.lexeme.span;
 
-        Ok(super::Statement::Return(self.expression().ok()))
+        match self.expression().ok() {
+            Some(expr) => Ok(super::Statement::Return(self.span_from(keyword_span.start), Some(expr))),
+            None => Ok(super::Statement::Return(keyword_span, None))
+        }
     }
 
     /// Display the resulting value of an expression.
     ///
     /// `<display> ::= "display" <expr>`
     fn display_stmt(&mut self) -> super::Result<super::Statement> {
-        self.consume_token_of_expected_type(&lexer::TokenType::DisplayKeyword, "display keyword")?;
+        let start = self.consume_token_of_expected_type(&lexer::TokenType::DisplayKeyword, "display keyword")?.lexeme.span.start;
+        let expr = self.expression()?;
 
-        Ok(super::Statement::Display(self.expression()?))
+        Ok(super::Statement::Display(self.span_from(start), expr))
     }
 
     /// `<param> ::= <type> identifier`
     fn parse_parameter(&mut self) -> super::Result<super::Parameter> {
-        let param_type = self.consume_type_identifier("function parameter type")?;
-        let (identifier, pos) = self.consume_identifier("function parameter identifier")?;
+        let (param_type, type_span) = self.consume_type_identifier("function parameter type")?;
+        let (identifier, identifier_span) = self.consume_identifier("function parameter identifier")?;
+
+        Ok(super::Parameter { param_type, identifier, span: type_span.to(&identifier_span) })
+    }
+
+    /// `<field> ::= typeidentifier identifier`
+    fn parse_field(&mut self) -> super::Result<super::Field> {
+        let (field_type, type_span) = self.consume_type_identifier("field type")?;
+        let (identifier, identifier_span) = self.consume_identifier("field identifier")?;
+
+        Ok(super::Field { field_type, identifier, span: type_span.to(&identifier_span) })
+    }
+
+    /// `<variant> ::= typeidentifier ("(" <field> ("," <field>)* ")")?`
+    fn parse_variant(&mut self) -> super::Result<super::Variant> {
+        let (identifier, identifier_span) = self.consume_type_identifier("enum variant identifier")?;
+        let fields = self.optional_bracketed_list(Self::parse_field)?;
+
+        Ok(super::Variant { identifier, fields, span: self.span_from(identifier_span.start) })
+    }
+
+    /// Parse a comma-seperated list of one or more items enclosed in brackets,
+    /// should an open bracket token be next in the stream.
+    fn optional_bracketed_list<I>(&mut self, parse_item: fn(&mut Self) -> super::Result<I>) -> super::Result<Vec<I>> {
+        let mut items = Vec::new();
+
+        if self.consume_token_if_type(&lexer::TokenType::BracketOpen, "").unwrap_or(None).is_some() {
+            loop {
+                items.push(parse_item(self)?);
+
+                if self.consume_token_if_type(&lexer::TokenType::Comma, "comma , token to seperate items")?.is_none() { break }
+            }
 
-        Ok(super::Parameter { param_type, identifier, pos })
+            self.consume_token_of_expected_type(&lexer::TokenType::BracketClose, "close bracket ) token")?;
+        }
+
+        Ok(items)
+    }
+
+    /// Parse one or more lines at the given indentation level (as with the
+    /// statements of a block), each of which is parsed by the given function.
+    fn indented_lines<I>(&mut self, lines_indent: usize, failure_msg: &'static str,
+                         mut parse_line: impl FnMut(&mut Self) -> super::Result<I>) -> super::Result<Vec<I>> {
+        self.consume_token_of_expected_type(&lexer::TokenType::Newline(lines_indent), failure_msg)?;
+
+        let mut lines = Vec::new();
+
+        loop {
+            lines.push(parse_line(self)?);
+
+            match self.peek_token("") {
+                Ok(lexer::Token { tok_type: lexer::TokenType::Newline(indent), lexeme }) => {
+                    if *indent == lines_indent { let _ = self.consume_token(""); }
+                    else if *indent < lines_indent { break }
+                    else {
+                        return Err(super::Failure::UnexpectedIndent {
+                            expected_indent: lines_indent,
+                            encountered_indent: *indent,
+                            span: lexeme.indentation_span()
+                        });
+                    }
+                }
+
+                Ok(_) | Err(super::Failure::UnexpectedStreamEnd(_)) => break,
+
+                Err(x) => return Err(x)
+            }
+        }
+
+        Ok(lines)
     }
 
     /// Parse a block (a collection of one or more sequential statements that
@@ -284,8 +663,27 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
         loop {
             log::trace!("Adding new statment to block");
 
-            let stmt = self.statement(block_indent, "statement contained in block")?;
-            stmts.push(stmt);
+            // A statement that fails to parse is left out of the block with
+            // the failure being yielded once the current top-level statement
+            // has been parsed:
+            let after = self.previous_end.clone();
+
+            match self.statement(block_indent, "statement contained in block") {
+                Ok(stmt) => stmts.push(stmt),
+                Err(failure) => {
+                    log::info!("Failed to parse statement in block due to error: {}", failure);
+                    self.skip_to_line_at_indent(&failure, block_indent);
+                    self.push_failure(failure, &after);
+                }
+            }
+
+            // Anything other than a newline following a statement is unexpected:
+            let newline_follows = matches!(self.peek_nth_token(0), None | Some(lexer::Token { tok_type: lexer::TokenType::Newline(_), .. }));
+            if !newline_follows {
+                let failure = super::Failure::UnexpectedToken(self.consume_token("").unwrap(), "newline following statement");
+                self.skip_to_line_at_indent(&failure, block_indent);
+                self.push_failure(failure, &after);
+            }
 
             match self.peek_token("") {
                 Ok(lexer::Token { tok_type: lexer::TokenType::Newline(indent), lexeme }) => {
@@ -303,7 +701,7 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
                         return Err(super::Failure::UnexpectedIndent {
                             expected_indent: block_indent,
                             encountered_indent: *indent,
-                            pos: lexeme.pos.clone()
+                            span: lexeme.indentation_span()
                         });
                     }
                 }
@@ -325,44 +723,75 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
     /// 'multiplcation', etc. in grammar file). This method is present to reduce
     /// the amount of repeated code required.
     fn left_right_expr(&mut self, sub_expr_func: fn(&mut Self) -> super::Result<super::Expression>,
-    seperators: &[(lexer::TokenType, fn(Box<super::Expression>, Box<super::Expression>) -> super::Expression)])
+    seperators: &[(lexer::TokenType, MakeExprFunc)])
     -> super::Result<super::Expression> {
-        let mut expr = sub_expr_func(self);
-        
-        for (seperating_tok_type, make_expr_func) in seperators {
-            if self.consume_token_if_type(seperating_tok_type, "").unwrap_or(None).is_some() {
-                let left = Box::new(expr?);
-                let right = Box::new(sub_expr_func(self)?);
-                
-                expr = Ok(make_expr_func(left, right));
+        let start = self.next_token_start();
+        let mut expr = sub_expr_func(self)?;
+
+        // Continue for as long as a seperating token follows (operators at the
+        // same level of precedence are left associative):
+        'outer: loop {
+            for (seperating_tok_type, make_expr_func) in seperators {
+                if self.consume_token_if_type(seperating_tok_type, "").unwrap_or(None).is_some() {
+                    let right = sub_expr_func(self)?;
+                    expr = make_expr_func(self.span_from(start.clone()), Box::new(expr), Box::new(right));
+
+                    continue 'outer;
+                }
             }
+            break;
         }
 
-        expr
+        Ok(expr)
     }
 
     /// Parse a TILL expression. Will return Failure should the token stream be
     /// at its end or if an expected token is encountered.
     ///
-    /// `<expr> ::= <comparison> ("==" <comparison>)*`
+    /// `<expr> ::= <conjunction> ("or" <conjunction>)*`
     fn expression(&mut self) -> super::Result<super::Expression> {
         log::trace!("Parsing expression...");
 
+        self.left_right_expr(
+            Self::conjunction_expr,
+            &[(lexer::TokenType::OrKeyword, |span, l, r| super::Expression::Or(span, l, r))]
+        )
+    }
+
+    /// `<conjunction> ::= <equality> ("and" <equality>)*`
+    fn conjunction_expr(&mut self) -> super::Result<super::Expression> {
+        self.left_right_expr(
+            Self::equality_expr,
+            &[(lexer::TokenType::AndKeyword, |span, l, r| super::Expression::And(span, l, r))]
+        )
+    }
+
+    /// `<equality> ::= <comparison> (("=="|"!=") <comparison>)*`
+    fn equality_expr(&mut self) -> super::Result<super::Expression> {
         self.left_right_expr(
             Self::comparison_expr,
-            &[(lexer::TokenType::DoubleEquals, |l, r| super::Expression::Equal(l, r))]
+            &[
+                (lexer::TokenType::DoubleEquals,
+                |span, l, r| super::Expression::Equal(span, l, r)),
+                (lexer::TokenType::NotEquals,
+                |span, l, r| super::Expression::NotEqual(span, l, r))
+            ]
         )
     }
 
-    /// `<comparison> ::= <addition> (("<"|">") <addition>)*`
+    /// `<comparison> ::= <addition> (("<"|">"|"<="|">=") <addition>)*`
     fn comparison_expr(&mut self) -> super::Result<super::Expression> {
         self.left_right_expr(
             Self::addition_expr,
             &[
                 (lexer::TokenType::GreaterThan,
-                |l, r| super::Expression::GreaterThan(l, r)),
+                |span, l, r| super::Expression::GreaterThan(span, l, r)),
                 (lexer::TokenType::LessThan,
-                |l, r| super::Expression::LessThan(l, r))
+                |span, l, r| super::Expression::LessThan(span, l, r)),
+                (lexer::TokenType::GreaterThanOrEqual,
+                |span, l, r| super::Expression::GreaterThanOrEqual(span, l, r)),
+                (lexer::TokenType::LessThanOrEqual,
+                |span, l, r| super::Expression::LessThanOrEqual(span, l, r))
             ]
         )
     }
@@ -373,44 +802,104 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
             Self::multiplication_expr,
             &[
                 (lexer::TokenType::Plus,
-                |l, r| super::Expression::Add(l, r)),
+                |span, l, r| super::Expression::Add(span, l, r)),
                 (lexer::TokenType::Minus,
-                |l, r| super::Expression::Subtract(l, r))
+                |span, l, r| super::Expression::Subtract(span, l, r))
             ]
         )
     }
 
-    /// `<multiplication> ::= <unary> (("*"|"/") <unary>)*`
+    /// `<multiplication> ::= <power> (("*"|"/"|"%") <power>)*`
     fn multiplication_expr(&mut self) -> super::Result<super::Expression> {
         self.left_right_expr(
-            Self::unary_expr,
+            Self::power_expr,
             &[
                 (lexer::TokenType::Star,
-                |l, r| super::Expression::Multiply(l, r)),
+                |span, l, r| super::Expression::Multiply(span, l, r)),
                 (lexer::TokenType::Slash,
-                |l, r| super::Expression::Divide(l, r))
+                |span, l, r| super::Expression::Divide(span, l, r)),
+                (lexer::TokenType::Percent,
+                |span, l, r| super::Expression::Modulo(span, l, r))
             ]
         )
     }
 
-    /// `<unary> ::= ("!"|"~") <unary> | <primary>`
+    /// Parse a power expression. Unlike the other binary operators, `^` is
+    /// right-associative so `2 ^ 3 ^ 2` is parsed as `2 ^ (3 ^ 2)`.
+    ///
+    /// `<power> ::= <unary> ("^" <power>)?`
+    fn power_expr(&mut self) -> super::Result<super::Expression> {
+        let start = self.next_token_start();
+        let base = self.unary_expr()?;
+
+        if self.consume_token_if_type(&lexer::TokenType::Caret, "").unwrap_or(None).is_some() {
+            let exponent = self.power_expr()?;
+            Ok(super::Expression::Power(self.span_from(start), Box::new(base), Box::new(exponent)))
+        }
+        else { Ok(base) }
+    }
+
+    /// Parse a unary expression. As the magnitude of the minimum Int is one
+    /// greater than the maximum Int, the minimum Int may only be written as the
+    /// unary minus operator applied to just an integer literal.
+    ///
+    /// `<unary> ::= ("!"|"~") <unary> | <member>`
     fn unary_expr(&mut self) -> super::Result<super::Expression> {
-        if self.consume_token_if_type(&lexer::TokenType::Tilde, "unary expression")?.is_some() {
-            Ok(super::Expression::UnaryMinus(Box::new(self.expression()?)))
+        if let Some(tok) = self.consume_token_if_type(&lexer::TokenType::Tilde, "unary expression")? {
+            if self.negated_min_int_follows() {
+                self.consume_token("integer literal")?;
+                return Ok(super::Expression::IntegerLiteral { value: i64::MIN, span: self.span_from(tok.lexeme.span.start) });
+            }
+
+            let expr = self.expression()?;
+            Ok(super::Expression::UnaryMinus(self.span_from(tok.lexeme.span.start), Box::new(expr)))
         }
-        else if self.consume_token_if_type(&lexer::TokenType::ExclaimationMark, "unary expression")?.is_some() {
-            Ok(super::Expression::BooleanNot(Box::new(self.expression()?)))
+        else if let Some(tok) = self.consume_token_if_type(&lexer::TokenType::ExclaimationMark, "unary expression")? {
+            let expr = self.expression()?;
+            Ok(super::Expression::BooleanNot(self.span_from(tok.lexeme.span.start), Box::new(expr)))
         }
-        else { self.primary_expr() }
+        else { self.member_expr() }
+    }
+
+    /// Is the next token the integer literal 9223372036854775808 with nothing
+    /// else following it in the same expression?
+    fn negated_min_int_follows(&mut self) -> bool {
+        let literal_follows = matches!(
+            self.peek_nth_token(0),
+            Some(lexer::Token { tok_type: lexer::TokenType::IntegerLiteral(value), .. }) if *value == i64::MIN.unsigned_abs()
+        );
+
+        literal_follows && matches!(
+            self.peek_nth_token(1).map(|tok| &tok.tok_type),
+            None | Some(lexer::TokenType::Newline(_) | lexer::TokenType::BracketClose |
+                        lexer::TokenType::SquareBracketClose | lexer::TokenType::Comma)
+        )
+    }
+
+    /// Parse a primary expression followed by the accessing of any number of
+    /// record fields.
+    ///
+    /// `<member> ::= <primary> ("." identifier)*`
+    fn member_expr(&mut self) -> super::Result<super::Expression> {
+        let start = self.next_token_start();
+        let mut expr = self.primary_expr()?;
+
+        while self.consume_token_if_type(&lexer::TokenType::Dot, "").unwrap_or(None).is_some() {
+            let (field, _) = self.consume_identifier("field identifier")?;
+            expr = super::Expression::FieldAccess { span: self.span_from(start.clone()), record: Box::new(expr), field };
+        }
+
+        Ok(expr)
     }
 
     /// Parse a primary expression (a literal, expression enclosed in brackets,
     /// or variable identifier).
     ///
     /// ```
-    /// <primary> ::= number | string | character | "true" | "false"
+    /// <primary> ::= integer | number | string | character | "true" | "false"
     ///             | "[" <exprs>? "]" | "(" <expr> ")"
-    ///             | identifier ("(" <exprs>? ")")?
+    ///             | identifier ("(" <exprs>? ")" | "[" <expr> "]")?
+    ///             | typeidentifier ("(" <exprs>? ")")?
     /// ```
     fn primary_expr(&mut self) -> super::Result<super::Expression> {
         let tok = self.consume_token("primary expression")?;
@@ -428,34 +917,76 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
             lexer::TokenType::Identifier(identifier) => {
                 // If open bracket follows identifier, then this must be a function
                 // call:
-                if self.consume_token_if_type(&lexer::TokenType::BracketOpen, "primary expression").unwrap_or(None).is_some() {
-                    let args = if self.check_type_of_peeked_token(&lexer::TokenType::BracketClose, "function call")? {
-                        vec![] // Closing bracket immediately following an opening
-                               // bracket indicates a function taking no arguments.
-                    }
-                    else { self.expressions()? };
-
-                    self.consume_token_of_expected_type(&lexer::TokenType::BracketClose, "function call closing bracket ) token")?;
-
-                    Ok(super::Expression::FunctionCall {
-                        args, identifier,
-                        pos: tok.lexeme.pos
-                    })
+                if self.check_type_of_peeked_token(&lexer::TokenType::BracketOpen, "").unwrap_or(false) {
+                    let args = self.function_call_args()?;
+                    Ok(super::Expression::FunctionCall { span: self.span_from(tok.lexeme.span.start), identifier, args })
+                }
+                // If open square bracket follows identifier, then this must be
+                // the indexing of an array:
+                else if self.check_type_of_peeked_token(&lexer::TokenType::SquareBracketOpen, "").unwrap_or(false) {
+                    let index = Box::new(self.array_index()?);
+                    Ok(super::Expression::Index { span: self.span_from(tok.lexeme.span.start), identifier, index })
                 }
                 else {
-                    Ok(super::Expression::Variable { identifier, pos: tok.lexeme.pos })
+                    Ok(super::Expression::Variable { identifier, span: tok.lexeme.span })
                 }
             }
 
-            lexer::TokenType::NumberLiteral(value) => Ok(super::Expression::NumberLiteral { value, pos: tok.lexeme.pos }),
-            lexer::TokenType::CharLiteral(value) => Ok(super::Expression::CharLiteral { value, pos: tok.lexeme.pos }),
-            lexer::TokenType::TrueKeyword => Ok(super::Expression::BooleanLiteral { value: true, pos: tok.lexeme.pos }),
-            lexer::TokenType::FalseKeyword => Ok(super::Expression::BooleanLiteral { value: false, pos: tok.lexeme.pos }),
+            // Type conversion, record construction, or enum variant construction
+            // (the brackets may be omitted when there are no arguments):
+            lexer::TokenType::TypeIdentifier(type_identifier) => {
+                let args = {
+                    if self.check_type_of_peeked_token(&lexer::TokenType::BracketOpen, "").unwrap_or(false) {
+                        self.function_call_args()?
+                    }
+                    else { vec![] }
+                };
+
+                Ok(super::Expression::Construction { args, type_identifier, span: self.span_from(tok.lexeme.span.start) })
+            }
+
+            lexer::TokenType::IntegerLiteral(value) => match i64::try_from(value) {
+                Ok(value) => Ok(super::Expression::IntegerLiteral { value, span: tok.lexeme.span }),
+                Err(_) => Err(super::Failure::IntegerOutOfRange(tok))
+            },
+            lexer::TokenType::NumberLiteral(value) => Ok(super::Expression::NumberLiteral { value, span: tok.lexeme.span }),
+            lexer::TokenType::CharLiteral(value) => Ok(super::Expression::CharLiteral { value, span: tok.lexeme.span }),
+            lexer::TokenType::StringLiteral(value) => Ok(super::Expression::StringLiteral { value, span: tok.lexeme.span }),
+            lexer::TokenType::TrueKeyword => Ok(super::Expression::BooleanLiteral { value: true, span: tok.lexeme.span }),
+            lexer::TokenType::FalseKeyword => Ok(super::Expression::BooleanLiteral { value: false, span: tok.lexeme.span }),
 
             _ => Err(super::Failure::UnexpectedToken(tok, "primary expression"))
         }
     }
 
+    /// Parse the bracketed arguments of a function call.
+    ///
+    /// `"(" <exprs>? ")"`
+    fn function_call_args(&mut self) -> super::Result<Vec<super::Expression>> {
+        self.consume_token_of_expected_type(&lexer::TokenType::BracketOpen, "function call open bracket ( token")?;
+
+        let args = if self.check_type_of_peeked_token(&lexer::TokenType::BracketClose, "function call")? {
+            vec![] // Closing bracket immediately following an opening
+                   // bracket indicates a function taking no arguments.
+        }
+        else { self.expressions()? };
+
+        self.consume_token_of_expected_type(&lexer::TokenType::BracketClose, "function call closing bracket ) token")?;
+
+        Ok(args)
+    }
+
+    /// Parse the bracketed index expression following an array identifier.
+    ///
+    /// `"[" <expr> "]"`
+    fn array_index(&mut self) -> super::Result<super::Expression> {
+        self.consume_token_of_expected_type(&lexer::TokenType::SquareBracketOpen, "open square bracket [ token")?;
+        let index = self.expression()?;
+        self.consume_token_of_expected_type(&lexer::TokenType::SquareBracketClose, "closing square bracket ] token")?;
+
+        Ok(index)
+    }
+
     /// `<exprs> ::= <expr> ("," <expr>)*`
     fn expressions(&mut self) -> super::Result<Vec<super::Expression>> {
         let mut exprs = vec![self.expression()?];
@@ -469,20 +1000,20 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
         Ok(exprs)
     }
 
-    fn consume_identifier(&mut self, msg: &'static str) -> super::Result<(String, stream::Position)> {
+    fn consume_identifier(&mut self, msg: &'static str) -> super::Result<(String, stream::Span)> {
         let tok = self.consume_token(msg)?;
 
         match tok.tok_type {
-            lexer::TokenType::Identifier(ident) => Ok((ident, tok.lexeme.pos)),
+            lexer::TokenType::Identifier(ident) => Ok((ident, tok.lexeme.span)),
             _ => Err(super::Failure::UnexpectedToken(tok, msg))
         }
     }
 
-    fn consume_type_identifier(&mut self, msg: &'static str) -> super::Result<String> {
+    fn consume_type_identifier(&mut self, msg: &'static str) -> super::Result<(String, stream::Span)> {
         let tok = self.consume_token(msg)?;
 
         match tok.tok_type {
-            lexer::TokenType::TypeIdentifier(ident) => Ok(ident),
+            lexer::TokenType::TypeIdentifier(ident) => Ok((ident, tok.lexeme.span)),
             _ => Err(super::Failure::UnexpectedToken(tok, msg))
         }
     }
@@ -491,25 +1022,28 @@ impl<T: Iterator<Item=lexer::Token>> StatementStream<T> {
 
 
 #[cfg(test)]
-#[allow(illegal_floating_point_literal_pattern)]
 mod tests {
-    use crate::{ assert_pattern, parsing, lexing::lexer, stream::Stream };
+    use crate::{ parsing, lexing::{ self, lexer }, stream::Stream };
 
     fn quick_parse(inp: &str) -> super::StatementStream<impl Iterator<Item=lexer::Token>> {
         let final_inp = inp.trim().replace("    ", "\t");
         let tokens = lexer::input(Stream::from_str(&final_inp)).map(Result::unwrap);
-        super::input(tokens)
+        super::input(tokens, Vec::new())
     }
 
     #[test]
     fn literal_primary_exprs() {
-        let mut prsr = quick_parse("10.5 true false '日'");
+        let mut prsr = quick_parse("10.5 true false '日' \"text\"");
         
-        assert_pattern!(prsr.primary_expr(), Ok(parsing::Expression::NumberLiteral { pos: _, value: 10.5 }));
-        assert_pattern!(prsr.primary_expr(), Ok(parsing::Expression::BooleanLiteral { pos: _, value: true }));
-        assert_pattern!(prsr.primary_expr(), Ok(parsing::Expression::BooleanLiteral { pos: _, value: false }));
+        assert_pattern!(prsr.primary_expr(), Ok(parsing::Expression::NumberLiteral { span: _, value: 10.5 }));
+        assert_pattern!(prsr.primary_expr(), Ok(parsing::Expression::BooleanLiteral { span: _, value: true }));
+        assert_pattern!(prsr.primary_expr(), Ok(parsing::Expression::BooleanLiteral { span: _, value: false }));
+        match prsr.primary_expr() {
+            Ok(parsing::Expression::CharLiteral { span: _, value: x }) => { assert_eq!(x, '日'); }
+            _ => panic!()
+        }
         match prsr.primary_expr() {
-            Ok(parsing::Expression::CharLiteral { pos: _, value: x }) => { assert_eq!(x, '日'); }
+            Ok(parsing::Expression::StringLiteral { span: _, value: x }) => { assert_eq!(x, "text"); }
             _ => panic!()
         }
     }
@@ -517,18 +1051,18 @@ mod tests {
     #[test]
     fn identifier_primary_exprs() {
         match quick_parse("my_func(2, true)").primary_expr() {
-            Ok(parsing::Expression::FunctionCall {pos: _, identifier, args }) => {
+            Ok(parsing::Expression::FunctionCall { span: _, identifier, args }) => {
                 assert_eq!(identifier, "my_func".to_string());
                 assert_eq!(args.len(), 2);
 
-                assert_pattern!(args[0], parsing::Expression::NumberLiteral { pos: _, value: 2.0 });
-                assert_pattern!(args[1], parsing::Expression::BooleanLiteral { pos: _, value: true });
+                assert_pattern!(args[0], parsing::Expression::IntegerLiteral { span: _, value: 2 });
+                assert_pattern!(args[1], parsing::Expression::BooleanLiteral { span: _, value: true });
             }
             _ => panic!()
         }
 
         match quick_parse("no_args()").primary_expr() {
-            Ok(parsing::Expression::FunctionCall { pos: _, identifier, args }) => {
+            Ok(parsing::Expression::FunctionCall { span: _, identifier, args }) => {
                 assert_eq!(identifier, "no_args".to_string());
                 assert!(args.is_empty());
             }
@@ -536,37 +1070,164 @@ mod tests {
         }
 
         match quick_parse("my_var").primary_expr() {
-            Ok(parsing::Expression::Variable { pos: _, identifier }) => {
+            Ok(parsing::Expression::Variable { span: _, identifier }) => {
                 assert_eq!(identifier, "my_var".to_string());
             }
             _ => panic!()
         }
     }
 
+    #[test]
+    fn construction_primary_exprs() {
+        match quick_parse("Int('a')").primary_expr() {
+            Ok(parsing::Expression::Construction { span: _, type_identifier, args }) => {
+                assert_eq!(type_identifier, "Int".to_string());
+                assert_pattern!(args.as_slice(), [parsing::Expression::CharLiteral { span: _, value: 'a' }]);
+            }
+            _ => panic!()
+        }
+
+        match quick_parse("Point(1.5, y)").primary_expr() {
+            Ok(parsing::Expression::Construction { span: _, type_identifier, args }) => {
+                assert_eq!(type_identifier, "Point".to_string());
+                assert_eq!(args.len(), 2);
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(quick_parse("Num(x + 1)").primary_expr(), Ok(parsing::Expression::Construction { .. }));
+
+        // Brackets may be omitted when there are no arguments:
+        match quick_parse("Empty + 5").primary_expr() {
+            Ok(parsing::Expression::Construction { span: _, type_identifier, args }) => {
+                assert_eq!(type_identifier, "Empty".to_string());
+                assert!(args.is_empty());
+            }
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn field_access_exprs() {
+        match quick_parse("line.start.x + 1").expression() {
+            Ok(parsing::Expression::Add(_, left, _)) => match *left {
+                parsing::Expression::FieldAccess { span: _, record, field } => {
+                    assert_eq!(field, "x".to_string());
+                    assert_pattern!(*record, parsing::Expression::FieldAccess { .. });
+                }
+                _ => panic!()
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(quick_parse("origin().x").expression(), Ok(parsing::Expression::FieldAccess { .. }));
+        assert_pattern!(quick_parse("p.").expression(), Err(parsing::Failure::UnexpectedStreamEnd(_)));
+    }
+
     #[test]
     fn test_unary_exprs() {
         match quick_parse("~10").unary_expr() {
-            Ok(parsing::Expression::UnaryMinus(expr)) => {
-                assert_pattern!(*expr, parsing::Expression::NumberLiteral { pos: _, value: 10.0 });
+            Ok(parsing::Expression::UnaryMinus(_, expr)) => {
+                assert_pattern!(*expr, parsing::Expression::IntegerLiteral { span: _, value: 10 });
             }
             _ => panic!()
         }
 
         match quick_parse("!some_bool").unary_expr() {
-            Ok(parsing::Expression::BooleanNot(expr)) => {
-                assert_pattern!(*expr, parsing::Expression::Variable { pos: _, identifier: _ });
+            Ok(parsing::Expression::BooleanNot(_, expr)) => {
+                assert_pattern!(*expr, parsing::Expression::Variable { span: _, identifier: _ });
             }
             _ => panic!()
         }
     }
 
+    #[test]
+    fn integer_range() {
+        assert_pattern!(quick_parse("9223372036854775807").expression(), Ok(parsing::Expression::IntegerLiteral { span: _, value: i64::MAX }));
+        assert_pattern!(quick_parse("~9223372036854775808").expression(), Ok(parsing::Expression::IntegerLiteral { span: _, value: i64::MIN }));
+        assert_pattern!(quick_parse("f(~9223372036854775808, 1)").expression(), Ok(parsing::Expression::FunctionCall { .. }));
+
+        // The literal may not otherwise be written, even as part of a larger
+        // operand of the unary minus operator:
+        assert_pattern!(quick_parse("9223372036854775808").expression(), Err(parsing::Failure::IntegerOutOfRange(_)));
+        assert_pattern!(quick_parse("~(9223372036854775808)").expression(), Err(parsing::Failure::IntegerOutOfRange(_)));
+        assert_pattern!(quick_parse("~9223372036854775808 + 1").expression(), Err(parsing::Failure::IntegerOutOfRange(_)));
+        assert_pattern!(quick_parse("100000000000000000000").expression(), Err(parsing::Failure::IntegerOutOfRange(_)));
+    }
+
     #[test]
     fn expr_operator_precedence() {
-        assert_pattern!(quick_parse("3 / 4 + 2").expression(), Ok(parsing::Expression::Add(_, _)));
-        assert_pattern!(quick_parse("2 + 3 - 4").expression(), Ok(parsing::Expression::Subtract(_, _)));
-        assert_pattern!(quick_parse("1 + 3 > 2").expression(), Ok(parsing::Expression::GreaterThan(_, _)));
-        assert_pattern!(quick_parse(" 1 > 2 == 3 < 4").expression(), Ok(parsing::Expression::Equal(_, _)));
-        assert_pattern!(quick_parse("3 * (4 + 2)").expression(), Ok(parsing::Expression::Multiply(_, _)));
+        assert_pattern!(quick_parse("3 / 4 + 2").expression(), Ok(parsing::Expression::Add(_, _, _)));
+        assert_pattern!(quick_parse("2 + 3 - 4").expression(), Ok(parsing::Expression::Subtract(_, _, _)));
+        assert_pattern!(quick_parse("1 + 3 > 2").expression(), Ok(parsing::Expression::GreaterThan(_, _, _)));
+        assert_pattern!(quick_parse(" 1 > 2 == 3 < 4").expression(), Ok(parsing::Expression::Equal(_, _, _)));
+        assert_pattern!(quick_parse("3 * (4 + 2)").expression(), Ok(parsing::Expression::Multiply(_, _, _)));
+        assert_pattern!(quick_parse("1 + 3 <= 2").expression(), Ok(parsing::Expression::LessThanOrEqual(_, _, _)));
+        assert_pattern!(quick_parse("1 >= 2 != 3 < 4").expression(), Ok(parsing::Expression::NotEqual(_, _, _)));
+        assert_pattern!(quick_parse("x == y and y == z").expression(), Ok(parsing::Expression::And(_, _, _)));
+        assert_pattern!(quick_parse("a and b or c and d").expression(), Ok(parsing::Expression::Or(_, _, _)));
+
+        match quick_parse("1 + 2 - 3 + 4").expression() {
+            Ok(parsing::Expression::Add(_, left, _)) => {
+                assert_pattern!(*left, parsing::Expression::Subtract(_, _, _));
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(quick_parse("2 * 3 ^ 2").expression(), Ok(parsing::Expression::Multiply(_, _, _)));
+        assert_pattern!(quick_parse("7 % 4 + 1").expression(), Ok(parsing::Expression::Add(_, _, _)));
+
+        match quick_parse("2 ^ 3 ^ 2").expression() {
+            Ok(parsing::Expression::Power(_, left, right)) => {
+                assert_pattern!(*left, parsing::Expression::IntegerLiteral { .. });
+                assert_pattern!(*right, parsing::Expression::Power(_, _, _));
+            }
+            _ => panic!()
+        }
+
+        match quick_parse("10 % 4 * 2").expression() {
+            Ok(parsing::Expression::Multiply(_, left, _)) => {
+                assert_pattern!(*left, parsing::Expression::Modulo(_, _, _));
+            }
+            _ => panic!()
+        }
+
+        match quick_parse("a or b or c").expression() {
+            Ok(parsing::Expression::Or(_, left, right)) => {
+                assert_pattern!(*left, parsing::Expression::Or(_, _, _));
+                assert_pattern!(*right, parsing::Expression::Variable { .. });
+            }
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let start_and_end = |span: &crate::stream::Span| (
+            span.start.line_number, span.start.line_position,
+            span.end.line_number, span.end.line_position
+        );
+
+        match quick_parse("(x + 1) * f(2)").expression() {
+            Ok(parsing::Expression::Multiply(span, left, right)) => {
+                assert_eq!(start_and_end(&span), (1, 1, 1, 14));
+
+                match (*left, *right) {
+                    (parsing::Expression::Add(left_span, ..), parsing::Expression::FunctionCall { span: right_span, .. }) => {
+                        assert_eq!(start_and_end(&left_span), (1, 2, 1, 6));
+                        assert_eq!(start_and_end(&right_span), (1, 11, 1, 14));
+                    }
+                    _ => panic!()
+                }
+            }
+            _ => panic!()
+        }
+
+        // Statements span any blocks they contain:
+        match quick_parse("while x\n    display x\n    x = 1\ndisplay y").next() {
+            Some(Ok(stmt @ parsing::Statement::While { .. })) => assert_eq!(start_and_end(stmt.span()), (1, 1, 3, 6)),
+            _ => panic!()
+        }
     }
 
     #[test]
@@ -574,9 +1235,9 @@ mod tests {
         let mut prsr = quick_parse("x = 10\nx =");
         
         match prsr.next().unwrap() {
-            Ok(parsing::Statement::VariableAssignment { identifier, assign_to }) => {
+            Ok(parsing::Statement::VariableAssignment { identifier, assign_to, .. }) => {
                 assert_eq!(identifier, "x".to_string());
-                assert_pattern!(assign_to, parsing::Expression::NumberLiteral { pos: _, value: 10.0 });
+                assert_pattern!(assign_to, parsing::Expression::IntegerLiteral { span: _, value: 10 });
             }
             _ => panic!()
         }
@@ -590,7 +1251,7 @@ mod tests {
     fn variable_declaration_stmts() {
         match quick_parse("Char x").next().unwrap() {
             Ok(parsing::Statement::VariableDeclaration {
-                value: None, var_type, identifier
+                value: None, var_type, identifier, ..
             }) => {
                 assert_eq!(identifier, "x".to_string());
                 assert_eq!(var_type, "Char".to_string());
@@ -600,8 +1261,8 @@ mod tests {
 
         match quick_parse("Num x = 2.5\n\n").next().unwrap() {
             Ok(parsing::Statement::VariableDeclaration {
-                value: Some(parsing::Expression::NumberLiteral { pos: _, value: 2.5}),
-                var_type, identifier
+                value: Some(parsing::Expression::NumberLiteral { span: _, value: 2.5}),
+                var_type, identifier, ..
             }) => {
                 assert_eq!(identifier, "x".to_string());
                 assert_eq!(var_type, "Num".to_string());
@@ -610,6 +1271,153 @@ mod tests {
         }
     }
 
+    #[test]
+    fn inferred_declaration_stmts() {
+        match quick_parse("let total = x + 1").next().unwrap() {
+            Ok(parsing::Statement::InferredDeclaration { identifier, value: parsing::Expression::Add(..), .. }) => {
+                assert_eq!(identifier, "total".to_string());
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(quick_parse("let x\n").next().unwrap(), Err(parsing::Failure::MissingInitialiser { .. }));
+        assert_pattern!(quick_parse("let x").next().unwrap(), Err(parsing::Failure::MissingInitialiser { .. }));
+        assert_pattern!(quick_parse("let Int x = 5").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+    }
+
+    #[test]
+    fn array_stmts() {
+        let mut prsr = quick_parse("[Num; 10] values\nvalues[i + 1] = values[i]");
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::ArrayDeclaration { span: _, element_type, length, identifier }) => {
+                assert_eq!(element_type, "Num".to_string());
+                assert_eq!(length, 10);
+                assert_eq!(identifier, "values".to_string());
+            }
+            _ => panic!()
+        }
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::ElementAssignment { span: _, identifier, index, assign_to }) => {
+                assert_eq!(identifier, "values".to_string());
+                assert_pattern!(index, parsing::Expression::Add(_, _, _));
+
+                match assign_to {
+                    parsing::Expression::Index { span: _, identifier, index } => {
+                        assert_eq!(identifier, "values".to_string());
+                        assert_pattern!(*index, parsing::Expression::Variable { .. });
+                    }
+                    _ => panic!()
+                }
+            }
+            _ => panic!()
+        }
+
+        assert!(prsr.next().is_none());
+
+        assert_pattern!(quick_parse("[Num; x] values").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+        assert_pattern!(quick_parse("[Num 10] values").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+    }
+
+    #[test]
+    fn record_stmts() {
+        let mut prsr = quick_parse("
+type Point
+    Num x
+    Num y
+main()
+    p.x = 2.5
+    line.end.y = p.x");
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::RecordDefinition { span: _, identifier, fields }) => {
+                assert_eq!(identifier, "Point".to_string());
+                assert_eq!(fields.iter().map(|field| (field.field_type.as_str(), field.identifier.as_str())).collect::<Vec<_>>(),
+                           vec![("Num", "x"), ("Num", "y")]);
+            }
+            _ => panic!()
+        }
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::FunctionDefinition { body, .. }) => match body.as_slice() {
+                [
+                    parsing::Statement::FieldAssignment { identifier: first, fields: first_fields, .. },
+                    parsing::Statement::FieldAssignment { identifier: second, fields: second_fields, assign_to, .. }
+                ] => {
+                    assert_eq!(first, "p");
+                    assert_eq!(first_fields, &vec!["x".to_string()]);
+                    assert_eq!(second, "line");
+                    assert_eq!(second_fields, &vec!["end".to_string(), "y".to_string()]);
+                    assert_pattern!(assign_to, parsing::Expression::FieldAccess { .. });
+                }
+                _ => panic!()
+            }
+            _ => panic!()
+        }
+
+        assert!(prsr.next().is_none());
+
+        assert_pattern!(quick_parse("type Point\nNum x").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+        assert_pattern!(quick_parse("type point\n    Num x").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+    }
+
+    #[test]
+    fn enum_and_match_stmts() {
+        let mut prsr = quick_parse("
+enum Shape
+    Circle(Num radius)
+    Rectangle(Num width, Num height)
+    Empty
+match shape
+    Circle(Num r)
+        display r
+    Empty
+        display Empty
+        display 0");
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::EnumDefinition { span: _, identifier, variants }) => {
+                assert_eq!(identifier, "Shape".to_string());
+                assert_eq!(
+                    variants.iter().map(|variant| (variant.identifier.as_str(), variant.fields.len())).collect::<Vec<_>>(),
+                    vec![("Circle", 1), ("Rectangle", 2), ("Empty", 0)]
+                );
+                assert_eq!(variants[1].fields[1].identifier, "height".to_string());
+            }
+            _ => panic!()
+        }
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::Match { expr, arms, .. }) => {
+                assert_pattern!(expr, parsing::Expression::Variable { .. });
+
+                match arms.as_slice() {
+                    [first, second] => {
+                        assert_eq!(first.variant, "Circle".to_string());
+                        assert_eq!(first.bindings.iter().map(|binding| binding.identifier.as_str()).collect::<Vec<_>>(), vec!["r"]);
+                        assert_eq!(first.block.len(), 1);
+
+                        assert_eq!(second.variant, "Empty".to_string());
+                        assert!(second.bindings.is_empty());
+                        assert_pattern!(
+                            &second.block[0],
+                            parsing::Statement::Display(_, parsing::Expression::Construction { args: _, .. })
+                        );
+                        assert_eq!(second.block.len(), 2);
+                    }
+                    _ => panic!()
+                }
+            }
+            _ => panic!()
+        }
+
+        assert!(prsr.next().is_none());
+
+        assert_pattern!(quick_parse("match x\nCircle(Num r)").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+        assert_pattern!(quick_parse("match x\n    Circle(r)\n        display r").next().unwrap(), Err(parsing::Failure::UnexpectedToken(_, _)));
+    }
+
     #[test]
     fn if_stmts() {
         let mut prsr = quick_parse("
@@ -624,23 +1432,54 @@ if x == 10
     x = 0");
 
         assert_pattern!(prsr.next().unwrap(), Ok(parsing::Statement::If {
-            condition: parsing::Expression::Equal(_, _), block: _
+            condition: parsing::Expression::Equal(_, _, _), block: _, else_block: None, ..
         }));
     }
 
+    #[test]
+    fn if_else_stmts() {
+        let mut prsr = quick_parse("
+if x == 10
+    y = 1
+elif x == 11
+    if y == 2
+        y = 3
+    else
+        y = 4
+else
+    y = 5
+x = 0");
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::If { else_block: Some(else_block), .. }) => {
+                match &else_block[..] {
+                    [parsing::Statement::If { block, else_block: Some(inner_else_block), .. }] => {
+                        assert_pattern!(block.as_slice(), [parsing::Statement::If { else_block: Some(_), .. }]);
+                        assert_pattern!(inner_else_block.as_slice(), [parsing::Statement::VariableAssignment { .. }]);
+                    }
+                    _ => panic!()
+                }
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(prsr.next().unwrap(), Ok(parsing::Statement::VariableAssignment { .. }));
+        assert!(prsr.next().is_none());
+    }
+
     #[test]
     fn while_stmts() {
         let mut prsr = quick_parse("while x < 10\n\tx = x + func(2)\n") ;
 
         assert_pattern!(prsr.next().unwrap(), Ok(parsing::Statement::While {
-            condition: parsing::Expression::LessThan(_, _), block: _
+            condition: parsing::Expression::LessThan(_, _, _), block: _, ..
         }))
     }
 
     #[test]
     fn function_parameters() {
         match quick_parse("Num my_param").parse_parameter() {
-            Ok(parsing::Parameter { param_type, identifier, pos: _ }) => {
+            Ok(parsing::Parameter { param_type, identifier, span: _ }) => {
                 assert_eq!(param_type, "Num".to_string());
                 assert_eq!(identifier, "my_param".to_string());
             }
@@ -660,7 +1499,7 @@ no_args()
 
         match prsr.next().unwrap() {
             Ok(parsing::Statement::FunctionDefinition {
-                identifier, parameters, body: _, pos: _,
+                identifier, parameters, body: _, span: _,
                 return_type: Some(_)
             }) => {
                 assert_eq!(identifier, "some_function".to_string());
@@ -671,7 +1510,7 @@ no_args()
 
         match prsr.next().unwrap() {
             Ok(parsing::Statement::FunctionDefinition {
-                identifier, parameters, body: _, pos: _,
+                identifier, parameters, body: _, span: _,
                 return_type: None
             }) => {
                 assert_eq!(identifier, "no_args".to_string());
@@ -681,18 +1520,99 @@ no_args()
         }
     }
 
+    #[test]
+    fn function_call_stmts() {
+        let mut prsr = quick_parse("
+main()
+    do_thing(5, x)
+    do_thing(Int(c))
+    no_args()
+greet()
+    no_args()");
+
+        match prsr.next().unwrap() {
+            Ok(parsing::Statement::FunctionDefinition { identifier, body, .. }) => {
+                assert_eq!(identifier, "main".to_string());
+
+                match body.as_slice() {
+                    [
+                        parsing::Statement::FunctionCall { identifier: first, args: first_args, .. },
+                        parsing::Statement::FunctionCall { args: conversion_args, .. },
+                        parsing::Statement::FunctionCall { identifier: second, args: second_args, .. }
+                    ] => {
+                        assert_eq!(first, "do_thing");
+                        assert_eq!(first_args.len(), 2);
+                        assert_pattern!(conversion_args.as_slice(), [parsing::Expression::Construction { .. }]);
+                        assert_eq!(second, "no_args");
+                        assert!(second_args.is_empty());
+                    }
+                    _ => panic!()
+                }
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(prsr.next().unwrap(), Ok(parsing::Statement::FunctionDefinition { .. }));
+        assert!(prsr.next().is_none());
+    }
+
     #[test]
     fn return_stmts() {
-        assert_eq!(
-            quick_parse("return\n\n").next().unwrap(),
-            Ok(parsing::Statement::Return(None))
-        );
+        assert_pattern!(quick_parse("return\n\n").next().unwrap(), Ok(parsing::Statement::Return(_, None)));
 
         match quick_parse("return 2.5").next().unwrap() {
-            Ok(parsing::Statement::Return(Some(
-                parsing::Expression::NumberLiteral { pos: _, value: 2.5 }
+            Ok(parsing::Statement::Return(_, Some(
+                parsing::Expression::NumberLiteral { span: _, value: 2.5 }
             ))) => {}
             _ => panic!()
         }
     }
+
+    #[test]
+    fn recovery_from_failures() {
+        let mut prsr = quick_parse("main()\n    display 1 +\n    display 2\n    Num = 3\n    display 4 5\nhelper(\nx = 5");
+
+        // Statements in a block that fail to parse are left out of that block:
+        match prsr.next() {
+            Some(Ok(parsing::Statement::FunctionDefinition { body, .. })) => {
+                assert_eq!(body.len(), 2);
+                assert_pattern!(body[0], parsing::Statement::Display(_, parsing::Expression::IntegerLiteral { span: _, value: 2 }));
+                assert_pattern!(body[1], parsing::Statement::Display(_, parsing::Expression::IntegerLiteral { span: _, value: 4 }));
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(prsr.next(), Some(Err(parsing::Failure::UnexpectedToken(lexer::Token { tok_type: lexer::TokenType::Newline(1), .. }, _))));
+        assert_pattern!(prsr.next(), Some(Err(parsing::Failure::UnexpectedToken(lexer::Token { tok_type: lexer::TokenType::Equals, .. }, _))));
+        assert_pattern!(prsr.next(), Some(Err(parsing::Failure::UnexpectedToken(lexer::Token { tok_type: lexer::TokenType::IntegerLiteral(5), .. }, _))));
+        assert_pattern!(prsr.next(), Some(Err(parsing::Failure::UnexpectedToken(lexer::Token { tok_type: lexer::TokenType::Newline(0), .. }, _))));
+        assert_pattern!(prsr.next(), Some(Ok(parsing::Statement::VariableAssignment { .. })));
+        assert_pattern!(prsr.next(), None);
+
+        // The remainder of a string literal containing a lexical error is
+        // skipped, and no failure is yielded for a statement containing a
+        // lexical error:
+        let inp = "main()\n\tdisplay \"a\\qb\"\n\tdisplay \"\\x01é\"\n\tdisplay $\n\tdisplay 1 $ 2\n\tdisplay 3\n\tdisplay 4 +";
+        let (tokens, lexical_failures): (Vec<_>, Vec<_>) = lexer::input(Stream::from_str(inp)).partition(Result::is_ok);
+
+        let lexical_failure_spans: Vec<_> = lexical_failures.into_iter().map(|failure| match failure {
+            Err(lexing::Failure::UnexpectedChar(chr, lexeme)) => (chr, lexeme.span),
+            _ => panic!()
+        }).collect();
+        assert_eq!(lexical_failure_spans.iter().map(|(chr, _)| *chr).collect::<Vec<_>>(), vec!['q', 'x', '$', '$']);
+
+        let mut prsr = super::input(tokens.into_iter().map(Result::unwrap), lexical_failure_spans.into_iter().map(|(_, span)| span).collect());
+
+        match prsr.next() {
+            Some(Ok(parsing::Statement::FunctionDefinition { body, .. })) => {
+                assert_eq!(body.len(), 2);
+                assert_pattern!(body[0], parsing::Statement::Display(_, parsing::Expression::IntegerLiteral { span: _, value: 1 }));
+                assert_pattern!(body[1], parsing::Statement::Display(_, parsing::Expression::IntegerLiteral { span: _, value: 3 }));
+            }
+            _ => panic!()
+        }
+
+        assert_pattern!(prsr.next(), Some(Err(parsing::Failure::UnexpectedStreamEnd(_))));
+        assert_pattern!(prsr.next(), None);
+    }
 }
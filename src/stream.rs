@@ -1,10 +1,11 @@
 //! Handle the reading of a input stream (e.g. a file) a character at a time.
 
-use std::{ fs, fmt };
+use std::fmt;
 use char_stream::CharStream;
 
-/// Structure representing a given position within a stream.
-#[derive(Clone, Debug, PartialEq)]
+/// Structure representing a given position within a stream. Positions are
+/// ordered by how far into the stream they are.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Position {
     pub position: u64,
     pub line_number: u64,
@@ -23,6 +24,33 @@ impl fmt::Display for Position {
     }
 }
 
+/// The section of a stream from one position to another, inclusive of the
+/// characters at both positions.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position
+}
+
+impl Span {
+    #[cfg(test)]
+    pub fn new() -> Span {
+        Span { start: Position::new(), end: Position::new() }
+    }
+
+    /// Create a span from the start of this span to the end of another.
+    pub fn to(&self, other: &Span) -> Span {
+        Span { start: self.start.clone(), end: other.end.clone() }
+    }
+}
+
+/// A span is displayed as the position at which it starts.
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
 /// Structure that allows the reading from an input source a character at a time
 /// while tracking position without said source.
 pub struct Stream {
@@ -31,7 +59,6 @@ pub struct Stream {
 }
 
 impl Stream {
-    #[allow(dead_code)]
     pub fn from_str(s: &str) -> Stream {
         Stream {
             char_stream: CharStream::from(s),
@@ -39,13 +66,6 @@ impl Stream {
         }
     }
 
-    pub fn from_file(f: fs::File) -> Stream {
-        Stream {
-            char_stream: CharStream::from_file(f),
-            pos: Position::new()
-        }
-    }
-
     pub fn peek(&mut self) -> Option<char> {
         self.char_stream.peek()
     }
@@ -64,7 +84,14 @@ impl Stream {
         &self.pos
     }
 
-    pub fn get_pos(&self) -> &Position { &self.pos }
+    /// Get the position of the next character to be read.
+    pub fn get_next_pos(&self) -> Position {
+        Position {
+            position: self.pos.position + 1,
+            line_number: self.pos.line_number,
+            line_position: self.pos.line_position + 1
+        }
+    }
 }
 
 #[cfg(test)]
@@ -87,5 +114,7 @@ mod tests {
         assert_eq!(pos.position, 3);
         assert_eq!(pos.line_number, 2);
         assert_eq!(pos.line_position, 1);
+
+        assert_eq!(s.get_next_pos(), super::Position { position: 4, line_number: 2, line_position: 2 });
     }
 }
\ No newline at end of file